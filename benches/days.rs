@@ -0,0 +1,64 @@
+use advent_of_code::solution::Solution;
+use advent_of_code::utils::inputs::{get_day_input_path, get_file};
+use advent_of_code::{
+    day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10, day_11, day_12,
+    day_13, day_14, day_15, day_16, day_17, day_18, day_19, day_20, day_21, day_22, day_23, day_24,
+    day_25,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+
+/// Benchmark the parsing of the bundled input of a day, then each part on the parsed input.
+/// The days whose input cannot be read or parsed are skipped.
+fn bench_day<S: Solution>(c: &mut Criterion) {
+    let path = get_day_input_path(S::DAY);
+    let input = match get_file(&path) {
+        Ok(input) => input,
+        Err(error) => {
+            return eprintln!("Skipping day {}: cannot read `{}`: {}", S::DAY, path, error)
+        }
+    };
+    let parsed = match S::parse(&input) {
+        Ok(parsed) => parsed,
+        Err(error) => return eprintln!("Skipping day {}: {}", S::DAY, error.render(&path, &input)),
+    };
+
+    let mut group = c.benchmark_group(format!("day_{:02}", S::DAY));
+    group.bench_function("parse", |b| b.iter(|| S::parse(black_box(&input))));
+    group.bench_function("part_one", |b| b.iter(|| S::part_one(black_box(&parsed))));
+    group.bench_function("part_two", |b| b.iter(|| S::part_two(black_box(&parsed))));
+    group.finish();
+}
+
+criterion_group! {
+    name = days;
+    // Some parts take seconds, so keep the number of samples to the minimum
+    config = Criterion::default().sample_size(10);
+    targets =
+        bench_day::<day_01::Day01>,
+        bench_day::<day_02::Day02>,
+        bench_day::<day_03::Day03>,
+        bench_day::<day_04::Day04>,
+        bench_day::<day_05::Day05>,
+        bench_day::<day_06::Day06>,
+        bench_day::<day_07::Day07>,
+        bench_day::<day_08::Day08>,
+        bench_day::<day_09::Day09>,
+        bench_day::<day_10::Day10>,
+        bench_day::<day_11::Day11>,
+        bench_day::<day_12::Day12>,
+        bench_day::<day_13::Day13>,
+        bench_day::<day_14::Day14>,
+        bench_day::<day_15::Day15>,
+        bench_day::<day_16::Day16>,
+        bench_day::<day_17::Day17>,
+        bench_day::<day_18::Day18>,
+        bench_day::<day_19::Day19>,
+        bench_day::<day_20::Day20>,
+        bench_day::<day_21::Day21>,
+        bench_day::<day_22::Day22>,
+        bench_day::<day_23::Day23>,
+        bench_day::<day_24::Day24>,
+        bench_day::<day_25::Day25>,
+}
+criterion_main!(days);
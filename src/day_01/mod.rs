@@ -1,29 +1,40 @@
 use itertools::Itertools;
 use std::cmp::Reverse;
 
-use advent_of_code::parse_input;
-use advent_of_code::utils::inputs::{get_file, LINE_ENDING};
+use crate::solution::Solution;
+use crate::utils::inputs::{parse_chunks, parse_lines, parse_number};
+use crate::utils::parse_error::ParseError;
 
-pub fn day_01() {
-    let inputs = get_input();
+pub struct Day01;
 
-    let solution_1 = part_one(&inputs);
-    println!("\t- Solution 1 is : {}", solution_1);
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+    type Input = Vec<u32>;
+    type PartOne = u32;
+    type PartTwo = u32;
 
-    let solution_2 = part_two(&inputs);
-    println!("\t- Solution 2 is : {}", solution_2);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(elves_food: &Self::Input) -> Self::PartOne {
+        part_one(elves_food)
+    }
+
+    fn part_two(elves_food: &Self::Input) -> Self::PartTwo {
+        part_two(elves_food)
+    }
 }
 
-fn get_input() -> Vec<u32> {
-    let file = get_file("./src/day_01/input.txt");
-    let split_separator = format!("{}{}", LINE_ENDING, LINE_ENDING);
-    file.split(&split_separator)
-        .map(|chunk| chunk.lines().map(|l| parse_input!(l, u32)).sum())
-        .collect()
+fn get_input(input: &str) -> Result<Vec<u32>, ParseError> {
+    parse_chunks(input, |chunk| {
+        let food = parse_lines(chunk, |l| parse_number::<u32>(l, l))?;
+        Ok(food.iter().sum())
+    })
 }
 
-fn part_one(elves_food: &[u32]) -> &u32 {
-    elves_food.iter().max().unwrap()
+fn part_one(elves_food: &[u32]) -> u32 {
+    *elves_food.iter().max().unwrap()
 }
 
 fn part_two(elves_food: &[u32]) -> u32 {
@@ -33,3 +44,30 @@ fn part_two(elves_food: &[u32]) -> u32 {
         .take(3)
         .sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "1000
+2000
+3000
+
+4000
+
+5000
+6000
+
+7000
+8000
+9000
+
+10000";
+
+    #[test]
+    fn test_example() {
+        let input = Day01::parse(EXAMPLE).unwrap();
+        assert_eq!(Day01::part_one(&input), 24000);
+        assert_eq!(Day01::part_two(&input), 45000);
+    }
+}
@@ -1,30 +1,47 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::inputs::parse_lines;
+use crate::utils::parse_error::ParseError;
 use std::str::FromStr;
 
-pub fn day_02() {
-    let strategy_guide = get_input();
+pub struct Day02;
 
-    let solution_1 = part_one(&strategy_guide);
-    println!("\t- Solution 1 is : {}", solution_1);
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    type Input = Vec<(GameAction, String)>;
+    type PartOne = u16;
+    type PartTwo = u16;
 
-    let solution_2 = part_two(&strategy_guide);
-    println!("\t- Solution 2 is : {}", solution_2);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(strategy_guide: &Self::Input) -> Self::PartOne {
+        part_one(strategy_guide)
+    }
+
+    fn part_two(strategy_guide: &Self::Input) -> Self::PartTwo {
+        part_two(strategy_guide)
+    }
 }
 
 /// Returns a vector of tuple (GameAction, `String`), where `String` is how the round needs to end.
-fn get_input() -> Vec<(GameAction, String)> {
-    get_file("./src/day_02/input.txt")
-        .lines()
-        .map(parse_line)
-        .collect()
+fn get_input(input: &str) -> Result<Vec<(GameAction, String)>, ParseError> {
+    parse_lines(input, parse_line)
 }
 
-fn parse_line(line: &str) -> (GameAction, String) {
+fn parse_line(line: &str) -> Result<(GameAction, String), ParseError> {
     let mut moves = line.trim().split(' ');
-    (
-        GameAction::from_str(moves.next().unwrap()).unwrap(),
-        moves.next().unwrap().to_string(),
-    )
+    let op_move = moves.next().unwrap_or_default();
+    let op_move = GameAction::from_str(op_move)
+        .map_err(|_| ParseError::new(line, op_move, "`A`, `B` or `C`"))?;
+    match moves.next() {
+        Some(value @ ("X" | "Y" | "Z")) => Ok((op_move, value.to_string())),
+        value => Err(ParseError::new(
+            line,
+            value.unwrap_or_default(),
+            "`X`, `Y` or `Z`",
+        )),
+    }
 }
 
 fn part_one(strategy_guide: &[(GameAction, String)]) -> u16 {
@@ -54,7 +71,7 @@ fn part_two(strategy_guide: &[(GameAction, String)]) -> u16 {
 }
 
 #[derive(Debug)]
-enum GameAction {
+pub enum GameAction {
     Rock,
     Paper,
     Scissors,
@@ -130,3 +147,19 @@ impl GameResult {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "A Y
+B X
+C Z";
+
+    #[test]
+    fn test_example() {
+        let input = Day02::parse(EXAMPLE).unwrap();
+        assert_eq!(Day02::part_one(&input), 15);
+        assert_eq!(Day02::part_two(&input), 12);
+    }
+}
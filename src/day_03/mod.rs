@@ -1,21 +1,84 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::inputs::parse_lines;
+use crate::utils::parse_error::ParseError;
 use std::collections::HashSet;
 
-pub fn day_03() {
-    let rucksacks = get_input();
+pub struct Day03;
 
-    let solution_1 = part_one(&rucksacks);
-    println!("\t- Solution 1 is : {}", solution_1);
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    type Input = Vec<Vec<char>>;
+    type PartOne = i32;
+    type PartTwo = i32;
 
-    let solution_2 = part_two(&rucksacks);
-    println!("\t- Solution 2 is : {}", solution_2);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(rucksacks: &Self::Input) -> Self::PartOne {
+        part_one(rucksacks)
+    }
+
+    fn part_two(rucksacks: &Self::Input) -> Self::PartTwo {
+        part_two(rucksacks)
+    }
+}
+
+/// The number of rucksacks carried by each group of elves
+const GROUP_SIZE: usize = 3;
+
+fn get_input(input: &str) -> Result<Vec<Vec<char>>, ParseError> {
+    let rucksacks = parse_lines(input, parse_rucksack)?;
+    let lines: Vec<_> = input.lines().collect();
+    if lines.len() % GROUP_SIZE != 0 {
+        return Err(ParseError::new(
+            input,
+            &input[input.len()..],
+            format!("groups of {} rucksacks", GROUP_SIZE),
+        ));
+    }
+    for (group, group_lines) in rucksacks.chunks(GROUP_SIZE).zip(lines.chunks(GROUP_SIZE)) {
+        if get_badge(group).is_none() {
+            return Err(ParseError::new(
+                input,
+                group_lines[0],
+                "a group of rucksacks sharing an item",
+            ));
+        }
+    }
+    Ok(rucksacks)
 }
 
-fn get_input() -> Vec<Vec<char>> {
-    get_file("./src/day_03/input.txt")
-        .lines()
-        .map(|l| l.chars().collect())
-        .collect()
+fn parse_rucksack(line: &str) -> Result<Vec<char>, ParseError> {
+    let rucksack = line
+        .char_indices()
+        .map(|(idx, c)| {
+            if c.is_ascii_alphabetic() {
+                Ok(c)
+            } else {
+                Err(ParseError::new(
+                    line,
+                    &line[idx..idx + c.len_utf8()],
+                    "an item letter",
+                ))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if rucksack.is_empty() || rucksack.len() % 2 != 0 {
+        return Err(ParseError::new(
+            line,
+            line,
+            "a rucksack with an even number of items",
+        ));
+    }
+    if get_common_item(&rucksack).is_none() {
+        return Err(ParseError::new(
+            line,
+            line,
+            "a rucksack whose compartments share an item",
+        ));
+    }
+    Ok(rucksack)
 }
 
 fn part_one(rucksacks: &[Vec<char>]) -> i32 {
@@ -26,11 +89,17 @@ fn part_one(rucksacks: &[Vec<char>]) -> i32 {
 }
 
 fn get_rucksack_priority(rucksack: &[char]) -> i32 {
+    let common = get_common_item(rucksack).expect("Rucksacks are checked when parsed");
+    get_char_priority(&common)
+}
+
+/// Returns an item found in both compartments of the rucksack, if any
+fn get_common_item(rucksack: &[char]) -> Option<char> {
     let compartment_size = rucksack.len() / 2;
     let left_part: HashSet<_> = rucksack[0..compartment_size].iter().collect();
     let right_part: HashSet<_> = rucksack[compartment_size..].iter().collect();
-    let common = left_part.intersection(&right_part).next().unwrap();
-    get_char_priority(common)
+    let common = left_part.intersection(&right_part).next();
+    common.map(|&&item| item)
 }
 
 fn get_char_priority(ch: &char) -> i32 {
@@ -43,18 +112,57 @@ fn get_char_priority(ch: &char) -> i32 {
 }
 
 fn part_two(rucksacks: &[Vec<char>]) -> i32 {
-    let mut item_types = vec![];
-    let hashset_rucksacks: Vec<HashSet<_>> = rucksacks.iter().map(|r| r.iter().collect()).collect();
-
-    for rucksacks_chunk in hashset_rucksacks.chunks(3) {
-        let first_intersection: HashSet<_> = rucksacks_chunk[0]
-            .intersection(&rucksacks_chunk[1])
-            .map(|c| c.to_owned())
-            .collect();
-        let total_intersection = first_intersection.intersection(&rucksacks_chunk[2]);
-        item_types.push(get_char_priority(
-            total_intersection.into_iter().next().unwrap(),
-        ))
-    }
-    item_types.iter().sum()
+    rucksacks
+        .chunks(GROUP_SIZE)
+        .map(|group| get_badge(group).expect("Groups are checked when parsed"))
+        .map(|badge| get_char_priority(&badge))
+        .sum()
+}
+
+/// Returns an item carried by every rucksack of the group, if any
+fn get_badge(group: &[Vec<char>]) -> Option<char> {
+    let mut rucksacks = group
+        .iter()
+        .map(|r| r.iter().copied().collect::<HashSet<_>>());
+    let first = rucksacks.next()?;
+    rucksacks
+        .fold(first, |common, rucksack| &common & &rucksack)
+        .into_iter()
+        .next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw";
+
+    #[test]
+    fn test_example() {
+        let input = Day03::parse(EXAMPLE).unwrap();
+        assert_eq!(Day03::part_one(&input), 157);
+        assert_eq!(Day03::part_two(&input), 70);
+    }
+
+    #[test]
+    fn test_invalid_rucksacks() {
+        let error = Day03::parse("x").unwrap_err();
+        assert_eq!(error.expected, "a rucksack with an even number of items");
+        let error = Day03::parse("aa\nabcd\nbb").unwrap_err();
+        assert_eq!((error.line, error.text.as_str()), (2, "abcd"));
+        assert_eq!(
+            error.expected,
+            "a rucksack whose compartments share an item"
+        );
+        let error = Day03::parse("aa\nbb").unwrap_err();
+        assert_eq!(error.expected, "groups of 3 rucksacks");
+        let error = Day03::parse("aa\nbb\ncc").unwrap_err();
+        assert_eq!((error.line, error.text.as_str()), (1, "aa"));
+        assert!(Day03::parse("").is_ok());
+    }
 }
@@ -1,37 +1,54 @@
-use advent_of_code::parse_input;
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::inputs::{parse_lines, parse_number};
+use crate::utils::parse_error::ParseError;
 use itertools::Itertools;
 
 type Section = (u16, u16);
 type Pair = (Section, Section);
 
-pub fn day_04() {
-    let pairs = get_input();
+pub struct Day04;
 
-    let solution_1 = part_one(&pairs);
-    println!("\t- Solution 1 is : {}", solution_1);
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    type Input = Vec<Pair>;
+    type PartOne = usize;
+    type PartTwo = usize;
 
-    let solution_2 = part_two(&pairs);
-    println!("\t- Solution 2 is : {}", solution_2);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(pairs: &Self::Input) -> Self::PartOne {
+        part_one(pairs)
+    }
+
+    fn part_two(pairs: &Self::Input) -> Self::PartTwo {
+        part_two(pairs)
+    }
 }
 
-fn get_input() -> Vec<Pair> {
-    get_file("./src/day_04/input.txt")
-        .lines()
-        .map(line_to_pair)
-        .collect()
+fn get_input(input: &str) -> Result<Vec<Pair>, ParseError> {
+    parse_lines(input, line_to_pair)
 }
 
-fn line_to_pair(line: &str) -> Pair {
-    line.split(',')
+fn line_to_pair(line: &str) -> Result<Pair, ParseError> {
+    let sections: Vec<Section> = line
+        .split(',')
         .map(|s| {
-            s.split('-')
-                .map(|s2| parse_input!(s2, u16))
+            let bounds: Vec<u16> = s
+                .split('-')
+                .map(|s2| parse_number(line, s2))
+                .collect::<Result<_, _>>()?;
+            bounds
+                .into_iter()
                 .collect_tuple()
-                .unwrap()
+                .ok_or_else(|| ParseError::new(line, s, "a section like `2-4`"))
         })
+        .collect::<Result<_, _>>()?;
+    sections
+        .into_iter()
         .collect_tuple()
-        .unwrap()
+        .ok_or_else(|| ParseError::new(line, line, "a pair of sections like `2-4,6-8`"))
 }
 
 fn part_one(pairs: &[Pair]) -> usize {
@@ -59,3 +76,22 @@ fn overlap(section_a: &Section, section_b: &Section) -> bool {
 fn is_part_of(value: u16, section: &Section) -> bool {
     value >= section.0 && value <= section.1
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8";
+
+    #[test]
+    fn test_example() {
+        let input = Day04::parse(EXAMPLE).unwrap();
+        assert_eq!(Day04::part_one(&input), 2);
+        assert_eq!(Day04::part_two(&input), 4);
+    }
+}
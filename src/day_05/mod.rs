@@ -1,11 +1,13 @@
-use advent_of_code::parse_input;
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::inputs::parse_number;
+use crate::utils::parse_error::ParseError;
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::VecDeque;
 
 lazy_static! {
-    static ref RE_COLUMN_NUMBERS: Regex = Regex::new(r"(?m)^(?: (\d)  ?)+[\s\n]*$").unwrap();
+    static ref RE_COLUMN_NUMBERS: Regex =
+        Regex::new(r"(?m)^[ \t]*(?:\d+[ \t]+)*(\d+)[ \t\r]*$").unwrap();
     static ref RE_CRATE_MOVE: Regex = Regex::new(r"move (\d+) from (\d+) to (\d+)").unwrap();
     static ref RE_CRATES_POSITIONS_LINE: Regex =
         Regex::new(r"(?m)^(?:[\[ ]([A-Z ])[\] ] ?)+").unwrap();
@@ -15,52 +17,97 @@ lazy_static! {
 type Stack = VecDeque<char>;
 type Move = (usize, usize, usize);
 
-pub fn day_05() {
-    let (stacks, moves) = get_input();
-    let solution_2 = part_one(&stacks, &moves);
-    println!("\t- Solution 1 is : {}", solution_2);
+pub struct Day05;
 
-    let solution_2 = part_two(&stacks, &moves);
-    println!("\t- Solution 2 is : {}", solution_2);
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+    type Input = (Vec<Stack>, Vec<Move>);
+    type PartOne = String;
+    type PartTwo = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(input: &Self::Input) -> Self::PartOne {
+        part_one(&input.0, &input.1)
+    }
+
+    fn part_two(input: &Self::Input) -> Self::PartTwo {
+        part_two(&input.0, &input.1)
+    }
 }
 
-fn get_input() -> (Vec<Stack>, Vec<Move>) {
-    let file = get_file("./src/day_05/input.txt");
+fn get_input(file: &str) -> Result<(Vec<Stack>, Vec<Move>), ParseError> {
     let mut moves = vec![];
-    let mut stacks = vec![VecDeque::default(); get_stacks_number(&file)];
+    let stacks_number = get_stacks_number(file)?;
+    let mut stacks = vec![VecDeque::default(); stacks_number];
 
-    for crate_line_capture in RE_CRATES_POSITIONS_LINE.captures_iter(&file) {
+    for crate_line_capture in RE_CRATES_POSITIONS_LINE.captures_iter(file) {
         let crate_position_line = crate_line_capture.get(0).unwrap().as_str();
         for (i, crate_capture_value) in RE_CRATES_POSITIONS_VALUES
             .captures_iter(crate_position_line)
             .enumerate()
         {
-            let crate_value = crate_capture_value
-                .get(1)
-                .unwrap()
-                .as_str()
-                .chars()
-                .next()
-                .unwrap();
-            if !crate_value.is_whitespace() {
-                stacks[i].push_back(crate_value)
+            let crate_value = crate_capture_value.get(1).unwrap().as_str();
+            if crate_value.trim().is_empty() {
+                continue;
             }
+            if i >= stacks_number {
+                return Err(ParseError::new(
+                    file,
+                    crate_value,
+                    format!("at most {} stacks", stacks_number),
+                ));
+            }
+            stacks[i].push_back(crate_value.chars().next().unwrap())
         }
     }
 
-    for val in RE_CRATE_MOVE.captures_iter(&file) {
-        let num = parse_input!(val.get(1).unwrap().as_str(), usize);
-        let a = parse_input!(val.get(2).unwrap().as_str(), usize) - 1;
-        let b = parse_input!(val.get(3).unwrap().as_str(), usize) - 1;
+    // Both cranes leave as many crates on each stack, so moves can be checked once
+    let mut heights: Vec<usize> = stacks.iter().map(VecDeque::len).collect();
+    for val in RE_CRATE_MOVE.captures_iter(file) {
+        let num_match = val.get(1).unwrap().as_str();
+        let num = parse_number(file, num_match)?;
+        let a = get_stack_idx(file, val.get(2).unwrap().as_str(), stacks_number)?;
+        let b = get_stack_idx(file, val.get(3).unwrap().as_str(), stacks_number)?;
+        if num > heights[a] {
+            return Err(ParseError::new(
+                file,
+                num_match,
+                format!("at most the {} crates of stack {}", heights[a], a + 1),
+            ));
+        }
+        heights[a] -= num;
+        heights[b] += num;
         moves.push((num, a, b));
     }
-    (stacks, moves)
+    Ok((stacks, moves))
+}
+
+/// Parse a stack number, starting at 1, into a stack index
+fn get_stack_idx(file: &str, value: &str, stacks_number: usize) -> Result<usize, ParseError> {
+    match parse_number::<usize>(file, value)? {
+        stack @ 1.. if stack <= stacks_number => Ok(stack - 1),
+        _ => Err(ParseError::new(
+            file,
+            value,
+            format!("a stack number between 1 and {}", stacks_number),
+        )),
+    }
 }
 
-fn get_stacks_number(file: &str) -> usize {
+fn get_stacks_number(file: &str) -> Result<usize, ParseError> {
     match RE_COLUMN_NUMBERS.captures(file) {
-        Some(val) => val.get(1).unwrap().as_str().parse().unwrap(),
-        None => panic!("Cannot find stack number: {:?}", file),
+        Some(val) => parse_number(file, val.get(1).unwrap().as_str()),
+        // The numbers are expected on the first line without crates
+        None => Err(ParseError::new(
+            file,
+            file.lines()
+                .find(|line| !line.contains('['))
+                .unwrap_or(&file[file.len()..]),
+            "a line of stack numbers like ` 1   2   3 `",
+        )),
     }
 }
 
@@ -74,7 +121,10 @@ fn part_one(stacks: &[Stack], moves: &[Move]) -> String {
             }
         }
     }
-    new_stacks.iter().filter_map(|stack| stack.get(0)).collect()
+    new_stacks
+        .iter()
+        .filter_map(|stack| stack.front())
+        .collect()
 }
 
 fn part_two(stacks: &[Stack], moves: &[Move]) -> String {
@@ -86,9 +136,52 @@ fn part_two(stacks: &[Stack], moves: &[Move]) -> String {
                 buffer.push_back(val);
             }
         }
-        for _ in 0..*crates_nbr {
-            new_stacks[*stack_dst].push_front(buffer.pop_back().unwrap());
+        while let Some(val) = buffer.pop_back() {
+            new_stacks[*stack_dst].push_front(val);
         }
     }
-    new_stacks.iter().filter_map(|stack| stack.get(0)).collect()
+    new_stacks
+        .iter()
+        .filter_map(|stack| stack.front())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2";
+
+    #[test]
+    fn test_example() {
+        let input = Day05::parse(EXAMPLE).unwrap();
+        assert_eq!(Day05::part_one(&input), "CMZ");
+        assert_eq!(Day05::part_two(&input), "MCD");
+
+        // Editors often strip trailing spaces
+        let stripped: Vec<_> = EXAMPLE.lines().map(str::trim_end).collect();
+        let input = Day05::parse(&stripped.join("\n")).unwrap();
+        assert_eq!(Day05::part_one(&input), "CMZ");
+    }
+
+    #[test]
+    fn test_invalid_moves() {
+        let input = EXAMPLE.replace("move 3 from 1 to 3", "move 9 from 2 to 1");
+        let error = Day05::parse(&input).unwrap_err();
+        assert_eq!((error.line, error.column), (7, 6));
+        assert_eq!(error.expected, "at most the 2 crates of stack 2");
+
+        let error = Day05::parse(&EXAMPLE.replace(" 1   2   3 ", "")).unwrap_err();
+        assert_eq!((error.line, error.text.as_str()), (4, ""));
+        let error = Day05::parse(&EXAMPLE.replace(" 1   2   3 ", "stacks")).unwrap_err();
+        assert_eq!((error.line, error.text.as_str()), (4, "stacks"));
+    }
 }
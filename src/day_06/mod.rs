@@ -1,40 +1,97 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::parse_error::ParseError;
 use itertools::Itertools;
 
-pub fn day_06() {
-    let datastream_buffer = get_input();
+pub struct Day06;
 
-    let solution_1 = part_one(&datastream_buffer);
-    println!("\t- Solution 1 is : {}", solution_1);
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+    type Input = String;
+    type PartOne = usize;
+    type PartTwo = usize;
 
-    let solution_2 = part_two(&datastream_buffer);
-    println!("\t- Solution 2 is : {}", solution_2);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(datastream_buffer: &Self::Input) -> Self::PartOne {
+        part_one(datastream_buffer)
+    }
+
+    fn part_two(datastream_buffer: &Self::Input) -> Self::PartTwo {
+        part_two(datastream_buffer)
+    }
 }
 
-fn get_input() -> String {
-    get_file("./src/day_06/input.txt")
+const START_OF_PACKET_SIZE: usize = 4;
+const START_OF_MESSAGE_SIZE: usize = 14;
+
+/// The start-of-message marker also contains a start-of-packet one, so both are found if the
+/// first one is
+fn get_input(input: &str) -> Result<String, ParseError> {
+    if get_consecutive_distinct_characters_idx(input, START_OF_MESSAGE_SIZE).is_none() {
+        return Err(ParseError::new(
+            input,
+            input,
+            format!(
+                "a datastream with {} different characters in a row",
+                START_OF_MESSAGE_SIZE
+            ),
+        ));
+    }
+    Ok(input.to_string())
 }
 
 fn part_one(datastream_buffer: &str) -> usize {
-    let consecutive_characters = 4;
-    get_consecutive_distinct_characters_idx(datastream_buffer, consecutive_characters)
-        + consecutive_characters
+    get_marker_end(datastream_buffer, START_OF_PACKET_SIZE)
 }
 
 fn part_two(datastream_buffer: &str) -> usize {
-    let consecutive_characters = 14;
-    get_consecutive_distinct_characters_idx(datastream_buffer, consecutive_characters)
-        + consecutive_characters
+    get_marker_end(datastream_buffer, START_OF_MESSAGE_SIZE)
 }
 
-fn get_consecutive_distinct_characters_idx(text: &str, char_nbr: usize) -> usize {
-    for (idx, window) in text.chars().collect_vec().windows(char_nbr).enumerate() {
-        if window.iter().unique().count() == char_nbr {
-            return idx;
+/// Returns the number of characters read once `char_nbr` different characters in a row are
+fn get_marker_end(text: &str, char_nbr: usize) -> usize {
+    get_consecutive_distinct_characters_idx(text, char_nbr)
+        .expect("The datastream is checked when parsed")
+        + char_nbr
+}
+
+fn get_consecutive_distinct_characters_idx(text: &str, char_nbr: usize) -> Option<usize> {
+    text.chars()
+        .collect_vec()
+        .windows(char_nbr)
+        .position(|window| window.iter().unique().count() == char_nbr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        let examples = vec![
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+        ];
+        for (example, start_of_packet, start_of_message) in examples {
+            let input = Day06::parse(example).unwrap();
+            assert_eq!(Day06::part_one(&input), start_of_packet);
+            assert_eq!(Day06::part_two(&input), start_of_message);
+        }
+    }
+
+    #[test]
+    fn test_no_marker() {
+        for input in ["", "abc", "abcdefghijklm", "abcdefghijklmabcdefghijklm"] {
+            let error = Day06::parse(input).unwrap_err();
+            assert_eq!(
+                error.expected,
+                "a datastream with 14 different characters in a row"
+            );
         }
     }
-    panic!(
-        "Could not find {} consecutive characters for text `{}`",
-        char_nbr, text
-    )
 }
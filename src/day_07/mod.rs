@@ -1,152 +1,298 @@
-use advent_of_code::utils::arena_tree::ArenaTree;
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::arena_tree::ArenaTree;
+use crate::utils::inputs::parse_number;
+use crate::utils::parse_error::ParseError;
+use ahash::AHashSet;
 use lazy_static::lazy_static;
 use regex::Regex;
 
 lazy_static! {
-    static ref RE_CD: Regex = Regex::new(r"(?m)^\s*cd\s+(.+)\s*").unwrap();
-    static ref RE_DIR: Regex = Regex::new(r"(?m)^dir (\w+)").unwrap();
-    static ref RE_FILE: Regex = Regex::new(r"(?m)^(\d+)\s+(\w+)").unwrap();
+    static ref RE_CD: Regex = Regex::new(r"^cd\s+(\S+)$").unwrap();
+    static ref RE_DIR: Regex = Regex::new(r"^dir (\S+)$").unwrap();
+    static ref RE_FILE: Regex = Regex::new(r"^(\d+)\s+(\S+)$").unwrap();
 }
 
-pub fn day_07() {
-    let commands = get_input();
+pub struct Day07;
 
-    let solution_1 = part_one(&commands);
-    println!("\t- Solution 1 is : {:?}", solution_1);
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+    type Input = Filesystem;
+    type PartOne = usize;
+    type PartTwo = usize;
 
-    let solution_2 = part_two(&commands);
-    println!("\t- Solution 2 is : {:?}", solution_2);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(filesystem: &Self::Input) -> Self::PartOne {
+        part_one(filesystem)
+    }
+
+    fn part_two(filesystem: &Self::Input) -> Self::PartTwo {
+        part_two(filesystem)
+    }
+}
+
+const DISK_SIZE: usize = 70_000_000;
+const REQUIRED_FREE_SPACE: usize = 30_000_000;
+
+fn get_input(input: &str) -> Result<Filesystem, ParseError> {
+    let filesystem = Filesystem::parse(input)?;
+    if filesystem.total_size("/").unwrap_or(0) > DISK_SIZE {
+        return Err(ParseError::new(
+            input,
+            &input[input.len()..],
+            format!("files taking at most {} in total", DISK_SIZE),
+        ));
+    }
+    Ok(filesystem)
 }
 
-fn get_input() -> ArenaTree<File> {
-    let mut arena_tree = ArenaTree::default();
-    let root_file = File::new_directory("/".to_string());
-    let mut current_path_index = arena_tree.insert_node(root_file, None);
-    let root_index = current_path_index;
-
-    for chunk in get_file("./src/day_07/input.txt").split('$') {
-        let mut lines = chunk.lines();
-        if let Some(raw_command) = lines.next() {
-            // Cd command
-            if let Command::Cd(path) = get_command(raw_command) {
-                current_path_index = match path.as_ref() {
-                    ".." => arena_tree.get_unwrapped(current_path_index).parent.unwrap(),
-                    "/" => root_index,
-                    _ => {
-                        if let Some(directory_idx) =
-                            get_directory_idx(&arena_tree, current_path_index, path.as_ref())
-                        {
-                            directory_idx
-                        } else {
-                            arena_tree
-                                .insert_node(File::new_directory(path), Some(current_path_index))
+fn part_one(filesystem: &Filesystem) -> usize {
+    filesystem
+        .du()
+        .into_iter()
+        .map(|(_, size)| size)
+        .filter(|size| *size <= 100_000)
+        .sum()
+}
+
+fn part_two(filesystem: &Filesystem) -> usize {
+    let used_space = filesystem.total_size("/").unwrap_or(0);
+    let free_space = DISK_SIZE - used_space;
+    let required_delete_space = REQUIRED_FREE_SPACE.saturating_sub(free_space);
+
+    filesystem
+        .du()
+        .into_iter()
+        .map(|(_, size)| size)
+        .filter(|size| *size >= required_delete_space)
+        .min()
+        .expect("The root directory is large enough")
+}
+
+/// A filesystem rebuilt from the transcript of the `cd` and `ls` commands browsing it
+#[derive(Debug)]
+pub struct Filesystem {
+    tree: ArenaTree<File>,
+    root: usize,
+}
+
+impl Filesystem {
+    /// Replay a transcript, starting from the root directory.
+    /// Moving to a directory which was not listed, or listing a directory twice with different
+    /// contents, is reported as an error.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut tree = ArenaTree::default();
+        let root = tree.insert_node(File::new_directory("/".to_string()), None);
+        let mut filesystem = Self { tree, root };
+        let mut listed_directories = AHashSet::new();
+        let mut current_idx = root;
+
+        for chunk in input.split('$') {
+            let mut lines = chunk.lines();
+            let raw_command = match lines.next() {
+                Some(raw_command) => raw_command.trim(),
+                None => continue,
+            };
+            match get_command(input, raw_command)? {
+                Command::Cd(path) => {
+                    current_idx = match filesystem.change_directory(current_idx, &path) {
+                        Some(idx) => idx,
+                        None if path == ".." => {
+                            return Err(ParseError::new(input, raw_command, "a parent directory"))
                         }
-                    }
+                        None => {
+                            return Err(ParseError::new(
+                                input,
+                                raw_command,
+                                format!(
+                                    "a directory listed in {}",
+                                    filesystem.get_path(current_idx)
+                                ),
+                            ))
+                        }
+                    };
                 }
-            } else {
-                // Ls command
-                for line in lines {
-                    if let Some(value) = RE_DIR.captures(line) {
-                        let new_file =
-                            File::new_directory(value.get(1).unwrap().as_str().to_string());
-                        arena_tree.insert_node(new_file, Some(current_path_index));
-                    } else if let Some(value) = RE_FILE.captures(line) {
-                        let new_file = File::new_file(
-                            value.get(2).unwrap().as_str().to_string(),
-                            value.get(1).unwrap().as_str().parse().unwrap(),
-                        );
-                        arena_tree.insert_node(new_file, Some(current_path_index));
+                Command::Ls => {
+                    let listing = get_listing(input, lines)?;
+                    if listed_directories.insert(current_idx) {
+                        for file in listing {
+                            filesystem.tree.insert_node(file, Some(current_idx));
+                        }
+                    } else if !filesystem.has_listing(current_idx, listing) {
+                        return Err(ParseError::new(
+                            input,
+                            raw_command,
+                            format!(
+                                "the same listing as the previous one of {}",
+                                filesystem.get_path(current_idx)
+                            ),
+                        ));
                     }
                 }
             }
         }
+        Ok(filesystem)
     }
-    arena_tree
-}
 
-fn get_command(line: &str) -> Command {
-    if let Some(value) = RE_CD.captures(line) {
-        Command::Cd(value.get(1).unwrap().as_str().to_string())
-    } else {
-        Command::Ls
+    /// Returns the directory reached by `cd <path>` from the directory `current_idx`
+    fn change_directory(&self, current_idx: usize, path: &str) -> Option<usize> {
+        match path {
+            "/" => Some(self.root),
+            ".." => self.tree.get_unwrapped(current_idx).parent(),
+            _ => self
+                .tree
+                .find_child(current_idx, |file| file.is_directory && file.name == path),
+        }
     }
-}
 
-fn part_one(arena: &ArenaTree<File>) -> usize {
-    let mut dir_list = vec![];
-    get_directories_with_size_at_most(arena, 100_000, 0, &mut dir_list);
-    dir_list.iter().sum::<usize>()
-}
+    /// State if a directory already contains exactly the files of `listing`
+    fn has_listing(&self, idx: usize, mut listing: Vec<File>) -> bool {
+        let mut contents: Vec<&File> = self
+            .tree
+            .get_unwrapped(idx)
+            .children()
+            .iter()
+            .map(|child| self.tree.get_unwrapped(*child).value())
+            .collect();
+        contents.sort_by(|a, b| a.name.cmp(&b.name));
+        listing.sort_by(|a, b| a.name.cmp(&b.name));
+        contents.into_iter().eq(listing.iter())
+    }
 
-fn get_directories_with_size_at_most(
-    arena: &ArenaTree<File>,
-    max_size: usize,
-    idx: usize,
-    dir_list: &mut Vec<usize>,
-) -> usize {
-    let current_node = arena.get_unwrapped(idx);
-    if !current_node.val.is_directory {
-        return current_node.val.size;
+    /// Returns the file at an absolute path. `.` and `..` components are allowed.
+    pub fn resolve(&self, path: &str) -> Option<&File> {
+        self.resolve_idx(path)
+            .map(|idx| self.tree.get_unwrapped(idx).value())
     }
 
-    let mut total = 0;
-    for child in &current_node.children {
-        total += get_directories_with_size_at_most(arena, max_size, *child, dir_list);
+    fn resolve_idx(&self, path: &str) -> Option<usize> {
+        let relative_path = path.strip_prefix('/')?;
+        let mut idx = self.root;
+        for component in relative_path.split('/') {
+            idx = match component {
+                "" | "." => idx,
+                ".." => self.tree.get_unwrapped(idx).parent().unwrap_or(self.root),
+                name => self.tree.find_child(idx, |file| file.name == name)?,
+            };
+        }
+        Some(idx)
     }
-    if total <= max_size {
-        dir_list.push(total);
+
+    /// Returns the absolute path of a file
+    fn get_path(&self, idx: usize) -> String {
+        let mut names: Vec<&str> = self
+            .tree
+            .path_to_root(idx)
+            .into_iter()
+            .filter(|idx| *idx != self.root)
+            .map(|idx| self.tree.get_unwrapped(idx).value().name.as_str())
+            .collect();
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    /// Returns the size of a file, or the total size of the files a directory contains, directly
+    /// or indirectly
+    pub fn total_size(&self, path: &str) -> Option<usize> {
+        self.resolve_idx(path).map(|idx| {
+            self.tree
+                .fold(idx, |file, children| get_total_size(file, &children))
+        })
     }
-    total
-}
 
-fn part_two(arena: &ArenaTree<File>) -> usize {
-    let mut dir_list = vec![];
-    let used_space = get_size(arena, 0);
-    let free_space = 70000000 - used_space;
-    let required_delete_space = 30000000 - free_space;
+    /// Returns the total size of every file and directory, in post-order
+    fn get_total_sizes(&self) -> Vec<(usize, usize)> {
+        self.tree.aggregate(self.root, get_total_size)
+    }
 
-    get_files_sizes(arena, 0, &mut dir_list);
-    let p = dir_list
-        .iter()
-        .filter(|&d| *d >= required_delete_space)
-        .min()
-        .unwrap();
-    *p
-}
+    /// Returns the path and total size of every directory, each one coming after its
+    /// subdirectories like the output of `du`
+    pub fn du(&self) -> Vec<(String, usize)> {
+        self.get_total_sizes()
+            .into_iter()
+            .filter(|(idx, _)| self.tree.get_unwrapped(*idx).value().is_directory)
+            .map(|(idx, size)| (self.get_path(idx), size))
+            .collect()
+    }
 
-fn get_files_sizes(arena: &ArenaTree<File>, idx: usize, dir_list: &mut Vec<usize>) -> usize {
-    let current_node = arena.get_unwrapped(idx);
-    if !current_node.val.is_directory {
-        return current_node.val.size;
+    /// Returns the path of every file and directory matching `predicate`, which gets the file
+    /// and its total size, in the order of `tree`
+    pub fn find<P>(&self, mut predicate: P) -> Vec<String>
+    where
+        P: FnMut(&File, usize) -> bool,
+    {
+        let mut total_sizes = vec![0; self.tree.len()];
+        for (idx, size) in self.get_total_sizes() {
+            total_sizes[idx] = size;
+        }
+        self.tree
+            .pre_order(self.root)
+            .filter(|idx| predicate(self.tree.get_unwrapped(*idx).value(), total_sizes[*idx]))
+            .map(|idx| self.get_path(idx))
+            .collect()
     }
 
-    let mut total = 0;
-    for child in &current_node.children {
-        let child_node = arena.get_unwrapped(*child);
-        total += get_files_sizes(arena, child_node.idx, dir_list);
+    /// Render the filesystem like the puzzle statement, one file per line
+    pub fn tree(&self) -> String {
+        self.tree
+            .pre_order(self.root)
+            .map(|idx| {
+                let file = self.tree.get_unwrapped(idx).value();
+                let indentation = "  ".repeat(self.tree.depth(idx));
+                if file.is_directory {
+                    format!("{}- {} (dir)", indentation, file.name)
+                } else {
+                    format!("{}- {} (file, size={})", indentation, file.name, file.size)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
     }
-    dir_list.push(total);
-    total
 }
 
-fn get_size(arena: &ArenaTree<File>, current_idx: usize) -> usize {
-    arena
-        .get_unwrapped(current_idx)
-        .children
-        .iter()
-        .map(|child_idx| get_size(arena, *child_idx))
-        .sum::<usize>()
-        + arena.get_unwrapped(current_idx).val.size
+fn get_total_size(file: &File, children_sizes: &[usize]) -> usize {
+    file.size + children_sizes.iter().sum::<usize>()
 }
 
-fn get_directory_idx(arena: &ArenaTree<File>, current_idx: usize, path: &str) -> Option<usize> {
-    for child_idx in &arena.get_unwrapped(current_idx).children {
-        if arena.get_unwrapped(*child_idx).val.name.eq(path) {
-            return Some(*child_idx);
+fn get_command(input: &str, line: &str) -> Result<Command, ParseError> {
+    if let Some(value) = RE_CD.captures(line) {
+        Ok(Command::Cd(value.get(1).unwrap().as_str().to_string()))
+    } else if line == "ls" {
+        Ok(Command::Ls)
+    } else {
+        Err(ParseError::new(input, line, "`cd <path>` or `ls`"))
+    }
+}
+
+/// Parse the output of `ls`, which cannot list a name twice
+fn get_listing<'a>(
+    input: &str,
+    lines: impl Iterator<Item = &'a str>,
+) -> Result<Vec<File>, ParseError> {
+    let mut listing: Vec<File> = vec![];
+    for line in lines {
+        let file = if let Some(value) = RE_DIR.captures(line) {
+            File::new_directory(value.get(1).unwrap().as_str().to_string())
+        } else if let Some(value) = RE_FILE.captures(line) {
+            File::new_file(
+                value.get(2).unwrap().as_str().to_string(),
+                parse_number(input, value.get(1).unwrap().as_str())?,
+            )
+        } else {
+            return Err(ParseError::new(
+                input,
+                line,
+                "`dir <name>` or `<size> <name>`",
+            ));
+        };
+        if listing.iter().any(|listed| listed.name == file.name) {
+            return Err(ParseError::new(input, line, "a name not already listed"));
         }
+        listing.push(file);
     }
-    None
+    Ok(listing)
 }
 
 #[derive(Debug)]
@@ -156,7 +302,7 @@ enum Command {
 }
 
 #[derive(Debug, Default, Eq, PartialEq)]
-struct File {
+pub struct File {
     name: String,
     is_directory: bool,
     size: usize,
@@ -178,4 +324,133 @@ impl File {
             size,
         }
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_directory(&self) -> bool {
+        self.is_directory
+    }
+
+    /// The size of a file, directories having none of their own
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+    #[test]
+    fn test_example() {
+        let input = Day07::parse(EXAMPLE).unwrap();
+        assert_eq!(Day07::part_one(&input), 95437);
+        assert_eq!(Day07::part_two(&input), 24933642);
+    }
+
+    #[test]
+    fn test_filesystem() {
+        let filesystem = Filesystem::parse(EXAMPLE).unwrap();
+        assert_eq!(
+            filesystem.tree(),
+            "- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - j (file, size=4060174)
+    - d.log (file, size=8033020)
+    - d.ext (file, size=5626152)
+    - k (file, size=7214296)"
+        );
+        assert_eq!(filesystem.resolve("/a/e/i").map(File::size), Some(584));
+        assert_eq!(filesystem.resolve("/d/../a/./e").map(File::name), Some("e"));
+        assert!(filesystem.resolve("/a/x").is_none());
+        assert!(filesystem.resolve("a").is_none());
+        assert_eq!(filesystem.total_size("/a"), Some(94853));
+        assert_eq!(
+            filesystem.du(),
+            vec![
+                ("/a/e".to_string(), 584),
+                ("/a".to_string(), 94853),
+                ("/d".to_string(), 24933642),
+                ("/".to_string(), 48381165)
+            ]
+        );
+        assert_eq!(
+            filesystem.find(|file, size| !file.is_directory() && size > 8_000_000),
+            vec!["/b.txt", "/c.dat", "/d/d.log"]
+        );
+        assert_eq!(
+            filesystem.find(|file, _| file.name().starts_with('d')),
+            vec!["/d", "/d/d.log", "/d/d.ext"]
+        );
+    }
+
+    #[test]
+    fn test_disk_size() {
+        // Nothing needs to be deleted
+        let input = Day07::parse("").unwrap();
+        assert_eq!((Day07::part_one(&input), Day07::part_two(&input)), (0, 0));
+        let input = Day07::parse("$ cd /\n$ ls\n40000000 a").unwrap();
+        assert_eq!(Day07::part_two(&input), 40000000);
+
+        let error = Day07::parse("$ cd /\n$ ls\n70000001 a").unwrap_err();
+        assert_eq!(error.expected, "files taking at most 70000000 in total");
+    }
+
+    #[test]
+    fn test_inconsistencies() {
+        let error = Filesystem::parse("$ cd /\n$ ls\ndir a\n$ cd b").unwrap_err();
+        assert_eq!(
+            (error.line, error.expected.as_str()),
+            (4, "a directory listed in /")
+        );
+
+        let error = Filesystem::parse("$ cd /\n$ ls\n12 a\n$ cd a").unwrap_err();
+        assert_eq!(error.line, 4);
+
+        let transcript = "$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\n1 b\n$ cd ..\n$ cd a\n$ ls\n2 b";
+        let error = Filesystem::parse(transcript).unwrap_err();
+        assert_eq!(
+            (error.line, error.expected.as_str()),
+            (9, "the same listing as the previous one of /a")
+        );
+        let transcript = "$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\n1 b\n$ cd ..\n$ cd a\n$ ls\n1 b";
+        assert!(Filesystem::parse(transcript).is_ok());
+
+        let error = Filesystem::parse("$ cd /\n$ ls\n1 a\ndir a").unwrap_err();
+        assert_eq!((error.line, error.text.as_str()), (4, "dir a"));
+    }
 }
@@ -1,88 +1,93 @@
-use advent_of_code::utils::inputs::get_file;
-use std::ops::Range;
+use crate::solution::Solution;
+use crate::utils::grid::{Grid, Position, NEIGHBOURS_4};
+use crate::utils::parse_error::ParseError;
 
-static HEIGHT: usize = 99;
-static WIDTH: usize = 99;
+pub struct Day08;
 
-pub fn day_08() {
-    let trees = get_input();
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+    type Input = Grid<u8>;
+    type PartOne = usize;
+    type PartTwo = usize;
 
-    let solution_1 = part_one(&trees);
-    println!("\t- Solution 1 is : {}", solution_1);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
 
-    let solution_2 = part_two(&trees);
-    println!("\t- Solution 2 is : {}", solution_2);
-}
+    fn part_one(trees: &Self::Input) -> Self::PartOne {
+        part_one(trees)
+    }
 
-fn get_input() -> Vec<Vec<u8>> {
-    get_file("./src/day_08/input.txt")
-        .lines()
-        .map(|l| l.chars().map(|c| c.to_digit(10).unwrap() as u8).collect())
-        .collect()
+    fn part_two(trees: &Self::Input) -> Self::PartTwo {
+        part_two(trees)
+    }
 }
 
-fn part_one(trees: &[Vec<u8>]) -> usize {
-    (0..HEIGHT)
-        .flat_map(|y| (0..WIDTH).map(move |x| is_visible(trees, y, x)))
-        .filter(|is_visible| *is_visible)
-        .count()
+fn get_input(input: &str) -> Result<Grid<u8>, ParseError> {
+    Grid::parse(input, "a digit", |c| {
+        c.to_digit(10).map(|height| height as u8)
+    })
 }
 
-fn is_visible(trees: &[Vec<u8>], y: usize, x: usize) -> bool {
-    let north_range = 0..y;
-    let south_range = (y + 1)..HEIGHT;
-    let east_range = 0..x;
-    let west_range = (x + 1)..WIDTH;
-
-    is_visible_iter(trees, trees[y][x], north_range, x..x + 1)
-        || is_visible_iter(trees, trees[y][x], south_range, x..x + 1)
-        || is_visible_iter(trees, trees[y][x], y..y + 1, east_range)
-        || is_visible_iter(trees, trees[y][x], y..y + 1, west_range)
+fn part_one(trees: &Grid<u8>) -> usize {
+    trees
+        .positions()
+        .filter(|position| is_visible(trees, *position))
+        .count()
 }
 
-fn is_visible_iter(
-    trees: &[Vec<u8>],
-    val: u8,
-    range_y: Range<usize>,
-    range_x: Range<usize>,
-) -> bool {
-    range_y
-        .flat_map(|y| range_x.clone().map(move |x| trees[y][x]))
-        .all(|t| val > t)
+/// A tree is visible if all the trees between it and an edge of the grid are shorter
+fn is_visible(trees: &Grid<u8>, position: Position) -> bool {
+    let height = trees[position];
+    NEIGHBOURS_4.iter().any(|direction| {
+        trees
+            .ray(position, *direction)
+            .all(|other| trees[other] < height)
+    })
 }
 
-fn part_two(trees: &[Vec<u8>]) -> usize {
-    (0..HEIGHT)
-        .flat_map(|y| (0..WIDTH).map(move |x| get_scenic_score(trees, y, x)))
+fn part_two(trees: &Grid<u8>) -> usize {
+    trees
+        .positions()
+        .map(|position| get_scenic_score(trees, position))
         .max()
         .unwrap()
 }
 
-fn get_scenic_score(trees: &[Vec<u8>], y: usize, x: usize) -> usize {
-    let north_range = (0..y).rev();
-    let south_range = (y + 1)..HEIGHT;
-    let east_range = (0..x).rev();
-    let west_range = (x + 1)..WIDTH;
-
-    get_score(trees, trees[y][x], north_range, x..x + 1)
-        * get_score(trees, trees[y][x], south_range, x..x + 1)
-        * get_score(trees, trees[y][x], y..y + 1, east_range)
-        * get_score(trees, trees[y][x], y..y + 1, west_range)
+fn get_scenic_score(trees: &Grid<u8>, position: Position) -> usize {
+    NEIGHBOURS_4
+        .iter()
+        .map(|direction| get_viewing_distance(trees, position, *direction))
+        .product()
 }
 
-fn get_score<X, Y>(trees: &[Vec<u8>], value: u8, range_y: Y, range_x: X) -> usize
-where
-    X: Iterator<Item = usize> + Clone,
-    Y: Iterator<Item = usize> + Clone,
-{
+/// Count the trees seen in a direction, up to the first one at least as tall
+fn get_viewing_distance(trees: &Grid<u8>, position: Position, direction: (isize, isize)) -> usize {
+    let height = trees[position];
     let mut count = 0;
-    for y in range_y {
-        for x in range_x.clone() {
-            count += 1;
-            if value <= trees[y][x] {
-                return count;
-            }
+    for other in trees.ray(position, direction) {
+        count += 1;
+        if height <= trees[other] {
+            break;
         }
     }
     count
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "30373
+25512
+65332
+33549
+35390";
+
+    #[test]
+    fn test_example() {
+        let input = Day08::parse(EXAMPLE).unwrap();
+        assert_eq!(Day08::part_one(&input), 21);
+        assert_eq!(Day08::part_two(&input), 8);
+    }
+}
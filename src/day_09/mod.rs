@@ -1,40 +1,53 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::geometry::{Direction, Point2};
+use crate::utils::inputs::{parse_lines, parse_number};
+use crate::utils::parse_error::ParseError;
 use std::collections::HashSet;
 
-pub fn day_09() {
-    let motions = get_input();
+pub struct Day09;
 
-    let solution_1 = get_rope_tail_nodes_visit_nbr(&motions, 2);
-    println!("\t- Solution 1 is : {}", solution_1);
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+    type Input = Vec<Motion>;
+    type PartOne = usize;
+    type PartTwo = usize;
 
-    let solution_2 = get_rope_tail_nodes_visit_nbr(&motions, 10);
-    println!("\t- Solution 2 is : {}", solution_2);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(motions: &Self::Input) -> Self::PartOne {
+        get_rope_tail_nodes_visit_nbr(motions, 2)
+    }
+
+    fn part_two(motions: &Self::Input) -> Self::PartTwo {
+        get_rope_tail_nodes_visit_nbr(motions, 10)
+    }
 }
 
-fn get_input() -> Vec<Motion> {
-    let mut instructions = vec![];
-    for line in get_file("./src/day_09/input.txt").lines() {
-        let values = line.split(' ').collect::<Vec<&str>>();
-        let direction = match values[0] {
+fn get_input(input: &str) -> Result<Vec<Motion>, ParseError> {
+    parse_lines(input, |line| {
+        let (direction, move_nbr) = line
+            .split_once(' ')
+            .ok_or_else(|| ParseError::new(line, line, "a direction and a number of moves"))?;
+        let direction = match direction {
             "U" => Direction::Up,
             "D" => Direction::Down,
             "L" => Direction::Left,
             "R" => Direction::Right,
-            _ => panic!("Cannot get direction from value {}", values[0]),
+            _ => return Err(ParseError::new(line, direction, "`U`, `D`, `L` or `R`")),
         };
-        let move_nbr = values[1].parse().unwrap();
-        instructions.push((direction, move_nbr))
-    }
-    instructions
+        Ok((direction, parse_number(line, move_nbr)?))
+    })
 }
 
 fn get_rope_tail_nodes_visit_nbr(motions: &[Motion], rope_size: usize) -> usize {
-    let mut rope = vec![(0, 0); rope_size];
+    let mut rope = vec![Point2::new(0, 0); rope_size];
     let mut tail_positions = HashSet::new();
 
-    for motion in motions {
-        for _ in 0..motion.1 {
-            rope[0] = move_head(rope[0], motion);
+    for (direction, move_nbr) in motions {
+        for _ in 0..*move_nbr {
+            rope[0] += direction.offset();
             for node_idx in 1..rope_size {
                 rope[node_idx] = move_tail(rope[node_idx - 1], rope[node_idx]);
             }
@@ -44,41 +57,48 @@ fn get_rope_tail_nodes_visit_nbr(motions: &[Motion], rope_size: usize) -> usize
     tail_positions.len()
 }
 
-fn move_head(head: (i32, i32), motion: &Motion) -> (i32, i32) {
-    let (y_modifier, x_modifier) = match motion.0 {
-        Direction::Up => (-1, 0),
-        Direction::Down => (1, 0),
-        Direction::Left => (0, -1),
-        Direction::Right => (0, 1),
-    };
-    (head.0 + y_modifier, head.1 + x_modifier)
-}
-
-fn move_tail(head: (i32, i32), tail: (i32, i32)) -> (i32, i32) {
-    if points_touching(head, tail) {
+/// The tail follows the head by a single step, once they are not touching anymore
+fn move_tail(head: Point2<i32>, tail: Point2<i32>) -> Point2<i32> {
+    if head.chebyshev_distance(&tail) <= 1 {
         return tail;
     }
-
-    let (delta_y, delta_x) = (head.0 - tail.0, head.1 - tail.1);
-    let (new_tail_y, new_tail_x) = (tail.0 + delta_y.signum(), tail.1 + delta_x.signum());
-    (new_tail_y, new_tail_x)
+    tail + (head - tail).signum()
 }
 
-fn points_touching(p1: (i32, i32), p2: (i32, i32)) -> bool {
-    if p1 == p2 {
-        return true;
+type Motion = (Direction, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "R 4
+U 4
+L 3
+D 1
+R 4
+D 1
+L 5
+R 2";
+
+    const LARGER_EXAMPLE: &str = "R 5
+U 8
+L 8
+D 3
+R 17
+D 10
+L 25
+U 20";
+
+    #[test]
+    fn test_example() {
+        let input = Day09::parse(EXAMPLE).unwrap();
+        assert_eq!(Day09::part_one(&input), 13);
+        assert_eq!(Day09::part_two(&input), 1);
     }
-    let (x1, y1) = p1;
-    let (x2, y2) = p2;
-    (x1 - x2).abs() <= 1 && (y1 - y2).abs() <= 1
-}
 
-#[derive(Debug)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+    #[test]
+    fn test_larger_example() {
+        let input = Day09::parse(LARGER_EXAMPLE).unwrap();
+        assert_eq!(Day09::part_two(&input), 36);
+    }
 }
-
-type Motion = (Direction, usize);
@@ -1,95 +1,420 @@
-use advent_of_code::utils::inputs::get_file;
-use std::collections::HashSet;
+use crate::solution::Solution;
+use crate::utils::grid::Grid;
+use crate::utils::inputs::{parse_lines, parse_number};
+use crate::utils::ocr;
+use crate::utils::parse_error::ParseError;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
 use std::str::FromStr;
 
-pub fn day_10() {
-    let op_list = get_input();
+pub struct Day10;
 
-    let solution_1 = part_one(&op_list);
-    println!("\t- Solution 1 is : {}", solution_1);
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    type Input = Vec<Op>;
+    type PartOne = i32;
+    type PartTwo = String;
 
-    print!("\t- Solution 2 is :");
-    part_two(&op_list);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(op_list: &Self::Input) -> Self::PartOne {
+        part_one(op_list)
+    }
+
+    fn part_two(op_list: &Self::Input) -> Self::PartTwo {
+        part_two(op_list)
+    }
 }
 
-fn get_input() -> Vec<Op> {
-    get_file("./src/day_10/input.txt")
-        .lines()
-        .map(|line| line.parse::<Op>().unwrap())
-        .collect()
+fn get_input(input: &str) -> Result<Vec<Op>, ParseError> {
+    parse_lines(input, str::parse)
 }
 
+/// The cycles during which the signal strength is measured
+const SIGNAL_CYCLES: [usize; 6] = [20, 60, 100, 140, 180, 220];
+
+const CRT_WIDTH: usize = 40;
+const CRT_HEIGHT: usize = 6;
+
 fn part_one(op_list: &[Op]) -> i32 {
-    let mut register_x = 1;
-    let mut current_cycle = 0;
-    let signal_steps: HashSet<i32> = vec![20, 60, 100, 140, 180, 220].into_iter().collect();
-    let mut sum_signal_strength = 0;
-
-    for op in op_list {
-        current_cycle += 1;
-        if signal_steps.contains(&current_cycle) {
-            sum_signal_strength += current_cycle * register_x;
-        };
-        if let Op::AddX(val) = op {
-            current_cycle += 1;
-            if signal_steps.contains(&current_cycle) {
-                sum_signal_strength += current_cycle * register_x;
-            };
-            register_x += val;
-        }
+    Cpu::new(op_list)
+        .filter(|(cycle, _)| SIGNAL_CYCLES.contains(cycle))
+        .map(|(cycle, register_x)| cycle as i32 * register_x)
+        .sum()
+}
+
+fn part_two(op_list: &[Op]) -> String {
+    let screen = get_screen(op_list);
+    ocr::recognize(&screen).unwrap_or_else(|_| {
+        screen
+            .map(|is_lit| if *is_lit { '█' } else { ' ' })
+            .to_string()
+    })
+}
+
+/// Returns the pixels lit by the CRT, which draws one pixel per cycle, row by row. A pixel is lit
+/// when the 3 pixels wide sprite, centered on X, covers it.
+fn get_screen(op_list: &[Op]) -> Grid<bool> {
+    let mut screen = Grid::new(CRT_WIDTH, CRT_HEIGHT, false);
+    for (cycle, register_x) in Cpu::new(op_list).take(CRT_WIDTH * CRT_HEIGHT) {
+        let (x, y) = ((cycle - 1) % CRT_WIDTH, (cycle - 1) / CRT_WIDTH);
+        screen[(x, y)] = (x as i32 - register_x).abs() <= 1;
     }
-    sum_signal_strength
+    screen
+}
+
+/// The most operands an instruction can take
+const MAX_ARITY: usize = 2;
+
+/// Everything defining an instruction, so a new one is added to the instruction set with a
+/// single `OpCode`
+#[derive(Debug, Copy, Clone)]
+pub struct OpCode {
+    pub mnemonic: &'static str,
+    /// The number of cycles the instruction takes, unless changed with `Cpu::with_cycle_cost`
+    pub cycle_cost: usize,
+    /// The number of integer operands following the mnemonic, at most `MAX_ARITY`
+    pub arity: usize,
+    /// Returns the value of X after the instruction, from its value before and the operands
+    pub execute: fn(i32, &[i32]) -> i32,
 }
 
-fn part_two(op_list: &[Op]) {
-    let mut register_x = 1;
-    let mut current_cycle = 0;
+pub const ADDX: OpCode = OpCode {
+    mnemonic: "addx",
+    cycle_cost: 2,
+    arity: 1,
+    execute: |register_x, operands| register_x + operands[0],
+};
+
+pub const NOOP: OpCode = OpCode {
+    mnemonic: "noop",
+    cycle_cost: 1,
+    arity: 0,
+    execute: |register_x, _| register_x,
+};
 
-    draw_pixel(register_x, current_cycle);
-    for op in op_list.iter() {
-        current_cycle += 1;
-        draw_pixel(register_x, current_cycle);
+/// The instructions of the handheld device
+pub const INSTRUCTION_SET: &[OpCode] = &[ADDX, NOOP];
 
-        if let Op::AddX(val) = op {
-            current_cycle += 1;
-            register_x += val;
-            draw_pixel(register_x, current_cycle);
+/// An instruction of a program, with its operands
+#[derive(Debug, Copy, Clone)]
+pub struct Op {
+    code: &'static OpCode,
+    operands: [i32; MAX_ARITY],
+}
+
+impl Op {
+    /// Parse an instruction from `instruction_set`, like `addx 3` for the handheld device
+    pub fn parse(s: &str, instruction_set: &'static [OpCode]) -> Result<Self, ParseError> {
+        let mut words = s.split_whitespace();
+        let mnemonic = words.next().unwrap_or("");
+        let code = instruction_set
+            .iter()
+            .find(|code| code.mnemonic == mnemonic)
+            .ok_or_else(|| {
+                let mnemonics: Vec<_> = instruction_set
+                    .iter()
+                    .map(|code| format!("`{}`", code.mnemonic))
+                    .collect();
+                ParseError::new(s, mnemonic, mnemonics.join(" or "))
+            })?;
+        assert!(
+            code.arity <= MAX_ARITY,
+            "`{}` takes more than {} operands",
+            code.mnemonic,
+            MAX_ARITY
+        );
+
+        let mut operands = [0; MAX_ARITY];
+        for operand in &mut operands[..code.arity] {
+            *operand = parse_number(s, words.next().unwrap_or(""))?;
+        }
+        match words.next() {
+            Some(extra) => Err(ParseError::new(s, extra, "the end of the instruction")),
+            None => Ok(Op { code, operands }),
         }
     }
+
+    pub fn mnemonic(&self) -> &'static str {
+        self.code.mnemonic
+    }
+
+    pub fn operands(&self) -> &[i32] {
+        &self.operands[..self.code.arity]
+    }
 }
 
-fn draw_pixel(sprite_position: i32, current_cycle: i32) {
-    let current_cycle = current_cycle % 40;
-    if current_cycle == 0 {
-        println!()
+impl FromStr for Op {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Op::parse(s, INSTRUCTION_SET)
     }
-    if current_cycle >= sprite_position - 1 && current_cycle <= sprite_position + 1 {
-        print!("█")
-    } else {
-        print!(" ")
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+        for operand in self.operands() {
+            write!(f, " {}", operand)?;
+        }
+        Ok(())
     }
 }
 
+/// Why `Cpu::run` stopped
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Stop {
+    /// The next cycle to run has a breakpoint
+    Breakpoint(usize),
+    /// The whole program was run
+    Halted,
+}
+
+/// The state of the CPU during a cycle
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct TraceEntry {
+    cycle: usize,
+    register_x: i32,
+    /// The index of the instruction being executed
+    instruction: usize,
+}
+
+/// The CPU of the handheld device, running a program one cycle at a time.
+///
+/// As an iterator, it yields the number of each cycle, from 1, with the value of the X register
+/// during that cycle.
 #[derive(Debug)]
-enum Op {
-    AddX(i32),
-    Noop,
+pub struct Cpu<'a> {
+    program: &'a [Op],
+    /// The cycle costs changed with `with_cycle_cost`, by mnemonic
+    cycle_costs: HashMap<&'static str, usize>,
+    breakpoints: BTreeSet<usize>,
+    /// The breakpoint `run` last stopped at, so running again goes past it
+    stopped_at: Option<usize>,
+    trace: Option<Vec<TraceEntry>>,
+    register_x: i32,
+    /// The number of cycles already run
+    cycle: usize,
+    /// The index of the instruction being executed
+    instruction: usize,
+    /// The number of cycles the current instruction has already run for
+    instruction_cycles: usize,
 }
 
-impl FromStr for Op {
-    type Err = ();
+impl<'a> Cpu<'a> {
+    pub fn new(program: &'a [Op]) -> Self {
+        Self {
+            program,
+            cycle_costs: HashMap::new(),
+            breakpoints: BTreeSet::new(),
+            stopped_at: None,
+            trace: None,
+            register_x: 1,
+            cycle: 0,
+            instruction: 0,
+            instruction_cycles: 0,
+        }
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut words = s.split_whitespace();
-        let op = words.next().unwrap();
+    /// Change the number of cycles taken by the instructions with this mnemonic.
+    /// Panics if `cost` is 0, as each instruction runs for at least one cycle.
+    pub fn with_cycle_cost(mut self, mnemonic: &'static str, cost: usize) -> Self {
+        assert!(cost > 0, "`{}` must take at least one cycle", mnemonic);
+        self.cycle_costs.insert(mnemonic, cost);
+        self
+    }
 
-        match op {
-            "addx" => {
-                let x = words.next().unwrap().parse::<i32>().unwrap();
-                Ok(Op::AddX(x))
+    /// Record every cycle, to be dumped by `dump_trace`
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(vec![]);
+        self
+    }
+
+    /// Make `run` stop before running this cycle
+    pub fn add_breakpoint(&mut self, cycle: usize) {
+        self.breakpoints.insert(cycle);
+    }
+
+    pub fn remove_breakpoint(&mut self, cycle: usize) {
+        self.breakpoints.remove(&cycle);
+    }
+
+    pub fn register_x(&self) -> i32 {
+        self.register_x
+    }
+
+    /// The number of cycles already run
+    pub fn elapsed_cycles(&self) -> usize {
+        self.cycle
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.instruction >= self.program.len()
+    }
+
+    fn get_cycle_cost(&self, op: &Op) -> usize {
+        self.cycle_costs
+            .get(op.mnemonic())
+            .copied()
+            .unwrap_or(op.code.cycle_cost)
+    }
+
+    /// Run a single cycle, and returns its number with the value of X during it.
+    /// An instruction changes the registers at the end of its last cycle.
+    pub fn step(&mut self) -> Option<(usize, i32)> {
+        let op = self.program.get(self.instruction)?;
+        self.cycle += 1;
+        self.instruction_cycles += 1;
+        let state = (self.cycle, self.register_x);
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry {
+                cycle: self.cycle,
+                register_x: self.register_x,
+                instruction: self.instruction,
+            });
+        }
+
+        if self.instruction_cycles == self.get_cycle_cost(op) {
+            self.register_x = (op.code.execute)(self.register_x, op.operands());
+            self.instruction += 1;
+            self.instruction_cycles = 0;
+        }
+        Some(state)
+    }
+
+    /// Run cycles until the program ends, or the next cycle has a breakpoint
+    pub fn run(&mut self) -> Stop {
+        loop {
+            let next_cycle = self.cycle + 1;
+            if self.is_halted() {
+                return Stop::Halted;
+            }
+            if self.breakpoints.contains(&next_cycle) && self.stopped_at != Some(next_cycle) {
+                self.stopped_at = Some(next_cycle);
+                return Stop::Breakpoint(next_cycle);
             }
-            "noop" => Ok(Op::Noop),
-            _ => Err(()),
+            self.step();
         }
     }
+
+    /// Returns one line per cycle run since the trace was enabled, with the value of X and the
+    /// instruction being executed
+    pub fn dump_trace(&self) -> String {
+        let trace = match &self.trace {
+            Some(trace) => trace,
+            None => return String::new(),
+        };
+        trace
+            .iter()
+            .map(|entry| {
+                format!(
+                    "cycle {:>4} | X = {:>4} | {:>4}: {}",
+                    entry.cycle,
+                    entry.register_x,
+                    entry.instruction,
+                    self.program[entry.instruction]
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Iterator for Cpu<'_> {
+    type Item = (usize, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_SCREEN: &str = "##..##..##..##..##..##..##..##..##..##..
+###...###...###...###...###...###...###.
+####....####....####....####....####....
+#####.....#####.....#####.....#####.....
+######......######......######......####
+#######.......#######.......#######.....";
+
+    #[test]
+    fn test_example() {
+        let input = Day10::parse(include_str!("input_example.txt")).unwrap();
+        assert_eq!(Day10::part_one(&input), 13140);
+
+        let screen = get_screen(&input).map(|is_lit| if *is_lit { '#' } else { '.' });
+        assert_eq!(screen.to_string(), EXAMPLE_SCREEN);
+        // The example does not draw letters
+        assert_eq!(
+            Day10::part_two(&input),
+            EXAMPLE_SCREEN.replace('#', "█").replace('.', " ")
+        );
+    }
+
+    #[test]
+    fn test_cpu() {
+        let program = Day10::parse("noop\naddx 3\naddx -5").unwrap();
+        let cycles: Vec<_> = Cpu::new(&program).collect();
+        assert_eq!(cycles, vec![(1, 1), (2, 1), (3, 1), (4, 4), (5, 4)]);
+
+        let mut cpu = Cpu::new(&program).with_cycle_cost("addx", 1);
+        assert_eq!(cpu.by_ref().last(), Some((3, 4)));
+        assert!(cpu.is_halted());
+        assert_eq!(cpu.register_x(), -1);
+    }
+
+    #[test]
+    fn test_extra_opcode() {
+        const MULX: OpCode = OpCode {
+            mnemonic: "mulx",
+            cycle_cost: 3,
+            arity: 1,
+            execute: |register_x, operands| register_x * operands[0],
+        };
+        const EXTENDED_SET: &[OpCode] = &[ADDX, NOOP, MULX];
+
+        let program: Vec<_> = ["addx 2", "mulx -4", "noop"]
+            .iter()
+            .map(|line| Op::parse(line, EXTENDED_SET).unwrap())
+            .collect();
+        assert_eq!(program[1].to_string(), "mulx -4");
+        let cycles: Vec<_> = Cpu::new(&program).collect();
+        assert_eq!(
+            cycles,
+            vec![(1, 1), (2, 1), (3, 3), (4, 3), (5, 3), (6, -12)]
+        );
+
+        assert_eq!(
+            Op::parse("mulx", EXTENDED_SET).unwrap_err().expected,
+            "a number"
+        );
+        assert_eq!(
+            "mulx 2".parse::<Op>().unwrap_err().expected,
+            "`addx` or `noop`"
+        );
+        assert_eq!(
+            Op::parse("noop 2", EXTENDED_SET).unwrap_err().expected,
+            "the end of the instruction"
+        );
+    }
+
+    #[test]
+    fn test_breakpoints_and_trace() {
+        let program = Day10::parse("noop\naddx 3\naddx -5").unwrap();
+        let mut cpu = Cpu::new(&program).with_trace();
+        cpu.add_breakpoint(4);
+        assert_eq!(cpu.run(), Stop::Breakpoint(4));
+        assert_eq!((cpu.elapsed_cycles(), cpu.register_x()), (3, 4));
+        assert_eq!(cpu.run(), Stop::Halted);
+        assert_eq!(cpu.register_x(), -1);
+        assert_eq!(
+            cpu.dump_trace().lines().nth(3),
+            Some("cycle    4 | X =    4 |    2: addx -5")
+        );
+    }
 }
@@ -1,175 +1,567 @@
-use advent_of_code::utils::inputs::{get_file, LINE_ENDING};
+use crate::solution::Solution;
+use crate::utils::inputs::{parse_chunks, parse_number};
+use crate::utils::parse_error::ParseError;
+use ahash::AHashMap;
 use itertools::Itertools;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
 use std::str::FromStr;
 
-pub fn day_11() {
-    let solution_1 = play_rounds(&mut get_input(), 20, true);
-    println!("\t- Solution 1 is : {}", solution_1);
+pub struct Day11;
 
-    let solution_2 = play_rounds(&mut get_input(), 10000, false);
-    println!("\t- Solution 2 is : {}", solution_2);
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    type Input = Vec<Monkey>;
+    type PartOne = u128;
+    type PartTwo = u128;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(monkeys: &Self::Input) -> Self::PartOne {
+        play_rounds(monkeys, 20, WorryPolicy::Divide(3))
+            .unwrap_or_else(|error| panic!("Cannot play the rounds: {}", error))
+    }
+
+    fn part_two(monkeys: &Self::Input) -> Self::PartTwo {
+        WorryPolicy::modulo_lcm(monkeys)
+            .and_then(|policy| Simulation::new(monkeys, policy))
+            .and_then(|simulation| simulation.inspections_after(10000))
+            .map(|inspections| get_monkey_business(&inspections))
+            .unwrap_or_else(|error| panic!("Cannot play the rounds: {}", error))
+    }
 }
 
-fn get_input() -> Vec<Monkey> {
-    let split_separator = format!("{}{}", LINE_ENDING, LINE_ENDING);
-    get_file("./src/day_11/input.txt")
-        .split(&split_separator)
-        .map(|line| Monkey::from_str(line).unwrap())
-        .collect()
+fn get_input(input: &str) -> Result<Vec<Monkey>, ParseError> {
+    let monkeys = parse_chunks(input, Monkey::from_str)?;
+    for monkey in &monkeys {
+        if monkey.test_divisible_by == 0 {
+            let text = monkey.test_divisible_by.to_string();
+            return Err(ParseError::new(input, &text, "a divisor above 0"));
+        }
+        for target in [monkey.if_true, monkey.if_false] {
+            if target >= monkeys.len() {
+                let text = target.to_string();
+                return Err(ParseError::new(
+                    input,
+                    &text,
+                    format!("a monkey below {}", monkeys.len()),
+                ));
+            }
+        }
+    }
+    Ok(monkeys)
 }
 
-fn play_rounds(monkeys: &mut [Monkey], round_nbr: usize, puzzle_part_one: bool) -> u128 {
+/// Plays `round_nbr` rounds and returns the product of the two highest inspection counts
+fn play_rounds(
+    monkeys: &[Monkey],
+    round_nbr: usize,
+    policy: WorryPolicy,
+) -> Result<u128, SimulationError> {
+    let mut simulation = Simulation::new(monkeys, policy)?;
     for _ in 0..round_nbr {
-        play_round(monkeys, puzzle_part_one)
+        simulation.play_round()?;
     }
-    monkeys
+    Ok(simulation.monkey_business())
+}
+
+fn get_monkey_business(inspections: &[u64]) -> u128 {
+    inspections
         .iter()
-        .map(|m| m.monkey_business)
         .sorted()
         .rev()
         .take(2)
+        .map(|&count| u128::from(count))
         .product()
 }
 
-fn play_round(monkeys: &mut [Monkey], puzzle_part_one: bool) {
-    let mut new_item_values_and_owners = vec![];
-    let common_divisor: u128 = monkeys.iter().map(|m| m.test_divisible_by).product();
+/// How the worry level of an item changes once a monkey has inspected it
+pub enum WorryPolicy {
+    /// The result of the operation is divided, rounding down
+    Divide(u128),
+    /// The operation is computed modulo a number. The tests give the same results as with the
+    /// actual worry levels if it is a multiple of every test divisor.
+    Modulo(u128),
+    /// The result of the operation goes through a function
+    Custom(Box<dyn Fn(u128) -> u128>),
+}
+
+impl WorryPolicy {
+    /// Keeps worry levels modulo the least common multiple of the test divisors, or fails if it
+    /// overflows
+    pub fn modulo_lcm(monkeys: &[Monkey]) -> Result<Self, SimulationError> {
+        monkeys
+            .iter()
+            .try_fold(1, |modulus, monkey| lcm(modulus, monkey.test_divisible_by))
+            .map(Self::Modulo)
+            .ok_or(SimulationError::ModulusOverflow)
+    }
+
+    /// Returns the new worry level of an item, or `None` if it cannot be computed
+    fn apply(&self, operation: &(Value, Op, Value), old: u128) -> Option<u128> {
+        match self {
+            Self::Divide(divisor) => apply_operation(operation, old)?.checked_div(*divisor),
+            Self::Modulo(modulus) => apply_operation_modulo(operation, old, *modulus),
+            Self::Custom(function) => apply_operation(operation, old).map(function),
+        }
+    }
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Returns `None` if the result overflows
+fn lcm(a: u128, b: u128) -> Option<u128> {
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Why a simulation cannot be built or played
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SimulationError {
+    /// The least common multiple of the test divisors does not fit in a worry level
+    ModulusOverflow,
+    /// The result of a monkey's operation cannot be computed from worry levels kept modulo a
+    /// number, such as a division
+    UnsupportedModulo {
+        monkey_idx: usize,
+        operation: String,
+    },
+    /// The new worry level of an item overflows, is negative or divides by zero
+    InvalidWorryLevel {
+        monkey_idx: usize,
+        operation: String,
+        old: u128,
+        round: u64,
+    },
+}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationError::ModulusOverflow => {
+                write!(f, "the test divisors have no common multiple small enough")
+            }
+            SimulationError::UnsupportedModulo {
+                monkey_idx,
+                operation,
+            } => write!(
+                f,
+                "operation `{}` of monkey {} is not supported modulo",
+                operation, monkey_idx
+            ),
+            SimulationError::InvalidWorryLevel {
+                monkey_idx,
+                operation,
+                old,
+                round,
+            } => write!(
+                f,
+                "operation `{}` of monkey {} cannot be computed with old = {} in round {}",
+                operation, monkey_idx, old, round
+            ),
+        }
+    }
+}
+
+impl Error for SimulationError {}
+
+/// Monkeys playing rounds of keep away with the items, their worry levels changing according to
+/// a policy.
+/// Iterating over a simulation plays the next round and returns a snapshot of its end, or why it
+/// could not be played.
+pub struct Simulation {
+    monkeys: Vec<Monkey>,
+    policy: WorryPolicy,
+    rounds: u64,
+}
+
+impl Simulation {
+    /// Fails if the policy cannot compute the operation of a monkey
+    pub fn new(monkeys: &[Monkey], policy: WorryPolicy) -> Result<Self, SimulationError> {
+        if let WorryPolicy::Modulo(_) = policy {
+            let unsupported = monkeys
+                .iter()
+                .position(|monkey| !is_supported_modulo(&monkey.operation));
+            if let Some(monkey_idx) = unsupported {
+                return Err(SimulationError::UnsupportedModulo {
+                    monkey_idx,
+                    operation: monkeys[monkey_idx].display_operation(),
+                });
+            }
+        }
+        Ok(Self {
+            monkeys: monkeys.to_vec(),
+            policy,
+            rounds: 0,
+        })
+    }
+
+    /// Returns the number of rounds played
+    pub fn rounds(&self) -> u64 {
+        self.rounds
+    }
 
-    for monkey_idx in 0..monkeys.len() {
-        for item_idx in 0..monkeys[monkey_idx].items.len() {
-            monkeys[monkey_idx].monkey_business += 1;
-            let item = monkeys[monkey_idx].items[item_idx];
-            let mut new_item_value = apply_operation(&monkeys[monkey_idx].operation, &item);
-            if puzzle_part_one {
-                new_item_value /= 3;
-            } else {
-                new_item_value %= common_divisor;
+    /// Fails if a worry level cannot be computed, for instance when it overflows or when
+    /// dividing by zero. The simulation is then stuck in the middle of the round, and should not
+    /// be played further.
+    pub fn play_round(&mut self) -> Result<(), SimulationError> {
+        for monkey_idx in 0..self.monkeys.len() {
+            let items = std::mem::take(&mut self.monkeys[monkey_idx].items);
+            self.monkeys[monkey_idx].inspections += items.len() as u64;
+            for item in items {
+                let (owner_idx, new_item) = self.inspect_item(monkey_idx, item, self.rounds)?;
+                self.monkeys[owner_idx].items.push(new_item);
             }
+        }
+        self.rounds += 1;
+        Ok(())
+    }
 
-            if new_item_value % monkeys[monkey_idx].test_divisible_by == 0 {
-                new_item_values_and_owners.push((new_item_value, monkeys[monkey_idx].if_true));
-            } else {
-                new_item_values_and_owners.push((new_item_value, monkeys[monkey_idx].if_false));
+    /// Returns the monkey an item is thrown to and its new worry level, once inspected by a
+    /// monkey during the round following `round`
+    fn inspect_item(
+        &self,
+        monkey_idx: usize,
+        item: u128,
+        round: u64,
+    ) -> Result<(usize, u128), SimulationError> {
+        let monkey = &self.monkeys[monkey_idx];
+        let new_item = self.policy.apply(&monkey.operation, item).ok_or_else(|| {
+            SimulationError::InvalidWorryLevel {
+                monkey_idx,
+                operation: monkey.display_operation(),
+                old: item,
+                round: round + 1,
             }
+        })?;
+        if new_item.is_multiple_of(monkey.test_divisible_by) {
+            Ok((monkey.if_true, new_item))
+        } else {
+            Ok((monkey.if_false, new_item))
         }
-        for (item_value, owner_idx) in &new_item_values_and_owners {
-            monkeys[*owner_idx].items.push(*item_value);
+    }
+
+    /// Returns the product of the two highest inspection counts
+    pub fn monkey_business(&self) -> u128 {
+        get_monkey_business(&self.get_inspections())
+    }
+
+    /// Returns the inspection counts after `rounds` more rounds, without playing them.
+    /// Items never interact, so each one is followed alone: once it is held by the same monkey
+    /// with the same worry level at the start of two rounds, it goes through the same
+    /// inspections again and again, and the remaining rounds are skipped. This only ends early
+    /// if worry levels are bounded, as with a modulo policy.
+    pub fn inspections_after(&self, rounds: u64) -> Result<Vec<u64>, SimulationError> {
+        let mut inspections = self.get_inspections();
+        for (owner_idx, monkey) in self.monkeys.iter().enumerate() {
+            for &item in &monkey.items {
+                let item_inspections = self.get_item_inspections(owner_idx, item, rounds)?;
+                for (total, count) in inspections.iter_mut().zip(item_inspections) {
+                    *total += count;
+                }
+            }
         }
-        monkeys[monkey_idx].items.clear();
-        new_item_values_and_owners.clear();
+        Ok(inspections)
+    }
+
+    /// Returns the number of times each monkey inspects an item during the next `rounds` rounds
+    fn get_item_inspections(
+        &self,
+        owner_idx: usize,
+        item: u128,
+        rounds: u64,
+    ) -> Result<Vec<u64>, SimulationError> {
+        // The round at which each state was first seen, and the inspections before each round
+        let mut seen = AHashMap::new();
+        let mut history = vec![vec![0; self.monkeys.len()]];
+        let mut state = (owner_idx, item);
+        for round in 0..rounds {
+            if let Some(&cycle_start) = seen.get(&state) {
+                let period = round - cycle_start;
+                let (cycles, offset) = (
+                    (rounds - cycle_start) / period,
+                    (rounds - cycle_start) % period,
+                );
+                let (start, end) = (&history[cycle_start as usize], &history[round as usize]);
+                return Ok(history[(cycle_start + offset) as usize]
+                    .iter()
+                    .zip(start.iter().zip(end))
+                    .map(|(count, (start, end))| count + cycles * (end - start))
+                    .collect());
+            }
+            seen.insert(state, round);
+            let mut inspections = history[round as usize].clone();
+            state = self.play_item_round(state, &mut inspections, self.rounds + round)?;
+            history.push(inspections);
+        }
+        Ok(history.pop().unwrap_or_default())
+    }
+
+    /// Plays a round for a single item, starting with the monkey holding it. An item can be
+    /// inspected several times per round, when it is thrown to a monkey yet to play.
+    fn play_item_round(
+        &self,
+        (mut owner_idx, mut item): (usize, u128),
+        inspections: &mut [u64],
+        round: u64,
+    ) -> Result<(usize, u128), SimulationError> {
+        loop {
+            inspections[owner_idx] += 1;
+            let (new_owner_idx, new_item) = self.inspect_item(owner_idx, item, round)?;
+            if new_owner_idx <= owner_idx {
+                return Ok((new_owner_idx, new_item));
+            }
+            owner_idx = new_owner_idx;
+            item = new_item;
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            round: self.rounds,
+            inspections: self.get_inspections(),
+            items: self.monkeys.iter().map(|m| m.items.clone()).collect(),
+        }
+    }
+
+    fn get_inspections(&self) -> Vec<u64> {
+        self.monkeys.iter().map(|m| m.inspections).collect()
+    }
+}
+
+impl Iterator for Simulation {
+    type Item = Result<Snapshot, SimulationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.play_round().map(|_| self.snapshot()))
+    }
+}
+
+/// The state of the monkeys at the end of a round, indexed by monkey
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Snapshot {
+    pub round: u64,
+    /// The number of items inspected by each monkey since the start
+    pub inspections: Vec<u64>,
+    /// The worry levels of the items held by each monkey
+    pub items: Vec<Vec<u128>>,
+}
+
+impl Snapshot {
+    /// Returns the product of the two highest inspection counts
+    pub fn monkey_business(&self) -> u128 {
+        get_monkey_business(&self.inspections)
     }
 }
 
-fn apply_operation(operation: &(Value, Op, Value), value: &u128) -> u128 {
-    let left_value = match operation.0 {
-        Value::RawValue(val) => val,
-        Value::Old => *value,
-    };
-    let right_value = match operation.2 {
-        Value::RawValue(val) => val,
-        Value::Old => *value,
-    };
-    match operation.1 {
-        Op::Add => left_value + right_value,
-        Op::Mult => left_value * right_value,
+/// Returns the result of the operation, or `None` if it overflows, is negative or divides by
+/// zero
+fn apply_operation(operation: &(Value, Op, Value), old: u128) -> Option<u128> {
+    let (left, op, right) = operation;
+    let (left, right) = (left.get(old), right.get(old));
+    match op {
+        Op::Add => left.checked_add(right),
+        Op::Sub => left.checked_sub(right),
+        Op::Mult => left.checked_mul(right),
+        Op::Div => left.checked_div(right),
+        Op::Pow => left.checked_pow(u32::try_from(right).ok()?),
     }
 }
 
-#[derive(Debug)]
+/// Returns whether the result of the operation modulo a number can be computed from `old` modulo
+/// that number, which is not the case of divisions, and powers whose exponent is `old`.
+/// Subtractions are not supported either: whether they are negative, which is an error without
+/// a modulo, cannot be told.
+fn is_supported_modulo(operation: &(Value, Op, Value)) -> bool {
+    !matches!(
+        operation,
+        (_, Op::Sub | Op::Div, _) | (_, Op::Pow, Value::Old)
+    )
+}
+
+/// Returns the result of the operation modulo `modulus`, or `None` if it cannot be computed:
+/// unsupported operations, or overflows
+fn apply_operation_modulo(
+    operation: &(Value, Op, Value),
+    old: u128,
+    modulus: u128,
+) -> Option<u128> {
+    let (left, op, right) = operation;
+    let reduce = |value: &Value| value.get(old).checked_rem(modulus);
+    let mul = |a: u128, b: u128| a.checked_mul(b).map(|product| product % modulus);
+    match op {
+        Op::Add => reduce(left)?
+            .checked_add(reduce(right)?)
+            .map(|sum| sum % modulus),
+        Op::Mult => mul(reduce(left)?, reduce(right)?),
+        Op::Sub | Op::Div => None,
+        Op::Pow => {
+            let mut exponent = match right {
+                Value::RawValue(exponent) => *exponent,
+                Value::Old => return None,
+            };
+            let (mut base, mut result) = (reduce(left)?, 1 % modulus);
+            while exponent > 0 {
+                if exponent % 2 == 1 {
+                    result = mul(result, base)?;
+                }
+                base = mul(base, base)?;
+                exponent /= 2;
+            }
+            Some(result)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 enum Value {
     Old,
     RawValue(u128),
 }
 
+impl Value {
+    fn get(&self, old: u128) -> u128 {
+        match self {
+            Value::Old => old,
+            Value::RawValue(value) => *value,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Old => write!(f, "old"),
+            Value::RawValue(value) => write!(f, "{}", value),
+        }
+    }
+}
+
 impl FromStr for Value {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "old" => Ok(Self::Old),
-            _ => Ok(Self::RawValue(s.parse().unwrap())),
+            _ => s
+                .parse()
+                .map(Self::RawValue)
+                .map_err(|_| ParseError::new(s, s, "`old` or a number")),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Op {
     Add,
+    Sub,
     Mult,
+    /// Integer division, rounding down
+    Div,
+    Pow,
+}
+
+impl Op {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Op::Add => "+",
+            Op::Sub => "-",
+            Op::Mult => "*",
+            Op::Div => "/",
+            Op::Pow => "^",
+        }
+    }
 }
 
 impl FromStr for Op {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "*" => Ok(Self::Mult),
             "+" => Ok(Self::Add),
-            _ => Err("Wrong value".to_owned()),
+            "-" => Ok(Self::Sub),
+            "*" => Ok(Self::Mult),
+            "/" => Ok(Self::Div),
+            "^" => Ok(Self::Pow),
+            _ => Err(ParseError::new(s, s, "`+`, `-`, `*`, `/` or `^`")),
         }
     }
 }
 
-#[derive(Debug)]
-struct Monkey {
+#[derive(Debug, Clone)]
+pub struct Monkey {
     _id: usize,
     items: Vec<u128>,
     operation: (Value, Op, Value),
     test_divisible_by: u128,
     if_true: usize,
     if_false: usize,
-    monkey_business: u128,
+    /// The number of items inspected since the start
+    inspections: u64,
 }
 
 impl FromStr for Monkey {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut lines = s.lines();
-        let header = lines.next().ok_or("missing Id")?;
-        let items = lines.next().ok_or("missing items")?;
-        let operation = lines.next().ok_or("missing operation")?;
-        let test = lines.next().ok_or("missing test")?;
-        let raw_if_true = lines.next().ok_or("missing if_true")?;
-        let raw_if_false = lines.next().ok_or("missing if_false")?;
+        let mut next_line = |expected: &str| {
+            lines
+                .next()
+                .ok_or_else(|| ParseError::new(s, &s[s.len()..], expected))
+        };
+        let header = next_line("a monkey header")?;
+        let items = next_line("starting items")?;
+        let operation = next_line("an operation")?;
+        let test = next_line("a test")?;
+        let raw_if_true = next_line("an `If true` line")?;
+        let raw_if_false = next_line("an `If false` line")?;
 
         // ID
-        let parts: Vec<&str> = header.split(':').collect();
-        let id = parts[0]
+        let id = header
+            .trim_end_matches(':')
             .split_whitespace()
             .last()
-            .ok_or("missing number")?
-            .parse()
-            .expect("invalid number");
+            .unwrap_or(header);
+        let id = parse_number(s, id)?;
 
         // Starting items
-        let parts: Vec<&str> = items.split(':').collect();
-        let starting_items = parts[1]
+        let starting_items = get_field(s, items, ": ")?
             .split(',')
-            .map(str::trim)
-            .map(|s| s.parse::<u128>().unwrap())
-            .collect();
+            .map(|item| parse_number(s, item))
+            .collect::<Result<_, _>>()?;
 
         // Operations
-        let parts: Vec<&str> = operation.split("= ").collect();
-        let values: Vec<&str> = parts[1].split_whitespace().collect();
+        let values: Vec<&str> = get_field(s, operation, "= ")?.split_whitespace().collect();
+        if values.len() != 3 {
+            return Err(ParseError::new(
+                s,
+                operation,
+                "an operation like `old * 19`",
+            ));
+        }
         let operation = (
-            Value::from_str(values[0]).unwrap(),
-            Op::from_str(values[1]).unwrap(),
-            Value::from_str(values[2]).unwrap(),
+            Value::from_str(values[0]).map_err(|e| e.within(s, values[0]))?,
+            Op::from_str(values[1]).map_err(|e| e.within(s, values[1]))?,
+            Value::from_str(values[2]).map_err(|e| e.within(s, values[2]))?,
         );
 
         // Divisible By
-        let parts: Vec<&str> = test.split(" by ").collect();
-        let test_divisible_by = parts[1].parse().unwrap();
+        let test_divisible_by = parse_number(s, get_field(s, test, " by ")?)?;
 
         // Is true
-        let parts: Vec<&str> = raw_if_true.split(" monkey ").collect();
-        let if_true = parts[1].parse().unwrap();
+        let if_true = parse_number(s, get_field(s, raw_if_true, " monkey ")?)?;
 
         // Is false
-        let parts: Vec<&str> = raw_if_false.split(" monkey ").collect();
-        let if_false = parts[1].parse().unwrap();
+        let if_false = parse_number(s, get_field(s, raw_if_false, " monkey ")?)?;
 
         Ok(Monkey {
             _id: id,
@@ -178,7 +570,216 @@ impl FromStr for Monkey {
             test_divisible_by,
             if_true,
             if_false,
-            monkey_business: 0,
+            inspections: 0,
         })
     }
 }
+
+impl Monkey {
+    fn display_operation(&self) -> String {
+        let (left, op, right) = &self.operation;
+        format!("new = {} {} {}", left, op.symbol(), right)
+    }
+}
+
+/// Returns the part of `line` after `separator`
+fn get_field<'a>(source: &str, line: &'a str, separator: &str) -> Result<&'a str, ParseError> {
+    line.split_once(separator)
+        .map(|(_, value)| value)
+        .ok_or_else(|| ParseError::new(source, line, format!("a line containing `{}`", separator)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1";
+
+    #[test]
+    fn test_example() {
+        let input = Day11::parse(EXAMPLE).unwrap();
+        assert_eq!(Day11::part_one(&input), 10605);
+        assert_eq!(Day11::part_two(&input), 2713310158);
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let monkeys = Day11::parse(EXAMPLE).unwrap();
+        let mut simulation = Simulation::new(&monkeys, WorryPolicy::Divide(3)).unwrap();
+        let first = simulation.next().unwrap().unwrap();
+        assert_eq!(first.round, 1);
+        assert_eq!(
+            first.items,
+            vec![
+                vec![20, 23, 27, 26],
+                vec![2080, 25, 167, 207, 401, 1046],
+                vec![],
+                vec![]
+            ]
+        );
+        assert_eq!(first.inspections, vec![2, 4, 3, 5]);
+        let last = simulation.by_ref().take(19).last().unwrap().unwrap();
+        assert_eq!(last.round, 20);
+        assert_eq!(last.inspections, vec![101, 95, 7, 105]);
+        assert_eq!(last.monkey_business(), 10605);
+        assert_eq!(simulation.monkey_business(), 10605);
+
+        let policy = WorryPolicy::modulo_lcm(&monkeys).unwrap();
+        let mut simulation = Simulation::new(&monkeys, policy).unwrap();
+        let inspections = simulation.nth(999).unwrap().unwrap().inspections;
+        assert_eq!(inspections, vec![5204, 4792, 199, 5192]);
+    }
+
+    #[test]
+    fn test_operations() {
+        let operation = |text: &str| {
+            let values: Vec<&str> = text.split(' ').collect();
+            let op = Op::from_str(values[1]).unwrap();
+            (values[0].parse().unwrap(), op, values[2].parse().unwrap())
+        };
+        assert_eq!(apply_operation(&operation("old - 3"), 10), Some(7));
+        assert_eq!(apply_operation(&operation("old - 3"), 2), None);
+        assert_eq!(apply_operation(&operation("old / 4"), 10), Some(2));
+        assert_eq!(apply_operation(&operation("old / 0"), 10), None);
+        assert_eq!(apply_operation(&operation("2 ^ old"), 10), Some(1024));
+        assert_eq!(apply_operation(&operation("old ^ 200"), 10), None);
+
+        assert_eq!(apply_operation_modulo(&operation("old - 3"), 2, 7), None);
+        assert_eq!(
+            apply_operation_modulo(&operation("old ^ 200"), 10, 7),
+            Some(2)
+        );
+        assert_eq!(apply_operation_modulo(&operation("2 ^ old"), 10, 7), None);
+        assert_eq!(apply_operation_modulo(&operation("old / 2"), 10, 7), None);
+
+        let policy = WorryPolicy::Custom(Box::new(|worry| worry.saturating_sub(1)));
+        assert_eq!(policy.apply(&operation("old * old"), 5), Some(24));
+        assert!(Op::from_str("%").is_err());
+    }
+
+    #[test]
+    fn test_unsupported_modulo() {
+        for (operation, text) in [
+            ("old / 2", "new = old / 2"),
+            ("3 ^ old", "new = 3 ^ old"),
+            ("old - 1", "new = old - 1"),
+        ] {
+            let monkeys = Day11::parse(&EXAMPLE.replacen("old + 6", operation, 1)).unwrap();
+            let policy = WorryPolicy::modulo_lcm(&monkeys).unwrap();
+            let error = Simulation::new(&monkeys, policy).err();
+            assert_eq!(
+                error,
+                Some(SimulationError::UnsupportedModulo {
+                    monkey_idx: 1,
+                    operation: text.to_string()
+                })
+            );
+            assert!(Simulation::new(&monkeys, WorryPolicy::Divide(3)).is_ok());
+        }
+        let error = SimulationError::UnsupportedModulo {
+            monkey_idx: 1,
+            operation: "new = old / 2".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "operation `new = old / 2` of monkey 1 is not supported modulo"
+        );
+    }
+
+    #[test]
+    fn test_invalid_worry_levels() {
+        // Without a modulo, worry levels keep growing until they overflow
+        let monkeys = Day11::parse(EXAMPLE).unwrap();
+        let error = play_rounds(&monkeys, 10000, WorryPolicy::Divide(1)).unwrap_err();
+        assert!(matches!(error, SimulationError::InvalidWorryLevel { .. }));
+        let simulation = Simulation::new(&monkeys, WorryPolicy::Divide(1)).unwrap();
+        let error = simulation.inspections_after(10000).unwrap_err();
+        assert!(matches!(error, SimulationError::InvalidWorryLevel { .. }));
+
+        let monkeys = Day11::parse(&EXAMPLE.replacen("old + 6", "old - 80", 1)).unwrap();
+        let mut simulation = Simulation::new(&monkeys, WorryPolicy::Divide(3)).unwrap();
+        let error = simulation.next().unwrap().unwrap_err();
+        assert_eq!(
+            error,
+            SimulationError::InvalidWorryLevel {
+                monkey_idx: 1,
+                operation: "new = old - 80".to_string(),
+                old: 54,
+                round: 1
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "operation `new = old - 80` of monkey 1 cannot be computed with old = 54 in round 1"
+        );
+
+        assert_eq!(lcm(u128::MAX, 2), None);
+        let huge_divisor = format!("divisible by {}", u128::MAX);
+        let monkeys = Day11::parse(&EXAMPLE.replacen("divisible by 23", &huge_divisor, 1)).unwrap();
+        assert_eq!(
+            WorryPolicy::modulo_lcm(&monkeys).err(),
+            Some(SimulationError::ModulusOverflow)
+        );
+        let error = Day11::parse(&EXAMPLE.replacen("divisible by 23", "divisible by 0", 1));
+        assert_eq!(error.unwrap_err().expected, "a divisor above 0");
+    }
+
+    #[test]
+    fn test_skip_rounds() {
+        let monkeys = Day11::parse(EXAMPLE).unwrap();
+        let modulo = || WorryPolicy::modulo_lcm(&monkeys).unwrap();
+        let mut simulation = Simulation::new(&monkeys, modulo()).unwrap();
+        simulation.by_ref().take(7).last();
+        let expected = simulation
+            .by_ref()
+            .take(1234)
+            .last()
+            .unwrap()
+            .unwrap()
+            .inspections;
+        simulation = Simulation::new(&monkeys, modulo()).unwrap();
+        simulation.by_ref().take(7).last();
+        assert_eq!(simulation.inspections_after(1234), Ok(expected));
+
+        let inspections = simulation.inspections_after(1_000_000_000_000 - 7);
+        assert_eq!(
+            inspections,
+            Ok(vec![
+                5217653508757,
+                4782346491239,
+                193256578955,
+                5202028508760
+            ])
+        );
+
+        // Rounds are played one by one until a state repeats, if ever
+        let simulation = Simulation::new(&monkeys, WorryPolicy::Divide(3)).unwrap();
+        assert_eq!(simulation.inspections_after(20), Ok(vec![101, 95, 7, 105]));
+    }
+}
@@ -1,49 +1,62 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::grid::{Grid, Position};
+use crate::utils::parse_error::ParseError;
+use crate::utils::search;
 use rayon::prelude::*;
-use std::collections::{HashMap, VecDeque};
 
-pub fn day_12() {
-    let elevation_map = get_input();
+pub struct Day12;
 
-    let solution_1 = part_one(&elevation_map);
-    println!("\t- Solution A is : {}", &solution_1);
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    type Input = Grid<i8>;
+    type PartOne = usize;
+    type PartTwo = usize;
 
-    let solution_2 = part_two(&elevation_map);
-    println!("\t- Solution B is : {}", &solution_2);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(elevation_map: &Self::Input) -> Self::PartOne {
+        part_one(elevation_map)
+    }
+
+    fn part_two(elevation_map: &Self::Input) -> Self::PartTwo {
+        part_two(elevation_map)
+    }
 }
 
-fn get_input() -> Vec<Vec<i8>> {
-    get_file("./src/day_12/input.txt")
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|c| match c {
-                    'S' => 0,
-                    'E' => 27,
-                    'a'..='z' => (c as i8 - 'a' as i8) + 1,
-                    _ => 0,
-                })
-                .collect()
-        })
-        .collect()
+fn get_input(input: &str) -> Result<Grid<i8>, ParseError> {
+    let elevation_map = Grid::parse(input, "`S`, `E` or a letter", |c| match c {
+        'S' => Some(0),
+        'E' => Some(27),
+        'a'..='z' => Some((c as i8 - 'a' as i8) + 1),
+        _ => None,
+    })?;
+    for (mark, value) in [("S", 0), ("E", 27)] {
+        if elevation_map.iter().filter(|(_, v)| **v == value).count() != 1 {
+            return Err(ParseError::new(
+                input,
+                "",
+                format!("a single `{}` mark", mark),
+            ));
+        }
+    }
+    Ok(elevation_map)
 }
 
-fn part_one(elevation_map: &[Vec<i8>]) -> usize {
+fn part_one(elevation_map: &Grid<i8>) -> usize {
     let start = get_coord_from_value(elevation_map, 0);
     let goal = get_coord_from_value(elevation_map, 27);
     bfs(elevation_map, &start, &goal).unwrap()
 }
 
-fn part_two(elevation_map: &[Vec<i8>]) -> usize {
+fn part_two(elevation_map: &Grid<i8>) -> usize {
     // Retrieve all points we can start from
-    let mut start_points = vec![];
-    for (y, row) in elevation_map.iter().enumerate() {
-        for (x, val) in row.iter().enumerate() {
-            if *val == 1 {
-                start_points.push((y, x));
-            }
-        }
-    }
+    let start_points: Vec<Position> = elevation_map
+        .iter()
+        .filter(|(_, val)| **val == 1)
+        .map(|(position, _)| position)
+        .collect();
 
     let goal = get_coord_from_value(elevation_map, 27);
     start_points
@@ -53,85 +66,44 @@ fn part_two(elevation_map: &[Vec<i8>]) -> usize {
         .unwrap()
 }
 
-fn bfs(elevation_map: &[Vec<i8>], start: &Point, goal: &Point) -> Option<usize> {
-    let mut frontier = VecDeque::new();
-    let mut came_from = HashMap::new();
-    frontier.push_back(*start);
-
-    while let Some(current_node) = frontier.pop_front() {
-        for child_node in get_children(elevation_map, &current_node) {
-            came_from.entry(child_node).or_insert_with(|| {
-                frontier.push_back(child_node);
-                current_node
-            });
-            if child_node == *goal {
-                return Some(get_path_len(&came_from, start, goal));
-            }
-        }
-        came_from.entry(current_node).or_insert_with(|| {
-            frontier.push_back(current_node);
-            current_node
-        });
-    }
-    None
+/// Returns the number of steps of the shortest path from `start` to `goal`, if any
+fn bfs(elevation_map: &Grid<i8>, start: &Position, goal: &Position) -> Option<usize> {
+    search::bfs(
+        *start,
+        |point| get_children(elevation_map, point),
+        |point| point == goal,
+    )
+    .goal_cost()
 }
 
-pub fn get_path_len(came_from: &HashMap<Point, Point>, start: &Point, end: &Point) -> usize {
-    let mut path_len = 0;
-    let mut current = end;
-
-    while current != start {
-        path_len += 1;
-        current = came_from.get(current).unwrap();
-    }
-    path_len
+fn get_coord_from_value(graph: &Grid<i8>, value: i8) -> Position {
+    graph
+        .find(|val| *val == value)
+        .unwrap_or_else(|| panic!("Value {} not found", value))
 }
 
-type Point = (usize, usize);
-
-fn get_coord_from_value(graph: &[Vec<i8>], value: i8) -> Point {
-    for (y, row) in graph.iter().enumerate() {
-        for (x, val) in row.iter().enumerate() {
-            if *val == value {
-                return (y, x);
-            }
-        }
-    }
-    panic!("Value {} not found", value)
+/// Returns the neighbours which are at most one step higher
+fn get_children(graph: &Grid<i8>, point: &Position) -> Vec<Position> {
+    graph
+        .neighbours(*point)
+        .filter(|new_point| graph[*point] - graph[*new_point] >= -1)
+        .collect()
 }
 
-fn get_children(graph: &[Vec<i8>], point: &Point) -> Vec<Point> {
-    let mut children = vec![];
-    if point.0 > 0 {
-        let new_point = (point.0 - 1, point.1);
-        if get_value(graph, point) - get_value(graph, &new_point) >= -1 {
-            children.push((point.0 - 1, point.1))
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Sabqponm
+abcryxxl
+accszExk
+acctuvwj
+abdefghi";
+
+    #[test]
+    fn test_example() {
+        let input = Day12::parse(EXAMPLE).unwrap();
+        assert_eq!(Day12::part_one(&input), 31);
+        assert_eq!(Day12::part_two(&input), 29);
     }
-    if point.0 < graph.len() - 1 {
-        let new_point = (point.0 + 1, point.1);
-        if get_value(graph, point) - get_value(graph, &new_point) >= -1 {
-            children.push((point.0 + 1, point.1))
-        }
-    }
-
-    if point.1 > 0 {
-        let new_point = (point.0, point.1 - 1);
-
-        if get_value(graph, point) - get_value(graph, &new_point) >= -1 {
-            children.push((point.0, point.1 - 1))
-        }
-    }
-    if point.1 < graph[0].len() - 1 {
-        let new_point = (point.0, point.1 + 1);
-        if get_value(graph, point) - get_value(graph, &new_point) >= -1 {
-            children.push((point.0, point.1 + 1))
-        }
-    }
-
-    children
-}
-
-fn get_value(graph: &[Vec<i8>], point: &Point) -> i8 {
-    graph[point.0][point.1]
 }
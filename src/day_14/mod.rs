@@ -1,39 +1,52 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::grid::{Grid, Position};
+use crate::utils::inputs::{parse_lines, parse_number};
+use crate::utils::parse_error::ParseError;
 use std::cmp::max;
 use std::ops::RangeInclusive;
-use std::str::FromStr;
 
-pub fn day_14() {
-    let mut cave = get_input(1);
-    let solution_1 = part_one(&mut cave);
-    println!("\t- Solution 1 is : {}", solution_1);
+const SAND_SOURCE: Position = (500, 0);
 
-    let mut cave = get_input(2);
-    let solution_2 = part_two(&mut cave);
-    println!("\t- Solution 2 is : {}", solution_2);
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    type Input = Vec<Wall>;
+    type PartOne = u32;
+    type PartTwo = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(walls: &Self::Input) -> Self::PartOne {
+        part_one(&mut get_cave(walls, 1))
+    }
+
+    fn part_two(walls: &Self::Input) -> Self::PartTwo {
+        part_two(&mut get_cave(walls, 2))
+    }
 }
 
-fn get_input(part: usize) -> Cave {
-    let file = get_file("./src/day_14/input.txt");
-    let mut walls = vec![];
-    let (mut max_x, mut max_y) = (usize::MIN, usize::MIN);
+type Wall = Vec<(usize, usize)>;
 
-    for line in file.lines() {
-        let wall_coordinates =
-            parse_wall_coordinates(line).expect("Cannot parse {line} coordinates");
-        for (x, y) in &wall_coordinates {
-            max_x = max(max_x, *x);
-            max_y = max(max_y, *y);
-        }
-        walls.push(wall_coordinates);
+fn get_input(input: &str) -> Result<Vec<Wall>, ParseError> {
+    parse_lines(input, parse_wall_coordinates)
+}
+
+fn get_cave(walls: &[Wall], part: usize) -> Cave {
+    let (mut max_x, mut max_y) = (usize::MIN, usize::MIN);
+    for (x, y) in walls.iter().flatten() {
+        max_x = max(max_x, *x);
+        max_y = max(max_y, *y);
     }
     if part == 2 {
         max_x = 1000;
         max_y += 2
     }
-    let mut cave = Cave::new(vec![false; (max_x + 1) * (max_y + 1)], max_x, max_y);
+    let mut cave = Cave::new(Grid::new(max_x + 1, max_y + 1, false), max_y);
 
-    for wall_coordinates in &walls {
+    for wall_coordinates in walls {
         for window in wall_coordinates.windows(2) {
             cave.add_wall(window[0].0, window[0].1, window[1].0, window[1].1);
         }
@@ -41,31 +54,29 @@ fn get_input(part: usize) -> Cave {
     cave
 }
 
-pub fn parse_wall_coordinates(s: &str) -> Result<Vec<(usize, usize)>, std::num::ParseIntError> {
+pub fn parse_wall_coordinates(s: &str) -> Result<Vec<(usize, usize)>, ParseError> {
     s.split(" -> ")
         .map(|part| {
-            let mut coords = part.split(',');
-            let x = usize::from_str(coords.next().unwrap())?;
-            let y = usize::from_str(coords.next().unwrap())?;
-            Ok((x, y))
+            let (x, y) = part
+                .split_once(',')
+                .ok_or_else(|| ParseError::new(s, part, "coordinates like `498,4`"))?;
+            Ok((parse_number(s, x)?, parse_number(s, y)?))
         })
         .collect()
 }
 
 struct Cave {
-    tiles: Vec<bool>,
+    tiles: Grid<bool>,
     max_x: usize,
     max_y: usize,
-    width: usize,
 }
 
 impl Cave {
-    fn new(tiles: Vec<bool>, max_x: usize, max_y: usize) -> Self {
+    fn new(tiles: Grid<bool>, max_y: usize) -> Self {
         Self {
+            max_x: tiles.width() - 1,
             tiles,
-            max_x,
             max_y,
-            width: max_x + 1,
         }
     }
 
@@ -73,60 +84,43 @@ impl Cave {
         // horizontal wall
         if start_x != end_x {
             for x in get_range(start_x, end_x) {
-                let index = start_y * self.width + x;
-                self.tiles[index] = true;
+                self.tiles[(x, start_y)] = true;
             }
         }
         // vertical wall
         else {
             for y in get_range(start_y, end_y) {
-                let index = y * self.width + start_x;
-                self.tiles[index] = true;
+                self.tiles[(start_x, y)] = true;
             }
         }
     }
 
-    fn get_next_sand_drop_coordinates(&mut self) -> Option<usize> {
-        if let Some(sand_coordinates) = self.get_sand_coordinates(500) {
+    fn get_next_sand_drop_coordinates(&mut self) -> Option<Position> {
+        if let Some(sand_coordinates) = self.get_sand_coordinates(SAND_SOURCE) {
             return Some(sand_coordinates);
         }
         None
     }
-    fn get_sand_coordinates(&self, mut sand_coordinate: usize) -> Option<usize> {
-        for _ in self.get_y(sand_coordinate)..self.max_y {
-            let next_position = sand_coordinate + self.width;
-
-            if self.tiles[next_position] {
-                let bottom_left = sand_coordinate + self.max_x;
-                if self.get_x(sand_coordinate) != 0 && !self.tiles[bottom_left] {
+    fn get_sand_coordinates(&self, (x, mut y): Position) -> Option<Position> {
+        while y < self.max_y {
+            if self.tiles[(x, y + 1)] {
+                if x != 0 && !self.tiles[(x - 1, y + 1)] {
                     // Sand roll left
-                    return self.get_sand_coordinates(bottom_left);
+                    return self.get_sand_coordinates((x - 1, y + 1));
                 }
 
-                let bottom_right = sand_coordinate + self.max_x + 2;
-                if self.get_x(sand_coordinate) < self.max_x && !self.tiles[bottom_right] {
+                if x < self.max_x && !self.tiles[(x + 1, y + 1)] {
                     // Sand roll right
-                    return self.get_sand_coordinates(bottom_right);
+                    return self.get_sand_coordinates((x + 1, y + 1));
                 }
                 // Sand land and stop
-                return Some(sand_coordinate);
-            }
-            // Sand fall out of bound
-            if self.get_y(sand_coordinate) > self.max_y {
-                return None;
+                return Some((x, y));
             }
-            sand_coordinate += self.width
+            y += 1
         }
+        // Sand fall out of bound
         None
     }
-
-    fn get_y(&self, coordinate: usize) -> usize {
-        coordinate / self.width
-    }
-
-    fn get_x(&self, coordinate: usize) -> usize {
-        coordinate % self.width
-    }
 }
 
 fn get_range(a: usize, b: usize) -> RangeInclusive<usize> {
@@ -153,10 +147,25 @@ fn part_two(cave: &mut Cave) -> u32 {
     for count in 0..1_000_000 {
         if let Some(sand_coordinate) = cave.get_next_sand_drop_coordinates() {
             cave.tiles[sand_coordinate] = true;
-            if sand_coordinate == 500 {
+            if sand_coordinate == SAND_SOURCE {
                 return count + 1;
             }
         }
     }
     panic!("Did not finished sand simulation")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "498,4 -> 498,6 -> 496,6
+503,4 -> 502,4 -> 502,9 -> 494,9";
+
+    #[test]
+    fn test_example() {
+        let input = Day14::parse(EXAMPLE).unwrap();
+        assert_eq!(Day14::part_one(&input), 24);
+        assert_eq!(Day14::part_two(&input), 93);
+    }
+}
@@ -1,9 +1,11 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::geometry::Point2;
+use crate::utils::inputs::{parse_lines, parse_number};
+use crate::utils::parse_error::ParseError;
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::cmp::{max, min};
 use std::collections::HashMap;
-use std::str::FromStr;
 
 lazy_static! {
     static ref RE_PARSE_LINE: Regex = Regex::new(r"x=(-?\d+), y=(-?\d+)").unwrap();
@@ -12,55 +14,61 @@ lazy_static! {
 type CustomRangeInt = i64;
 type CustomRange = (CustomRangeInt, CustomRangeInt);
 
-pub fn day_15() {
-    let sensors = get_input();
+pub struct Day15;
 
-    let solution_1 = part_one(&sensors, 2000000);
-    println!("\t- Solution 1 is : {}", solution_1);
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    type Input = Vec<Sensor>;
+    type PartOne = CustomRangeInt;
+    type PartTwo = CustomRangeInt;
 
-    let solution_2 = part_two(&sensors);
-    println!("\t- Solution 2 is : {}", solution_2);
-}
-
-#[derive(Debug)]
-struct Point {
-    y: CustomRangeInt,
-    x: CustomRangeInt,
-}
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
 
-impl Point {
-    fn new(x: CustomRangeInt, y: CustomRangeInt) -> Self {
-        Self { x, y }
+    fn part_one(sensors: &Self::Input) -> Self::PartOne {
+        part_one(sensors, 2000000)
     }
-}
 
-fn manhattan_distance(p1: &Point, p2: &Point) -> CustomRangeInt {
-    (p1.x - p2.x).abs() + (p1.y - p2.y).abs()
+    fn part_two(sensors: &Self::Input) -> Self::PartTwo {
+        part_two(sensors, 4000000)
+    }
 }
 
 #[derive(Debug)]
-struct Sensor {
-    center: Point,
+pub struct Sensor {
+    center: Point2<CustomRangeInt>,
     distance: CustomRangeInt,
 }
 
-fn get_input() -> Vec<Sensor> {
-    get_file("./src/day_15/input.txt")
-        .lines()
-        .map(parse_line)
-        .collect()
+fn get_input(input: &str) -> Result<Vec<Sensor>, ParseError> {
+    let sensors = parse_lines(input, parse_line)?;
+    if sensors.is_empty() {
+        return Err(ParseError::new(input, input, "at least one sensor"));
+    }
+    Ok(sensors)
 }
 
-fn parse_line(line: &str) -> Sensor {
-    let mut points = RE_PARSE_LINE.captures_iter(line).map(|cap| {
-        let x = CustomRangeInt::from_str(&cap[1]).unwrap();
-        let y = CustomRangeInt::from_str(&cap[2]).unwrap();
-        Point::new(x, y)
-    });
-    let center = points.next().unwrap();
-    let beacon = points.next().unwrap();
-    let distance = manhattan_distance(&center, &beacon);
-    Sensor { center, distance }
+fn parse_line(line: &str) -> Result<Sensor, ParseError> {
+    let mut points = RE_PARSE_LINE
+        .captures_iter(line)
+        .map(|cap| {
+            let x = parse_number(line, cap.get(1).unwrap().as_str())?;
+            let y = parse_number(line, cap.get(2).unwrap().as_str())?;
+            Ok(Point2::new(x, y))
+        })
+        .collect::<Result<Vec<Point2<CustomRangeInt>>, ParseError>>()?;
+    if points.len() != 2 {
+        return Err(ParseError::new(
+            line,
+            line,
+            "a sensor and a beacon positions",
+        ));
+    }
+    let beacon = points.pop().unwrap();
+    let center = points.pop().unwrap();
+    let distance = center.manhattan_distance(&beacon);
+    Ok(Sensor { center, distance })
 }
 
 fn part_one(sensors: &[Sensor], target_y: CustomRangeInt) -> CustomRangeInt {
@@ -72,12 +80,10 @@ fn part_one(sensors: &[Sensor], target_y: CustomRangeInt) -> CustomRangeInt {
         populate_ground_map(&mut ground_map, sensor, x_boundaries, y_boundaries);
     }
 
+    // No sensor may cover the row
     ground_map
         .get(&target_y)
-        .expect("Y {target_y} not found in the ground map")
-        .iter()
-        .map(|(a, b)| (b - a).abs())
-        .sum()
+        .map_or(0, |row| row.iter().map(|(a, b)| (b - a).abs()).sum())
 }
 
 fn populate_ground_map(
@@ -93,7 +99,7 @@ fn populate_ground_map(
         }
         let range_start = max(sensor.center.x - y_delta, x_boundaries.0);
         let range_end = min(sensor.center.x + y_delta, x_boundaries.1);
-        ground_map.entry(current_y).or_insert_with(Vec::new);
+        ground_map.entry(current_y).or_default();
         update_row(
             ground_map.get_mut(&current_y).unwrap(),
             range_start,
@@ -107,7 +113,7 @@ fn populate_ground_map(
         }
         let range_start = max(sensor.center.x - y_delta, x_boundaries.0);
         let range_end = min(sensor.center.x + y_delta, x_boundaries.1);
-        ground_map.entry(current_y).or_insert_with(Vec::new);
+        ground_map.entry(current_y).or_default();
         update_row(
             ground_map.get_mut(&current_y).unwrap(),
             range_start,
@@ -141,8 +147,8 @@ fn update_row(row: &mut Vec<CustomRange>, range_start: CustomRangeInt, range_end
 }
 
 fn get_x_boundaries(sensors: &[Sensor]) -> CustomRange {
-    let mut min_x = CustomRangeInt::max_value();
-    let mut max_x = CustomRangeInt::min_value();
+    let mut min_x = CustomRangeInt::MAX;
+    let mut max_x = CustomRangeInt::MIN;
     for sensor in sensors {
         min_x = min(min_x, sensor.center.x - sensor.distance);
         max_x = max(max_x, sensor.center.x + sensor.distance);
@@ -151,8 +157,8 @@ fn get_x_boundaries(sensors: &[Sensor]) -> CustomRange {
 }
 
 fn get_y_boundaries(sensors: &[Sensor]) -> CustomRange {
-    let mut min_y = CustomRangeInt::max_value();
-    let mut max_y = CustomRangeInt::min_value();
+    let mut min_y = CustomRangeInt::MAX;
+    let mut max_y = CustomRangeInt::MIN;
     for sensor in sensors {
         min_y = min(min_y, sensor.center.y - sensor.distance);
         max_y = max(max_y, sensor.center.y + sensor.distance);
@@ -160,16 +166,16 @@ fn get_y_boundaries(sensors: &[Sensor]) -> CustomRange {
     (min_y, max_y)
 }
 
-fn part_two(sensors: &[Sensor]) -> CustomRangeInt {
-    let x_boundaries = (0, 4000000);
-    let y_boundaries = (0, 4000000);
+fn part_two(sensors: &[Sensor], max_coordinate: CustomRangeInt) -> CustomRangeInt {
+    let x_boundaries = (0, max_coordinate);
+    let y_boundaries = (0, max_coordinate);
 
     let mut ground_map = HashMap::new();
     for sensor in sensors {
         populate_ground_map(&mut ground_map, sensor, x_boundaries, y_boundaries);
     }
-    let reference_row = vec![(0, 4000000)];
-    for y in 0..4000000 {
+    let reference_row = vec![(0, max_coordinate)];
+    for y in 0..max_coordinate {
         if let Some(value) = ground_map.get(&y) {
             if value != &reference_row {
                 let x = value[0].1 + 1;
@@ -179,4 +185,42 @@ fn part_two(sensors: &[Sensor]) -> CustomRangeInt {
     }
     panic!("Solution not found for Day 15 Part 2")
 }
-// cargo run 15  55.30s user 0.95s system 99% cpu 56.619 total
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3";
+
+    #[test]
+    fn test_example() {
+        // The example looks at the row 10 and a search area up to 20, instead of 2000000 and 4000000
+        let sensors = Day15::parse(EXAMPLE).unwrap();
+        assert_eq!(part_one(&sensors, 10), 26);
+        assert_eq!(part_two(&sensors, 20), 56000011);
+        assert_eq!(part_one(&sensors, 1000), 0);
+    }
+
+    #[test]
+    fn test_invalid_sensors() {
+        assert_eq!(
+            Day15::parse("").unwrap_err().expected,
+            "at least one sensor"
+        );
+        let error = Day15::parse("Sensor at x=2, y=18").unwrap_err();
+        assert_eq!(error.expected, "a sensor and a beacon positions");
+    }
+}
@@ -1,12 +1,14 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::inputs::{parse_lines, parse_number};
+use crate::utils::parse_error::ParseError;
+use crate::utils::search::bfs_reach;
 use ahash::AHashMap;
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use rayon::prelude::*;
 use regex::Regex;
 use std::cmp::max;
-use std::collections::hash_map::Entry;
-use std::collections::{HashMap, VecDeque};
+use std::collections::HashMap;
 
 lazy_static! {
     static ref RE_PARSE_LINE: Regex = Regex::new(
@@ -24,7 +26,7 @@ struct Path {
 }
 
 #[derive(Debug, Clone)]
-struct Cave {
+pub struct Cave {
     idx: usize,
     flow_rate: usize,
     paths: Vec<Path>,
@@ -40,28 +42,48 @@ impl Default for Cave {
     }
 }
 
-pub fn day_16() {
-    // ========================================================================
-    // Part 1
-    let caves = get_input();
-    let opened_valves = 0;
-    let mut cache = AHashMap::with_capacity(45_000_000);
-    let solution_1 = explore_caves_cached(&mut cache, &caves, *START_IDX, 30, opened_valves, 0);
-    println!("\t- Solution 1 is : {}", solution_1);
+pub struct Day16;
 
-    // ========================================================================
-    // Part 2
-    let solution_2 = part_two_init(&mut cache, &caves);
-    println!("\t- Solution 2 is : {}", solution_2);
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+    type Input = Vec<Cave>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(caves: &Self::Input) -> Self::PartOne {
+        part_one(caves)
+    }
+
+    fn part_two(caves: &Self::Input) -> Self::PartTwo {
+        part_two(caves)
+    }
 }
 
-fn get_input() -> Vec<Cave> {
+fn get_input(input: &str) -> Result<Vec<Cave>, ParseError> {
     // ========================================================================
     // Get caves raw values
-    let raw_caves: HashMap<usize, Cave> = get_file("./src/day_16/input.txt")
-        .lines()
-        .map(parse_line)
-        .collect();
+    let raw_caves: HashMap<usize, Cave> = parse_lines(input, parse_line)?.into_iter().collect();
+    if !raw_caves.contains_key(&START_IDX) {
+        return Err(ParseError::new(input, "", "a valve named `AA`"));
+    }
+    for cave in raw_caves.values() {
+        if let Some(path) = cave
+            .paths
+            .iter()
+            .find(|p| !raw_caves.contains_key(&p.dst_name))
+        {
+            let name = usize_to_str(path.dst_name);
+            return Err(ParseError::new(
+                input,
+                &name,
+                "a valve defined in the input",
+            ));
+        }
+    }
 
     // ========================================================================
     // Reduce caves paths.
@@ -94,37 +116,52 @@ fn get_input() -> Vec<Cave> {
         }
         valves[*new_idx as usize] = new_valve;
     }
-    valves
+    Ok(valves)
 }
 
-fn parse_line(line: &str) -> (usize, Cave) {
-    let caps = RE_PARSE_LINE
-        .captures(line)
-        .unwrap_or_else(|| panic!("Cannot parse line {}", line));
-
-    let name = caps[1].to_string();
-    let flow_rate = caps[2].parse::<usize>().unwrap();
-    let links: Vec<Path> = caps[6]
+fn parse_line(line: &str) -> Result<(usize, Cave), ParseError> {
+    let caps = RE_PARSE_LINE.captures(line).ok_or_else(|| {
+        ParseError::new(
+            line,
+            line,
+            "a line like `Valve AA has flow rate=0; tunnels lead to valves DD, II`",
+        )
+    })?;
+
+    let name = caps.get(1).unwrap().as_str();
+    let flow_rate = parse_number(line, caps.get(2).unwrap().as_str())?;
+    let links = caps
+        .get(6)
+        .unwrap()
+        .as_str()
         .split(", ")
-        .map(|s| Path {
-            distance: 1,
-            dst_name: str_to_usize(s).unwrap(),
+        .map(|s| {
+            Ok(Path {
+                distance: 1,
+                dst_name: get_valve_name(line, s)?,
+            })
         })
-        .collect();
+        .collect::<Result<Vec<Path>, ParseError>>()?;
 
-    (
-        str_to_usize(&name).unwrap(),
+    let idx = get_valve_name(line, name)?;
+    Ok((
+        idx,
         Cave {
-            idx: str_to_usize(&name).unwrap(),
+            idx,
             flow_rate,
             paths: links,
         },
-    )
+    ))
+}
+
+fn get_valve_name(line: &str, name: &str) -> Result<usize, ParseError> {
+    str_to_usize(name)
+        .ok_or_else(|| ParseError::new(line, name, "a valve name of two uppercase letters"))
 }
 
 fn str_to_usize(input: &str) -> Option<usize> {
     let bytes = input.as_bytes();
-    if bytes.len() == 2 {
+    if bytes.len() == 2 && bytes.iter().all(u8::is_ascii_uppercase) {
         let a = bytes[0] as usize - 65;
         let b = bytes[1] as usize - 65;
 
@@ -134,6 +171,13 @@ fn str_to_usize(input: &str) -> Option<usize> {
     }
 }
 
+fn usize_to_str(input: usize) -> String {
+    [input >> 5, input & 0b11111]
+        .iter()
+        .map(|v| (*v as u8 + 65) as char)
+        .collect()
+}
+
 fn reduce_caves_paths(paths: &HashMap<usize, Cave>) -> HashMap<usize, Cave> {
     let valves: Vec<_> = paths
         .values()
@@ -151,58 +195,33 @@ fn reduce_caves_paths(paths: &HashMap<usize, Cave>) -> HashMap<usize, Cave> {
 }
 
 fn reduce_cave_links(paths: &HashMap<usize, Cave>, cave_idx: usize) -> Cave {
-    let came_from = bfs(paths, cave_idx);
-    let positive_flow_rates: Vec<_> = came_from
-        .keys()
-        .filter(|&&k| paths.get(&k).unwrap().flow_rate > 0)
-        .cloned()
-        .collect();
+    // Only go through the caves whose valve is not worth opening
+    let search = bfs_reach(cave_idx, |current_valve| {
+        let cave = paths.get(current_valve).unwrap();
+        let is_passage = *current_valve == cave_idx || cave.flow_rate == 0;
+        cave.paths
+            .iter()
+            .filter(move |_| is_passage)
+            .map(|child| child.dst_name)
+    });
     let mut valve = Cave {
         idx: cave_idx,
         flow_rate: paths.get(&cave_idx).unwrap().flow_rate,
         paths: vec![],
     };
-    for positive_flow_rate in positive_flow_rates {
-        if positive_flow_rate.eq(&cave_idx) {
+    for positive_flow_rate in search.reached() {
+        if positive_flow_rate.eq(&cave_idx) || paths.get(positive_flow_rate).unwrap().flow_rate == 0
+        {
             continue;
         }
-        let dist_from_start = get_path_len(&came_from, cave_idx, positive_flow_rate);
         valve.paths.push(Path {
-            distance: dist_from_start,
-            dst_name: positive_flow_rate,
+            distance: search.cost(positive_flow_rate).unwrap(),
+            dst_name: *positive_flow_rate,
         })
     }
     valve
 }
 
-fn bfs(paths: &HashMap<usize, Cave>, start: usize) -> HashMap<usize, usize> {
-    let mut frontier = VecDeque::new();
-    let mut came_from = HashMap::new();
-    frontier.push_back(start);
-
-    while let Some(current_valve) = frontier.pop_front() {
-        for child in &paths.get(&current_valve).unwrap().paths {
-            if let Entry::Vacant(e) = came_from.entry(child.dst_name) {
-                e.insert(current_valve);
-                if paths.get(&child.dst_name).unwrap().flow_rate == 0 {
-                    frontier.push_back(child.dst_name)
-                }
-            }
-        }
-    }
-    came_from
-}
-pub fn get_path_len(came_from: &HashMap<usize, usize>, start: usize, end: usize) -> usize {
-    let mut path_len = 0;
-    let mut current = end;
-
-    while current != start {
-        path_len += 1;
-        current = *came_from.get(&current).unwrap();
-    }
-    path_len
-}
-
 type CacheKeyType = (usize, usize, u16, usize);
 
 fn explore_caves_cached(
@@ -287,7 +306,14 @@ fn explore_caves(
     max_pressure
 }
 
-fn part_two_init(cache: &mut AHashMap<CacheKeyType, usize>, valves: &Vec<Cave>) -> usize {
+fn part_one(caves: &Vec<Cave>) -> usize {
+    let opened_valves = 0;
+    let mut cache = AHashMap::with_capacity(45_000_000);
+    explore_caves_cached(&mut cache, caves, *START_IDX, 30, opened_valves, 0)
+}
+
+fn part_two(valves: &Vec<Cave>) -> usize {
+    let mut cache = AHashMap::with_capacity(45_000_000);
     let f_valves: Vec<Cave> = valves
         .iter()
         .filter(|&v| v.idx != *START_IDX)
@@ -305,8 +331,10 @@ fn part_two_init(cache: &mut AHashMap<CacheKeyType, usize>, valves: &Vec<Cave>)
         }
         // OTHER -----------------------------------------------------------
         let opened_valves_other = !opened_valves_me;
-        let my_score = explore_caves_cached(cache, valves, *START_IDX, 26, opened_valves_me, 0);
-        let op_score = explore_caves_cached(cache, valves, *START_IDX, 26, opened_valves_other, 0);
+        let my_score =
+            explore_caves_cached(&mut cache, valves, *START_IDX, 26, opened_valves_me, 0);
+        let op_score =
+            explore_caves_cached(&mut cache, valves, *START_IDX, 26, opened_valves_other, 0);
 
         let total_score = my_score + op_score;
         max_val = max(max_val, total_score);
@@ -328,3 +356,26 @@ fn clear_bit(value: u16, n: u8) -> u16 {
 fn is_set(value: u16, n: u8) -> bool {
     (value & (1 << n)) != 0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II";
+
+    #[test]
+    fn test_example() {
+        let input = Day16::parse(EXAMPLE).unwrap();
+        assert_eq!(Day16::part_one(&input), 1651);
+        assert_eq!(Day16::part_two(&input), 1707);
+    }
+}
@@ -1,134 +1,309 @@
-use advent_of_code::utils::inputs::get_file;
-
-const ROCK_SHAPE_BAR_H: &[u8] = &[0b11110000];
-const ROCK_SHAPE_PLUS: &[u8] = &[0b01000000, 0b11100000, 0b01000000];
-const ROCK_SHAPE_CORNER: &[u8] = &[0b00100000, 0b00100000, 0b11100000];
-const ROCK_SHAPE_BAR_V: &[u8] = &[0b10000000, 0b10000000, 0b10000000, 0b10000000];
-const ROCK_SHAPE_SQUARE: &[u8] = &[0b11000000, 0b11000000];
-
-const ROCK_SHAPES: [&[u8]; 5] = [
-    ROCK_SHAPE_BAR_H,
-    ROCK_SHAPE_PLUS,
-    ROCK_SHAPE_CORNER,
-    ROCK_SHAPE_BAR_V,
-    ROCK_SHAPE_SQUARE,
-];
-
-const FULL_ROW: u8 = 0b11111111;
-const ROW_LEFT_TILE: u8 = 0b10000000;
-const ROW_RIGHT_TILE: u8 = 0b00000011; // The row is only 7 wide
-
-pub fn day_17() {
-    let directions = get_input();
-
-    let solution_1 = simulate_falls(&directions, 2022);
-    println!("\t- Solution 1 is : {}", solution_1);
-
-    let solution_2 = simulate_falls(&directions, 1000000000000);
-    println!("\t- Solution 2 is : {}", solution_2);
+use crate::solution::Solution;
+use crate::utils::cycle::CycleDetector;
+use crate::utils::grid::Grid;
+use crate::utils::inputs::parse_chunks;
+use crate::utils::parse_error::ParseError;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::mem;
+use std::str::FromStr;
+
+/// The rocks of the puzzle, falling in this order
+const DEFAULT_ROCKS: &str = "####
+
+.#.
+###
+.#.
+
+..#
+..#
+###
+
+#
+#
+#
+#
+
+##
+##";
+const DEFAULT_CHAMBER_WIDTH: usize = 7;
+/// A new rock appears 2 units away from the left wall, and 3 units above the highest point
+const DEFAULT_SPAWN_OFFSETS: (usize, usize) = (2, 3);
+
+/// A row of the chamber, the highest bit being the leftmost tile
+type Row = u64;
+
+const MAX_CHAMBER_WIDTH: usize = Row::BITS as usize;
+const FULL_ROW: Row = Row::MAX;
+const ROW_LEFT_TILE: Row = 1 << (MAX_CHAMBER_WIDTH - 1);
+/// How deep the top of the tower is looked at to detect a cycle
+const SURFACE_DEPTH: usize = 64;
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    type Input = Vec<JetDirection>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(directions: &Self::Input) -> Self::PartOne {
+        simulate_falls(directions, 2022)
+    }
+
+    fn part_two(directions: &Self::Input) -> Self::PartTwo {
+        simulate_falls(directions, 1000000000000)
+    }
 }
 
 #[derive(Debug)]
-enum JetDirection {
+pub enum JetDirection {
     Left,
     Right,
 }
 
-fn get_input() -> Vec<JetDirection> {
-    get_file("./src/day_17/input.txt")
-        .trim()
-        .chars()
-        .map(|c| match c {
-            '<' => JetDirection::Left,
-            _ => JetDirection::Right,
+fn get_input(input: &str) -> Result<Vec<JetDirection>, ParseError> {
+    let pattern = input.trim();
+    if pattern.is_empty() {
+        return Err(ParseError::new(input, pattern, "a jet pattern"));
+    }
+    pattern
+        .char_indices()
+        .map(|(idx, c)| match c {
+            '<' => Ok(JetDirection::Left),
+            '>' => Ok(JetDirection::Right),
+            _ => Err(ParseError::new(
+                input,
+                &pattern[idx..idx + c.len_utf8()],
+                "`<` or `>`",
+            )),
         })
         .collect()
 }
 
-fn simulate_falls(jet_pattern: &[JetDirection], mut rocks_to_fall: usize) -> usize {
-    let mut state = State::new();
-    let mut jet_idx = 0;
-    let mut additional_height = 0;
+/// The shape of a rock, as rows from the top
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Rock {
+    rows: Vec<Row>,
+    width: usize,
+}
 
-    while state.nbr_landed_rocks < rocks_to_fall {
-        apply_jet_push(&jet_pattern[jet_idx % jet_pattern.len()], &mut state);
-        if move_down_and_try_to_land_rock(&mut state, jet_idx % jet_pattern.len())
-            && additional_height == 0
-        {
-            // If a pattern is detected:
-            //  - reduce the rocks to simulate
-            //  - calculate the height generated by the cycle, and add it to `additional_height`
-            if let Some((height_per_cycle, rocks_per_cycle)) = detect_cycle(&state) {
-                let remaining_rocks_to_fall = rocks_to_fall - state.nbr_landed_rocks;
-                let remaining_loops_nbr = remaining_rocks_to_fall / rocks_per_cycle;
-                rocks_to_fall -= remaining_loops_nbr * rocks_per_cycle;
-                additional_height = height_per_cycle * remaining_loops_nbr;
-            }
+/// Parse a rock drawn with `#` and `.`, empty rows and columns around it being ignored
+impl FromStr for Rock {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tiles = Grid::parse(s, "`#` or `.`", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })?;
+        let positions: Vec<_> = tiles
+            .iter()
+            .filter(|(_, is_rock)| **is_rock)
+            .map(|(position, _)| position)
+            .collect();
+        if positions.is_empty() {
+            return Err(ParseError::new(s, s, "a rock with at least one `#`"));
         }
-        jet_idx += 1;
+        let min_x = positions.iter().map(|(x, _)| *x).min().unwrap_or(0);
+        let max_x = positions.iter().map(|(x, _)| *x).max().unwrap_or(0);
+        let min_y = positions.iter().map(|(_, y)| *y).min().unwrap_or(0);
+        let max_y = positions.iter().map(|(_, y)| *y).max().unwrap_or(0);
+        let width = max_x - min_x + 1;
+        if width > MAX_CHAMBER_WIDTH {
+            let message = format!("a rock at most {} tiles wide", MAX_CHAMBER_WIDTH);
+            return Err(ParseError::new(s, s, message));
+        }
+        let mut rows = vec![0; max_y - min_y + 1];
+        for (x, y) in positions {
+            rows[y - min_y] |= ROW_LEFT_TILE >> (x - min_x);
+        }
+        Ok(Self { rows, width })
     }
-    state.highest_point + additional_height
 }
 
-/// Try to detect if a pattern is occurring among the falling rocks.
-/// Returns Some(height_per_cycle, rocks_per_cycle) if a pattern is detected
-fn detect_cycle(state: &State) -> Option<(usize, usize)> {
-    let latest_rock = &state.history[state.history.len() - 1];
-    for (idx, rock) in state.history.iter().enumerate().skip(1) {
-        if idx == state.history.len() - 1 {
-            break;
-        }
-        if does_cycle_exist(
-            &state.history,
-            &state.history[state.history.len() - 1],
-            &state.history[idx],
-            state.history.len() - 1,
-            idx,
-        ) {
-            let y_offset = latest_rock.y - rock.y;
-            let idx_offset = state.history.len() - 1 - idx;
-            return Some((y_offset, idx_offset));
+/// Parse rocks drawn with `#` and `.`, separated by an empty line
+pub fn parse_rocks(input: &str) -> Result<Vec<Rock>, ParseError> {
+    parse_chunks(input, Rock::from_str)
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ChamberError {
+    /// The chamber must be between 1 and 64 tiles wide
+    Width(usize),
+    NoRocks,
+    /// A rock does not fit between the walls once it appears
+    RockTooWide {
+        rock_idx: usize,
+        width: usize,
+    },
+}
+
+impl fmt::Display for ChamberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChamberError::Width(width) => write!(
+                f,
+                "a chamber must be between 1 and {} tiles wide, not {}",
+                MAX_CHAMBER_WIDTH, width
+            ),
+            ChamberError::NoRocks => write!(f, "at least one rock must fall"),
+            ChamberError::RockTooWide { rock_idx, width } => write!(
+                f,
+                "rock {} does not fit in a chamber {} tiles wide once it appears",
+                rock_idx, width
+            ),
         }
     }
-    None
 }
 
-/// Checks if a cycle exists in the history of falling rocks.
-///
-/// This function takes a slice of `RockHistory` objects and indices for the most recent
-/// and oldest rocks to compare. It then checks if a cycle exists by comparing the vertical
-/// positions (`y` values) of the rocks at these indices.
-fn does_cycle_exist(
-    history: &[RockHistory],
-    earliest_rock: &RockHistory,
-    latest_rock: &RockHistory,
-    earliest_rock_idx: usize,
-    latest_rock_idx: usize,
-) -> bool {
-    let y_offset = earliest_rock.y - latest_rock.y;
-    let mut current_earliest_index = earliest_rock_idx;
-    let mut current_latest_index = latest_rock_idx;
-
-    if latest_rock.y <= 1 {
-        // We can't go back in history if it's the 1st occurrence
-        return false;
-    }
-
-    while current_earliest_index > latest_rock_idx {
-        if !history[current_earliest_index]
-            .is_eq_with_y_offset(&history[current_latest_index], y_offset)
+impl Error for ChamberError {}
+
+/// The chamber the rocks fall in, the rocks falling in turn
+#[derive(Debug, Clone)]
+pub struct Chamber {
+    width: usize,
+    rocks: Vec<Rock>,
+    /// The distance between a new rock and the left wall
+    spawn_left: usize,
+    /// The distance between a new rock and the highest point
+    spawn_up: usize,
+}
+
+impl Chamber {
+    /// `spawn_offsets` are the distances between a new rock and the left wall, and between a new
+    /// rock and the highest point
+    pub fn new(
+        width: usize,
+        rocks: Vec<Rock>,
+        (spawn_left, spawn_up): (usize, usize),
+    ) -> Result<Self, ChamberError> {
+        if !(1..=MAX_CHAMBER_WIDTH).contains(&width) {
+            return Err(ChamberError::Width(width));
+        }
+        if rocks.is_empty() {
+            return Err(ChamberError::NoRocks);
+        }
+        if let Some(rock_idx) = rocks
+            .iter()
+            .position(|rock| spawn_left + rock.width > width)
         {
-            return false;
+            return Err(ChamberError::RockTooWide { rock_idx, width });
         }
+        Ok(Self {
+            width,
+            rocks,
+            spawn_left,
+            spawn_up,
+        })
+    }
 
-        if current_latest_index == 0 {
-            return current_earliest_index == latest_rock_idx + 1;
+    /// Returns the height of the tower once `rocks_to_fall` rocks fell, pushed in turn by the
+    /// `jets`, which repeat once all used.
+    /// Falls are skipped once they repeat, unless rocks keep falling deep into narrow wells, in
+    /// which case every rock is dropped.
+    /// Panics if there are no jets.
+    pub fn simulate(&self, jets: &[JetDirection], rocks_to_fall: usize) -> usize {
+        self.drop_rocks(jets, rocks_to_fall, true)
+    }
+
+    /// Drops the rocks one by one. With `skip_cycles`, the falls are skipped once the top of the
+    /// tower repeats, as the next rocks then fall like the ones after its previous occurrence.
+    fn drop_rocks(&self, jets: &[JetDirection], rocks_to_fall: usize, skip_cycles: bool) -> usize {
+        assert!(
+            !jets.is_empty(),
+            "The rocks must be pushed by at least one jet"
+        );
+        let mut state = State::new(self);
+        let mut history = get_rock_history();
+        let mut jet_idx = 0;
+        // The first jet pushing the current rock, and the top of the tower when it appeared
+        let mut first_jet_idx = 0;
+        let mut surface = (state.highest_point, get_surface(&state));
+
+        while state.nbr_landed_rocks < rocks_to_fall {
+            apply_jet_push(&jets[jet_idx % jets.len()], &mut state);
+            let (rock_shape_idx, rock_y) = (state.rock_shape_idx, state.rock_y);
+            jet_idx += 1;
+            if !move_down_and_try_to_land_rock(&mut state) || !skip_cycles {
+                continue;
+            }
+            let (highest_point, rock_surface) =
+                mem::replace(&mut surface, (state.highest_point, get_surface(&state)));
+            // A rock which checked a tile below the surface could fall differently on top of
+            // the same surface, so it cannot be part of a cycle
+            let unique_idx =
+                Some(state.nbr_landed_rocks).filter(|_| rock_y + SURFACE_DEPTH <= highest_point);
+            let rock = (rock_shape_idx, first_jet_idx, rock_surface, unique_idx);
+            first_jet_idx = jet_idx % jets.len();
+            // Once a pattern is detected, the height after the last rock is the height after
+            // the matching rock of the first cycles, plus the height generated by the other ones
+            if let Some(cycle) = history.push(rock, state.highest_point) {
+                let (rock_idx, cycles_nbr) = cycle.reduce(rocks_to_fall - 1);
+                return history.offset(rock_idx) + cycles_nbr * cycle.delta;
+            }
         }
+        state.highest_point
+    }
+
+    /// The tile of a row touching the right wall
+    fn row_right_tile(&self) -> Row {
+        ROW_LEFT_TILE >> (self.width - 1)
+    }
+}
+
+/// The chamber of the puzzle
+impl Default for Chamber {
+    fn default() -> Self {
+        let rocks = parse_rocks(DEFAULT_ROCKS).expect("The default rocks should be valid");
+        Self::new(DEFAULT_CHAMBER_WIDTH, rocks, DEFAULT_SPAWN_OFFSETS)
+            .expect("The default chamber should be valid")
+    }
+}
 
-        current_earliest_index -= 1;
-        current_latest_index -= 1;
+/// Returns the height of the tower in the chamber of the puzzle once `rocks_to_fall` rocks fell
+fn simulate_falls(jet_pattern: &[JetDirection], rocks_to_fall: usize) -> usize {
+    Chamber::default().simulate(jet_pattern, rocks_to_fall)
+}
+
+/// The landed rocks, with their shape, the jet which pushed them first and the top of the tower
+/// when they appeared, and the highest point once they landed.
+/// The rocks of a whole cycle have to repeat before a pattern is detected, so each of them is
+/// known to fall the same way on top of the same surface.
+fn get_rock_history<K: Hash + Eq>() -> CycleDetector<K, usize> {
+    CycleDetector::new().with_repetitions(2)
+}
+
+/// Returns the empty tiles rocks can still reach from above the highest point, moving down, left
+/// or right, row by row from the highest point down to at most `SURFACE_DEPTH` rows. Nothing
+/// below them can change where the next rocks land, so they describe the top of the tower.
+fn get_surface(state: &State) -> Vec<Row> {
+    let inside = FULL_ROW << (MAX_CHAMBER_WIDTH - state.chamber.width);
+    let mut surface = vec![];
+    let mut reachable = inside;
+    let rows = &state.map[state.highest_point.saturating_sub(SURFACE_DEPTH)..=state.highest_point];
+    for row in rows.iter().rev() {
+        let empty = inside & !row;
+        reachable &= empty;
+        loop {
+            let spread = (reachable | reachable << 1 | reachable >> 1) & empty;
+            if spread == reachable {
+                break;
+            }
+            reachable = spread;
+        }
+        if reachable == 0 {
+            break;
+        }
+        surface.push(reachable);
     }
-    true
+    surface
 }
 
 /// Move the current piece according to the direction of the jet
@@ -148,20 +323,25 @@ fn apply_jet_push(direction: &JetDirection, state: &mut State) {
 }
 
 fn has_reached_max_left_position(state: &State) -> bool {
-    ROCK_SHAPES[state.rock_shape_idx]
+    state
+        .rock()
+        .rows
         .iter()
         .any(|row| (row >> state.rock_x) & ROW_LEFT_TILE != 0)
 }
 
 fn has_reached_max_right_position(state: &State) -> bool {
-    ROCK_SHAPES[state.rock_shape_idx]
+    let right_tile = state.chamber.row_right_tile();
+    state
+        .rock()
+        .rows
         .iter()
-        .any(|row| (row >> state.rock_x) & ROW_RIGHT_TILE != 0)
+        .any(|row| (row >> state.rock_x) & right_tile != 0)
 }
 
 fn will_collide_left(state: &State) -> bool {
-    for (i, row) in ROCK_SHAPES[state.rock_shape_idx].iter().enumerate() {
-        let idx = state.rock_y + ROCK_SHAPES[state.rock_shape_idx].len() - 1 - i;
+    for (i, row) in state.rock().rows.iter().enumerate() {
+        let idx = state.rock_y + state.rock().rows.len() - 1 - i;
         let current_row = state.map[idx];
         if ((row >> state.rock_x) << 1) & current_row != 0 {
             return true;
@@ -171,8 +351,8 @@ fn will_collide_left(state: &State) -> bool {
 }
 
 fn will_collide_right(state: &State) -> bool {
-    for (i, piece_row) in ROCK_SHAPES[state.rock_shape_idx].iter().enumerate() {
-        let idx = state.rock_y + ROCK_SHAPES[state.rock_shape_idx].len() - 1 - i;
+    for (i, piece_row) in state.rock().rows.iter().enumerate() {
+        let idx = state.rock_y + state.rock().rows.len() - 1 - i;
         let current_row = state.map[idx];
         if ((piece_row >> state.rock_x) >> 1) & current_row != 0 {
             return true;
@@ -183,28 +363,22 @@ fn will_collide_right(state: &State) -> bool {
 
 /// Move the rock one unit lower, if possible, or land it.
 /// Returns `true` if the rock has landed, `false` otherwise`
-fn move_down_and_try_to_land_rock(state: &mut State, jet_idx: usize) -> bool {
+fn move_down_and_try_to_land_rock(state: &mut State) -> bool {
     if !will_collide_with_bottom(state) {
         state.move_rock_down();
         false
     } else {
-        let rock_x = state.rock_x;
-        let rock_type = state.rock_shape_idx;
         state.merge_rock_to_map();
         state.nbr_landed_rocks += 1;
         state.update_highest_point();
-        let rock_y = state.highest_point;
         state.add_next_piece();
-        state
-            .history
-            .push(RockHistory::new(rock_type, rock_y, rock_x, jet_idx));
         true
     }
 }
 
 fn will_collide_with_bottom(state: &State) -> bool {
-    for (i, row) in ROCK_SHAPES[state.rock_shape_idx].iter().enumerate() {
-        let idx = state.rock_y + ROCK_SHAPES[state.rock_shape_idx].len() - 1 - i - 1;
+    for (i, row) in state.rock().rows.iter().enumerate() {
+        let idx = state.rock_y + state.rock().rows.len() - 1 - i - 1;
         let current_row = state.map[idx];
         if (row >> state.rock_x) & current_row != 0 {
             return true;
@@ -214,68 +388,44 @@ fn will_collide_with_bottom(state: &State) -> bool {
 }
 
 #[derive(Debug)]
-struct RockHistory {
-    rock_type: usize,
-    y: usize,
-    x: usize,
-    jet_idx: usize,
-}
-
-impl RockHistory {
-    fn new(rock_type: usize, y: usize, x: usize, jet_idx: usize) -> Self {
-        Self {
-            rock_type,
-            y,
-            x,
-            jet_idx,
-        }
-    }
-
-    /// State if the `other` RockHistory is equal to `self`, modulo the `y_offset`.
-    /// It helps checking if `other` could be part of a pattern belonging to `self`
-    fn is_eq_with_y_offset(&self, other: &Self, y_offset: usize) -> bool {
-        self.rock_type == other.rock_type
-            && self.x == other.x
-            && self.y == other.y + y_offset
-            && self.jet_idx == other.jet_idx
-    }
-}
-
-#[derive(Debug)]
-struct State {
-    map: Vec<u8>,
+struct State<'a> {
+    chamber: &'a Chamber,
+    map: Vec<Row>,
     rock_shape_idx: usize,
     rock_x: usize,
     rock_y: usize,
     highest_point: usize,
     nbr_landed_rocks: usize,
-    history: Vec<RockHistory>,
 }
 
-impl State {
-    fn new() -> Self {
+impl<'a> State<'a> {
+    fn new(chamber: &'a Chamber) -> Self {
         let mut new = Self {
+            chamber,
             map: vec![FULL_ROW],
-            rock_shape_idx: ROCK_SHAPES.len() - 1,
-            rock_x: 2,
+            rock_shape_idx: chamber.rocks.len() - 1,
+            rock_x: chamber.spawn_left,
             rock_y: 0,
             highest_point: 0,
             nbr_landed_rocks: 0,
-            history: vec![],
         };
         new.add_next_piece();
         new
     }
 
+    fn rock(&self) -> &'a Rock {
+        &self.chamber.rocks[self.rock_shape_idx]
+    }
+
     /// - Update `piece_nbr` to the next piece
-    /// - Update the `self.rock_y` so there is a distance of 3 spaces between the highest point and the new piece
+    /// - Update the `self.rock_x` and `self.rock_y` so the new piece is at the spawn offsets of the chamber
     fn add_next_piece(&mut self) {
-        self.rock_shape_idx = (self.rock_shape_idx + 1) % ROCK_SHAPES.len();
-        self.rock_x = 2; // Set a distance of 2 from the left wall
-        self.rock_y = self.highest_point + 1 + 3; // Set a space of 3 with the highest point
+        self.rock_shape_idx = (self.rock_shape_idx + 1) % self.chamber.rocks.len();
+        self.rock_x = self.chamber.spawn_left; // Set the distance from the left wall
+        self.rock_y = self.highest_point + 1 + self.chamber.spawn_up; // Set the space with the highest point
 
         // Then add rows so there is no part in the new piece which has no corresponding row in the map
-        let highest_y = self.highest_point + 3 + ROCK_SHAPES[self.rock_shape_idx].len() + 1;
+        let highest_y = self.highest_point + self.chamber.spawn_up + self.rock().rows.len() + 1;
         if highest_y > self.map.len() {
             for _ in self.map.len()..=highest_y {
                 self.map.push(0);
@@ -296,8 +446,8 @@ impl State {
     }
 
     fn merge_rock_to_map(&mut self) {
-        for (i, rock_row) in ROCK_SHAPES[self.rock_shape_idx].iter().enumerate() {
-            let idx = self.rock_y + ROCK_SHAPES[self.rock_shape_idx].len() - 1 - i;
+        for (i, rock_row) in self.rock().rows.iter().enumerate() {
+            let idx = self.rock_y + self.rock().rows.len() - 1 - i;
             self.map[idx] |= rock_row >> self.rock_x;
         }
     }
@@ -313,39 +463,37 @@ impl State {
         panic!("Could not find highest point.")
     }
 
-    fn _print_state(&self) {
-        println!("{:?}", self);
-
-        // Format the current map
-        let mut lines = vec![];
-        for &byte in self.map.iter() {
-            let binary_str: String = format!("{:08b}", byte)
-                .chars()
-                .map(|c| if c == '0' { '.' } else { '▓' })
-                .collect();
-            lines.push(binary_str);
+    /// Draw the chamber from the top, with the landed rocks and the falling one
+    #[allow(dead_code)]
+    fn get_chamber(&self) -> Grid<char> {
+        let width = self.chamber.width;
+        let mut chamber = Grid::new(width, self.map.len(), '.');
+        let top = self.map.len() - 1;
+        for (y, row) in self.map.iter().enumerate() {
+            for x in 0..width {
+                if (row << x) & ROW_LEFT_TILE != 0 {
+                    chamber[(x, top - y)] = '▓';
+                }
+            }
         }
 
-        // Add the current falling piece
-        for (i, piece_row) in ROCK_SHAPES[self.rock_shape_idx].iter().enumerate() {
-            let idx = self.rock_y + ROCK_SHAPES[self.rock_shape_idx].len() - 1 - i;
-
-            let piece = piece_row >> self.rock_x;
-            if piece != 0 {
-                let mut chars: Vec<char> = lines[idx].chars().collect();
-                let o: Vec<char> = format!("{:08b}", piece).chars().collect();
-                for (ii, b) in o.iter().enumerate() {
-                    if b.eq(&'1') {
-                        chars[ii] = '░';
-                    }
+        let shape = &self.rock().rows;
+        for (i, piece_row) in shape.iter().enumerate() {
+            let y = self.rock_y + shape.len() - 1 - i;
+            for x in 0..width {
+                if ((piece_row >> self.rock_x) << x) & ROW_LEFT_TILE != 0 {
+                    chamber[(x, top - y)] = '░';
                 }
-                lines[idx] = chars.into_iter().collect();
             }
         }
+        chamber
+    }
 
-        for (idx, line) in lines.iter().enumerate().rev() {
-            let padded_idx = format!("{:06}", idx);
-            println!("{}: {}", padded_idx, line);
+    fn _print_state(&self) {
+        println!("{:?}", self);
+        let chamber = self.get_chamber().to_string();
+        for (line, idx) in chamber.lines().zip((0..self.map.len()).rev()) {
+            println!("{:06}: {}", idx, line);
         }
         println!("-------------------------------------");
     }
@@ -355,14 +503,34 @@ impl State {
 mod tests {
     use super::*;
 
+    const EXAMPLE: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
+
+    #[test]
+    fn test_example() {
+        let input = Day17::parse(EXAMPLE).unwrap();
+        assert_eq!(Day17::part_one(&input), 3068);
+        assert_eq!(Day17::part_two(&input), 1514285714288);
+    }
+
+    #[test]
+    fn test_get_chamber() {
+        let chamber = Chamber::default();
+        let state = State::new(&chamber);
+        assert_eq!(
+            state.get_chamber().to_string(),
+            ".......\n..░░░░.\n.......\n.......\n.......\n▓▓▓▓▓▓▓"
+        );
+    }
+
     #[test]
     fn test_is_max_left() {
+        let chamber = Chamber::default();
         // |...@...|
         // |..@@@..|
         // |...@...|
         // |.......|
         // +-------+
-        let mut state = State::new();
+        let mut state = State::new(&chamber);
         state.rock_shape_idx = 1;
         state.rock_x = 2;
         state.rock_y = 2;
@@ -373,7 +541,7 @@ mod tests {
         // |.@.....|
         // |.......|
         // +-------+
-        let mut state = State::new();
+        let mut state = State::new(&chamber);
         state.rock_shape_idx = 1;
         state.rock_x = 0;
         state.rock_y = 2;
@@ -384,7 +552,7 @@ mod tests {
         // |.....@.|
         // |.......|
         // +-------+
-        let mut state = State::new();
+        let mut state = State::new(&chamber);
         state.rock_shape_idx = 1;
         state.rock_x = 4;
         state.rock_y = 2;
@@ -393,12 +561,13 @@ mod tests {
 
     #[test]
     fn test_is_max_right() {
+        let chamber = Chamber::default();
         // |...@...|
         // |..@@@..|
         // |...@...|
         // |.......|
         // +-------+
-        let mut state = State::new();
+        let mut state = State::new(&chamber);
         state.rock_shape_idx = 1;
         state.rock_x = 2;
         state.rock_y = 2;
@@ -409,7 +578,7 @@ mod tests {
         // |.@.....|
         // |.......|
         // +-------+
-        let mut state = State::new();
+        let mut state = State::new(&chamber);
         state.rock_shape_idx = 1;
         state.rock_x = 0;
         state.rock_y = 2;
@@ -420,7 +589,7 @@ mod tests {
         // |.....@.|
         // |.......|
         // +-------+
-        let mut state = State::new();
+        let mut state = State::new(&chamber);
         state.rock_shape_idx = 1;
         state.rock_x = 4;
         state.rock_y = 2;
@@ -429,16 +598,17 @@ mod tests {
 
     #[test]
     fn test_is_collision_bottom() {
+        let chamber = Chamber::default();
         // |...@...|
         // |..@@@#.|
         // |...@###|
         // |.....#.|
         // +-------+
-        let mut state = State::new();
-        state.map[1] = 0b00000100;
-        state.map[2] = 0b00001110;
-        state.map[3] = 0b00000100;
-        state.map[4] = 0b00000000;
+        let mut state = State::new(&chamber);
+        state.map[1] = 0b00000100 << 56;
+        state.map[2] = 0b00001110 << 56;
+        state.map[3] = 0b00000100 << 56;
+        state.map[4] = 0b00000000 << 56;
         state.rock_shape_idx = 1;
         state.rock_x = 2;
         state.rock_y = 2;
@@ -450,11 +620,11 @@ mod tests {
         // |..@.###|
         // |.....#.|
         // +-------+
-        let mut state = State::new();
-        state.map[1] = 0b00000100;
-        state.map[2] = 0b00001110;
-        state.map[3] = 0b00000100;
-        state.map[4] = 0b00000000;
+        let mut state = State::new(&chamber);
+        state.map[1] = 0b00000100 << 56;
+        state.map[2] = 0b00001110 << 56;
+        state.map[3] = 0b00000100 << 56;
+        state.map[4] = 0b00000000 << 56;
 
         state.rock_shape_idx = 1;
         state.rock_x = 1;
@@ -463,57 +633,64 @@ mod tests {
         assert!(!will_collide_with_bottom(&state));
     }
 
+    /// Feed the history with rocks as `(type, y, x)`, and returns
+    /// `Some(height_per_cycle, rocks_per_cycle)` if the last one completes a pattern
+    fn detect_cycle(rocks: &[(usize, usize, usize)]) -> Option<(usize, usize)> {
+        let mut history = get_rock_history();
+        rocks
+            .iter()
+            .fold(None, |_, &(rock_type, y, x)| {
+                history.push((rock_type, x, 0), y)
+            })
+            .map(|cycle| (cycle.delta, cycle.period))
+    }
+
     #[test]
     fn test_detect_cycle_a() {
-        let mut state = State::new();
-        state.history = vec![
-            RockHistory::new(0, 1, 1, 0),  // 0
-            RockHistory::new(1, 4, 2, 0),  // 1
-            RockHistory::new(2, 6, 3, 0),  // 2
-            RockHistory::new(0, 7, 1, 0),  // 3
-            RockHistory::new(1, 10, 2, 0), // 4
-            RockHistory::new(2, 12, 3, 0), // 5
-        ];
-        assert!(detect_cycle(&state).is_some())
+        let res = detect_cycle(&[
+            (0, 1, 1),  // 0
+            (1, 4, 2),  // 1
+            (2, 6, 3),  // 2
+            (0, 7, 1),  // 3
+            (1, 10, 2), // 4
+            (2, 12, 3), // 5
+        ]);
+        assert!(res.is_some())
     }
 
     #[test]
     fn test_detect_cycle_b() {
-        let mut state = State::new();
-        state.history = vec![
-            RockHistory::new(0, 1, 1, 0),  // 0
-            RockHistory::new(1, 4, 2, 0),  // 1
-            RockHistory::new(2, 6, 3, 0),  // 2
-            RockHistory::new(0, 7, 1, 0),  // 3
-            RockHistory::new(1, 10, 2, 0), // 4
-        ];
-        assert!(!detect_cycle(&state).is_some())
+        let res = detect_cycle(&[
+            (0, 1, 1),  // 0
+            (1, 4, 2),  // 1
+            (2, 6, 3),  // 2
+            (0, 7, 1),  // 3
+            (1, 10, 2), // 4
+        ]);
+        assert!(res.is_none())
     }
 
     #[test]
     fn test_detect_cycle_c() {
-        let mut state = State::new();
-        state.history = vec![
+        let res = detect_cycle(&[
             // (type, y, x),
-            RockHistory::new(0, 1, 1, 0), // 0
-        ];
-        assert!(!detect_cycle(&state).is_some())
+            (0, 1, 1), // 0
+        ]);
+        assert!(res.is_none())
     }
 
     #[test]
     fn test_detect_cycle_d() {
-        let mut state = State::new();
-        state.history = vec![
+        let res = detect_cycle(&[
             // (type, y, x),
-            RockHistory::new(2, 0, 1, 0),  // 0
-            RockHistory::new(0, 1, 1, 0),  // 0
-            RockHistory::new(1, 4, 2, 0),  // 1
-            RockHistory::new(2, 6, 3, 0),  // 2 <-
-            RockHistory::new(0, 7, 1, 0),  // 3
-            RockHistory::new(1, 10, 2, 0), // 4
-            RockHistory::new(2, 12, 3, 0), // 5 <-
-        ];
-        let res = detect_cycle(&state);
+            (2, 0, 1),  // 0
+            (0, 1, 1),  // 0
+            (1, 4, 2),  // 1
+            (2, 6, 3),  // 2 <-
+            (0, 7, 1),  // 3
+            (1, 10, 2), // 4
+            (2, 12, 3), // 5 <-
+        ]);
         assert!(res.is_some());
         if let Some((height_per_cycle, rocks_per_cycle)) = res {
             assert_eq!(height_per_cycle, 6);
@@ -523,24 +700,107 @@ mod tests {
 
     #[test]
     fn test_detect_cycle_e() {
-        let mut state = State::new();
-        state.history = vec![
+        let res = detect_cycle(&[
             // (type, y, x),
-            RockHistory::new(2, 0, 1, 0),  // 0
-            RockHistory::new(0, 1, 1, 0),  // 0
-            RockHistory::new(1, 4, 2, 0),  // 1
-            RockHistory::new(2, 6, 3, 0),  // 2
-            RockHistory::new(1, 12, 4, 0), // 3 <-- Pattern end
-            RockHistory::new(0, 13, 1, 0), // 4
-            RockHistory::new(1, 16, 2, 0), // 5
-            RockHistory::new(2, 18, 3, 0), // 6
-            RockHistory::new(1, 24, 4, 0), // 7 <-- Pattern start
-        ];
-        let res = detect_cycle(&state);
+            (2, 0, 1),  // 0
+            (0, 1, 1),  // 0
+            (1, 4, 2),  // 1
+            (2, 6, 3),  // 2
+            (1, 12, 4), // 3 <-- Pattern end
+            (0, 13, 1), // 4
+            (1, 16, 2), // 5
+            (2, 18, 3), // 6
+            (1, 24, 4), // 7 <-- Pattern start
+        ]);
         assert!(res.is_some());
         if let Some((height_per_cycle, rocks_per_cycle)) = res {
             assert_eq!(height_per_cycle, 12);
             assert_eq!(rocks_per_cycle, 4);
         };
     }
+
+    #[test]
+    fn test_parse_rocks() {
+        let rocks = parse_rocks(DEFAULT_ROCKS).unwrap();
+        assert_eq!(rocks.len(), 5);
+        assert_eq!(rocks[1].rows, vec![0b010 << 61, 0b111 << 61, 0b010 << 61]);
+        assert_eq!(rocks[2].width, 3);
+
+        // Empty rows and columns are ignored
+        let rock = Rock::from_str("....\n.#..\n.##.\n....").unwrap();
+        assert_eq!(rock.rows, vec![0b10 << 62, 0b11 << 62]);
+        assert_eq!(rock.width, 2);
+
+        let error = parse_rocks("####\n\n.#.\n#x#").unwrap_err();
+        assert_eq!((error.line, error.column), (4, 2));
+        assert!(Rock::from_str("...").is_err());
+        assert!(Rock::from_str(&"#".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_chamber_configuration() {
+        let rocks = parse_rocks(DEFAULT_ROCKS).unwrap();
+        let new_chamber = |width, rocks| Chamber::new(width, rocks, DEFAULT_SPAWN_OFFSETS);
+        assert_eq!(
+            new_chamber(65, rocks.clone()).unwrap_err(),
+            ChamberError::Width(65)
+        );
+        assert_eq!(new_chamber(7, vec![]).unwrap_err(), ChamberError::NoRocks);
+        assert_eq!(
+            new_chamber(5, rocks.clone()).unwrap_err(),
+            ChamberError::RockTooWide {
+                rock_idx: 0,
+                width: 5
+            }
+        );
+        assert!(Chamber::new(7, rocks, (4, 3)).is_err());
+
+        // Rocks as wide as the chamber are stacked whatever the jets
+        let jets = Day17::parse("<<>").unwrap();
+        let bar = parse_rocks(&"#".repeat(64)).unwrap();
+        let chamber = Chamber::new(64, bar, (0, 3)).unwrap();
+        assert_eq!(chamber.simulate(&jets, 2022), 2022);
+        assert_eq!(chamber.simulate(&jets, 1000000000000), 1000000000000);
+
+        // Flat rocks pushed against the left wall each add a row, leaving holes on the right
+        let rocks = parse_rocks("##\n\n####").unwrap();
+        let chamber = Chamber::new(4, rocks, (0, 0)).unwrap();
+        let jets = Day17::parse("<").unwrap();
+        assert_eq!(chamber.simulate(&jets, 2), 2);
+        assert_eq!(chamber.simulate(&jets, 1000000000001), 1000000000001);
+    }
+
+    #[test]
+    fn test_simulate() {
+        let chamber = |width, rocks: &str, spawn_offsets| {
+            Chamber::new(width, parse_rocks(rocks).unwrap(), spawn_offsets).unwrap()
+        };
+        let (jets, small_rocks) = ("<>><><><><>><<>", "#.#\n###\n\n##\n\n#");
+        // Single tiles keep falling deep into wells in the first two chambers, while cycles are
+        // found in the others
+        let configs = [
+            (chamber(9, small_rocks, (3, 1)), jets),
+            (chamber(11, small_rocks, (4, 5)), jets),
+            (chamber(9, "#.#\n###\n\n##", (3, 1)), jets),
+            (chamber(12, DEFAULT_ROCKS, (4, 2)), EXAMPLE),
+            (chamber(5, ".#\n##\n\n###\n\n#\n#\n#\n#", (1, 3)), EXAMPLE),
+            (Chamber::default(), EXAMPLE),
+        ];
+        for (chamber, jets) in &configs {
+            let jets = Day17::parse(jets).unwrap();
+            for rocks_to_fall in [0, 1, 120, 500, 1234, 2022] {
+                assert_eq!(
+                    chamber.simulate(&jets, rocks_to_fall),
+                    chamber.drop_rocks(&jets, rocks_to_fall, false),
+                    "{} rocks in {:?}",
+                    rocks_to_fall,
+                    chamber
+                );
+            }
+        }
+        // Checked by dropping the rocks one by one
+        let jets = Day17::parse(jets).unwrap();
+        assert_eq!(configs[0].0.simulate(&jets, 500), 600);
+        assert_eq!(configs[0].0.simulate(&jets, 2022), 2417);
+    }
 }
@@ -1,94 +1,49 @@
-use advent_of_code::utils::inputs::get_file;
-use std::cmp::{max, min};
-use std::collections::{HashMap, HashSet, VecDeque};
+use crate::solution::Solution;
+use crate::utils::geometry::Point3;
+use crate::utils::inputs::{parse_lines, parse_number};
+use crate::utils::parse_error::ParseError;
+use crate::utils::search::bfs_reach;
+use std::collections::HashSet;
 use std::iter::FromIterator;
 
-pub fn day_18() {
-    let cubes = get_input();
+pub struct Day18;
 
-    let solution_1 = part_one(&cubes);
-    println!("\t- Solution 1 is : {}", solution_1);
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+    type Input = Vec<Cube>;
+    type PartOne = usize;
+    type PartTwo = usize;
 
-    let solution_2 = part_two(&cubes);
-    println!("\t- Solution 2 is : {}", solution_2);
-}
-
-fn get_input() -> Vec<Cube> {
-    get_file("./src/day_18/input.txt")
-        .lines()
-        .map(|l| {
-            let split = l
-                .split(',')
-                .filter_map(|part| part.trim().parse::<i32>().ok())
-                .collect::<Vec<i32>>();
-            Cube {
-                x: split[0],
-                y: split[1],
-                z: split[2],
-            }
-        })
-        .collect()
-}
-
-type AreaLimits = ((i32, i32), (i32, i32), (i32, i32));
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
 
-#[derive(Debug, Hash, Eq, PartialEq, Copy, Clone, Default)]
-struct Cube {
-    pub x: i32,
-    pub y: i32,
-    pub z: i32,
-}
+    fn part_one(cubes: &Self::Input) -> Self::PartOne {
+        part_one(cubes)
+    }
 
-struct CubeNeighbours {
-    cube: Cube,
-    index: i32,
+    fn part_two(cubes: &Self::Input) -> Self::PartTwo {
+        part_two(cubes)
+    }
 }
 
-impl Cube {
-    fn neighbours(&self) -> CubeNeighbours {
-        CubeNeighbours {
-            cube: *self,
-            index: 0,
+fn get_input(input: &str) -> Result<Vec<Cube>, ParseError> {
+    parse_lines(input, |l| {
+        let split = l
+            .split(',')
+            .map(|part| parse_number(l, part))
+            .collect::<Result<Vec<i32>, ParseError>>()?;
+        if split.len() != 3 {
+            return Err(ParseError::new(l, l, "three comma-separated coordinates"));
         }
-    }
+        Ok(Cube::new(split[0], split[1], split[2]))
+    })
 }
 
-impl Iterator for CubeNeighbours {
-    type Item = Cube;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let result = match self.index {
-            0 => Some(Cube {
-                x: self.cube.x - 1,
-                ..self.cube
-            }),
-            1 => Some(Cube {
-                x: self.cube.x + 1,
-                ..self.cube
-            }),
-            2 => Some(Cube {
-                y: self.cube.y - 1,
-                ..self.cube
-            }),
-            3 => Some(Cube {
-                y: self.cube.y + 1,
-                ..self.cube
-            }),
-            4 => Some(Cube {
-                z: self.cube.z - 1,
-                ..self.cube
-            }),
-            5 => Some(Cube {
-                z: self.cube.z + 1,
-                ..self.cube
-            }),
-            _ => return None,
-        };
-
-        self.index += 1;
-        result
-    }
-}
+/// The lowest and highest corners of the area to explore
+type AreaLimits = (Cube, Cube);
+
+pub type Cube = Point3<i32>;
 
 fn part_one(cubes: &[Cube]) -> usize {
     let total_sides = cubes.len() * 6;
@@ -105,61 +60,60 @@ fn part_one(cubes: &[Cube]) -> usize {
 fn part_two(cubes: &[Cube]) -> usize {
     let set_cubes: HashSet<Cube> = HashSet::from_iter(cubes.iter().cloned());
     let limit_values = get_area_limit(cubes);
-    let outside_cubes = bfs(&set_cubes, limit_values);
+    // Flood the area around the droplet, from a corner which cannot be inside it
+    let outside = bfs_reach(limit_values.0, |cube| {
+        IntoIterator::into_iter(cube.neighbours())
+            .filter(|child| is_inside_limit(&set_cubes, limit_values, child))
+    });
 
     cubes
         .iter()
         .flat_map(|cube| cube.neighbours())
-        .filter(|cube| outside_cubes.contains(cube))
+        .filter(|cube| outside.is_reached(cube))
         .count()
 }
 
 fn get_area_limit(cubes: &[Cube]) -> AreaLimits {
-    let mut limit_values = ((999, -999), (999, -999), (999, -999));
+    let mut limit_values = (Cube::new(999, 999, 999), Cube::new(-999, -999, -999));
     for cube in cubes {
         for neighbour in cube.neighbours() {
-            limit_values.0 .0 = min(neighbour.x, limit_values.0 .0);
-            limit_values.0 .1 = max(neighbour.x, limit_values.0 .1);
-            limit_values.1 .0 = min(neighbour.y, limit_values.1 .0);
-            limit_values.1 .1 = max(neighbour.y, limit_values.1 .1);
-            limit_values.2 .0 = min(neighbour.z, limit_values.2 .0);
-            limit_values.2 .1 = max(neighbour.z, limit_values.2 .1);
+            limit_values.0 = limit_values.0.component_min(&neighbour);
+            limit_values.1 = limit_values.1.component_max(&neighbour);
         }
     }
     limit_values
 }
 
-fn bfs(set_cubes: &HashSet<Cube>, area_limit: AreaLimits) -> HashSet<Cube> {
-    let mut frontier = VecDeque::new();
-    let mut came_from = HashMap::new();
-    let start = Cube {
-        x: area_limit.0 .0,
-        y: area_limit.1 .0,
-        z: area_limit.2 .0,
-    };
-    frontier.push_back(start);
-    while let Some(current_node) = frontier.pop_front() {
-        for child_node in current_node.neighbours() {
-            if !is_inside_limit(set_cubes, area_limit, &child_node) {
-                continue;
-            }
-            came_from.entry(child_node).or_insert_with(|| {
-                frontier.push_back(child_node);
-                current_node
-            });
-        }
-    }
-    came_from.keys().cloned().collect()
-}
-
 fn is_inside_limit(set_cubes: &HashSet<Cube>, area_limit: AreaLimits, cube: &Cube) -> bool {
     if set_cubes.contains(cube) {
         return false;
     }
-    cube.x >= area_limit.0 .0
-        && cube.x <= area_limit.0 .1
-        && cube.y >= area_limit.1 .0
-        && cube.y <= area_limit.1 .1
-        && cube.z >= area_limit.2 .0
-        && cube.z <= area_limit.2 .1
+    let (lowest, highest) = area_limit;
+    cube.component_max(&lowest) == *cube && cube.component_min(&highest) == *cube
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "2,2,2
+1,2,2
+3,2,2
+2,1,2
+2,3,2
+2,2,1
+2,2,3
+2,2,4
+2,2,6
+1,2,5
+3,2,5
+2,1,5
+2,3,5";
+
+    #[test]
+    fn test_example() {
+        let input = Day18::parse(EXAMPLE).unwrap();
+        assert_eq!(Day18::part_one(&input), 64);
+        assert_eq!(Day18::part_two(&input), 58);
+    }
 }
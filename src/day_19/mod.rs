@@ -1,4 +1,6 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::inputs::{parse_lines, parse_number};
+use crate::utils::parse_error::ParseError;
 use lazy_static::lazy_static;
 use rayon::prelude::*;
 use regex::Regex;
@@ -8,13 +10,25 @@ lazy_static! {
     static ref RE_PARSE_LINE: Regex = Regex::new(r"[[a-zA-Z] ]+(\d+)[[a-zA-Z] :]+(\d+)[[a-zA-Z] .]+(\d+)[[a-zA-Z] .]+(\d+)[[a-zA-Z] ]+(\d+)[[a-zA-Z] .]+(\d+)[[a-zA-Z] ]+(\d+)").unwrap();
 }
 
-pub fn day_19() {
-    let blueprints = get_input("./src/day_19/input.txt");
-    let solution_1 = part_one(&blueprints);
-    println!("\t- Solution 1 is : {}", solution_1);
+pub struct Day19;
 
-    let solution_2 = part_two(&blueprints);
-    println!("\t- Solution 2 is : {}", solution_2);
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+    type Input = Vec<Blueprint>;
+    type PartOne = i16;
+    type PartTwo = i16;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(blueprints: &Self::Input) -> Self::PartOne {
+        part_one(blueprints)
+    }
+
+    fn part_two(blueprints: &Self::Input) -> Self::PartTwo {
+        part_two(blueprints, 32)
+    }
 }
 
 fn part_one(blueprints: &[Blueprint]) -> i16 {
@@ -24,11 +38,11 @@ fn part_one(blueprints: &[Blueprint]) -> i16 {
         .sum()
 }
 
-fn part_two(blueprints: &[Blueprint]) -> i16 {
+fn part_two(blueprints: &[Blueprint], max_turn: i16) -> i16 {
     blueprints
         .par_iter()
         .take(3)
-        .map(|b| get_max_geodes(b, State::new(), 32))
+        .map(|b| get_max_geodes(b, State::new(), max_turn))
         .product()
 }
 
@@ -65,7 +79,7 @@ impl Robot {
 }
 
 #[derive(Debug, Clone)]
-struct Blueprint {
+pub struct Blueprint {
     id: i16,
     robot_ore: Robot,
     robot_clay: Robot,
@@ -246,26 +260,27 @@ fn divide_and_round_up(dividend: i16, divisor: i16) -> i16 {
     max((dividend + divisor - 1) / divisor, 0)
 }
 
-fn get_input(file: &str) -> Vec<Blueprint> {
-    get_file(file).lines().map(parse_line).collect()
+fn get_input(input: &str) -> Result<Vec<Blueprint>, ParseError> {
+    parse_lines(input, parse_line)
 }
 
-fn parse_line(line: &str) -> Blueprint {
-    let values: Vec<_> = RE_PARSE_LINE
+fn parse_line(line: &str) -> Result<Blueprint, ParseError> {
+    let values = RE_PARSE_LINE
         .captures(line)
-        .unwrap_or_else(|| panic!("Cannot parse line {}", line))
+        .ok_or_else(|| ParseError::new(line, line, "a blueprint with seven costs"))?
         .iter()
         .skip(1)
-        .filter_map(|value| value.as_ref().and_then(|m| m.as_str().parse::<i16>().ok()))
-        .collect();
+        .flatten()
+        .map(|m| parse_number(line, m.as_str()))
+        .collect::<Result<Vec<i16>, ParseError>>()?;
 
-    Blueprint::new(
+    Ok(Blueprint::new(
         values[0],
         Robot::new(values[1], 0, 0),
         Robot::new(values[2], 0, 0),
         Robot::new(values[3], values[4], 0),
         Robot::new(values[5], 0, values[6]),
-    )
+    ))
 }
 
 fn get_max_geodes(blueprint: &Blueprint, state: State, max_turn: i16) -> i16 {
@@ -283,6 +298,30 @@ fn get_max_geodes(blueprint: &Blueprint, state: State, max_turn: i16) -> i16 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::inputs::get_file;
+
+    const EXAMPLE: &str = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
+Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.";
+
+    #[test]
+    fn test_example() {
+        let input = Day19::parse(EXAMPLE).unwrap();
+        assert_eq!(Day19::part_one(&input), 33);
+    }
+
+    #[test]
+    fn test_example_part_two_short() {
+        // 26 minutes instead of 32 keeps the search to about a second in debug builds
+        let input = Day19::parse(EXAMPLE).unwrap();
+        assert_eq!(part_two(&input, 26), 15 * 20);
+    }
+
+    #[test]
+    #[ignore = "the example blueprints take minutes to explore for 32 minutes"]
+    fn test_example_part_two() {
+        let input = Day19::parse(EXAMPLE).unwrap();
+        assert_eq!(Day19::part_two(&input), 56 * 62);
+    }
 
     #[test]
     fn test_elapse_n_turns() {
@@ -320,7 +359,8 @@ mod tests {
 
     #[test]
     fn test_can_afford_in_future_with_example() {
-        let blueprint = get_input("./src/day_19/input_example.txt")[0].clone();
+        let blueprint =
+            get_input(&get_file("./src/day_19/input_example.txt").unwrap()).unwrap()[0].clone();
         let state = State::new();
 
         assert!(state
@@ -454,7 +494,9 @@ mod tests {
 
     #[test]
     fn test_can_afford_in_future_with_given_input() {
-        let blueprint = get_input("./src/day_19/input_test_given_input.txt")[7].clone();
+        let blueprint = get_input(&get_file("./src/day_19/input_test_given_input.txt").unwrap())
+            .unwrap()[7]
+            .clone();
         let state = State::new();
         assert_eq!(state.turn, 0);
 
@@ -1,29 +1,45 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::inputs::{parse_lines, parse_number};
+use crate::utils::parse_error::ParseError;
 use std::collections::VecDeque;
 
 #[derive(Debug, Copy, Clone)]
-struct Number {
+pub struct Number {
     id: usize,
     value: i64,
 }
 
-pub fn day_20() {
-    let file = get_input("./src/day_20/input.txt");
+pub struct Day20;
 
-    let solution_1 = part_one(file.clone());
-    println!("\t- Solution 1 is : {}", solution_1);
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+    type Input = VecDeque<Number>;
+    type PartOne = i64;
+    type PartTwo = i64;
 
-    let solution_2 = part_two(file);
-    println!("\t- Solution 2 is : {}", solution_2);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(file: &Self::Input) -> Self::PartOne {
+        part_one(file.clone())
+    }
+
+    fn part_two(file: &Self::Input) -> Self::PartTwo {
+        part_two(file.clone())
+    }
 }
 
-fn get_input(file: &str) -> VecDeque<Number> {
-    get_file(file)
-        .lines()
-        .map(|l| l.parse::<i64>().unwrap())
+fn get_input(input: &str) -> Result<VecDeque<Number>, ParseError> {
+    let values: Vec<i64> = parse_lines(input, |l| parse_number(l, l))?;
+    if !values.contains(&0) {
+        return Err(ParseError::new(input, "", "a number equal to 0"));
+    }
+    Ok(values
+        .into_iter()
         .enumerate()
         .map(|(id, value)| Number { id, value })
-        .collect()
+        .collect())
 }
 
 fn part_one(mut file: VecDeque<Number>) -> i64 {
@@ -86,10 +102,26 @@ fn get_number_index_by_id(file: &VecDeque<Number>, number_id: usize) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::inputs::get_file;
+
+    const EXAMPLE: &str = "1
+2
+-3
+3
+-2
+0
+4";
+
+    #[test]
+    fn test_example() {
+        let input = Day20::parse(EXAMPLE).unwrap();
+        assert_eq!(Day20::part_one(&input), 3);
+        assert_eq!(Day20::part_two(&input), 1623178306);
+    }
 
     #[test]
     fn test_find_nth_number_after_zero() {
-        let file = get_input("./src/day_20/input_example.txt");
+        let file = get_input(&get_file("./src/day_20/input_example.txt").unwrap()).unwrap();
         assert_eq!(find_nth_number_after_zero(&file, 1000), -2);
         assert_eq!(find_nth_number_after_zero(&file, 2000), 3);
         assert_eq!(find_nth_number_after_zero(&file, 3000), -3);
@@ -1,184 +1,293 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::expression::{Equation, ExpressionDag, ExpressionError, Node, Operator};
+use crate::utils::inputs::{parse_lines, parse_number};
+use crate::utils::parse_error::ParseError;
+use crate::utils::rational::Rational;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 
+const ROOT_KEY: &str = "root";
 const MY_KEY: &str = "humn";
 
-pub fn day_21() {
-    let node = get_input();
+pub struct Day21;
 
-    let solution_1 = part_one(&node);
-    println!("\t- Solution 1 is : {}", solution_1);
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+    type Input = Monkeys;
+    type PartOne = i64;
+    type PartTwo = i64;
 
-    let solution_2 = part_two(&node);
-    println!("\t- Solution 2 is : {}", solution_2);
-}
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(monkeys: &Self::Input) -> Self::PartOne {
+        part_one(monkeys)
+    }
 
-fn get_input() -> Node {
-    let mut node = HashMap::new();
-    let file = get_file("./src/day_21/input.txt");
-    for line in file.lines() {
-        let split = line.split(": ").collect::<Vec<_>>();
-        let name = split[0].to_string();
-        let split = split[1].split(' ').collect::<Vec<_>>();
-        node.insert(name, split);
+    fn part_two(monkeys: &Self::Input) -> Self::PartTwo {
+        part_two(monkeys)
     }
-    build_node(&node, "root")
 }
 
-fn build_node(monkeys: &HashMap<String, Vec<&str>>, name: &str) -> Node {
-    let monkey = monkeys.get(name).unwrap();
-    let expression = match monkey[..] {
-        [_, "+", _] => Expression::Add,
-        [_, "-", _] => Expression::Sub,
-        [_, "*", _] => Expression::Mul,
-        [_, "/", _] => Expression::Div,
-        [value] => Expression::Value(value.parse().unwrap()),
-        _ => panic!("Cannot parse {:?}", monkey),
+fn get_input(input: &str) -> Result<Monkeys, ParseError> {
+    let lines = parse_lines(input, parse_line)?;
+    let mut names = vec![];
+    let mut ids = HashMap::new();
+    for (name, _) in &lines {
+        if ids.insert(name.to_string(), names.len()).is_some() {
+            return Err(ParseError::new(input, name, "a monkey not already defined"));
+        }
+        names.push(*name);
+    }
+    let get_id = |name: &str| {
+        ids.get(name)
+            .copied()
+            .ok_or_else(|| ParseError::new(input, name, "the name of a monkey"))
     };
-    let (left, right) = if let Expression::Value(_) = expression {
-        (None, None)
-    } else {
-        (
-            Some(Box::new(build_node(monkeys, monkey[0]))),
-            Some(Box::new(build_node(monkeys, monkey[2]))),
+    let jobs = lines
+        .iter()
+        .map(|(_, job)| match job {
+            Job::Number(value) => Ok(Job::Number(*value)),
+            Job::Operation(left, operator, right) => {
+                Ok(Job::Operation(get_id(left)?, *operator, get_id(right)?))
+            }
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+    match ids.get(ROOT_KEY).map(|&id| (id, &jobs[id])) {
+        None => return Err(ParseError::new(input, input, "a monkey named `root`")),
+        Some((id, Job::Number(_))) => {
+            return Err(ParseError::new(
+                input,
+                lines[id].0,
+                "a `root` monkey comparing two monkeys",
+            ))
+        }
+        Some((_, Job::Operation(..))) => {}
+    }
+    let order = get_topological_order(&jobs).map_err(|id| {
+        ParseError::new(
+            input,
+            names[id],
+            "a monkey whose job does not depend on itself",
         )
+    })?;
+
+    Ok(Monkeys {
+        names: names.into_iter().map(String::from).collect(),
+        ids,
+        jobs,
+        order,
+    })
+}
+
+fn parse_line(line: &str) -> Result<(&str, Job<&str>), ParseError> {
+    let (name, job) = line
+        .split_once(": ")
+        .ok_or_else(|| ParseError::new(line, line, "a line like `root: pppw + sjmn`"))?;
+    let split = job.split(' ').collect::<Vec<_>>();
+    let job = match split[..] {
+        [left, operator, right] => {
+            let operator = Operator::from_symbol(operator)
+                .ok_or_else(|| ParseError::new(line, operator, "`+`, `-`, `*` or `/`"))?;
+            Job::Operation(left, operator, right)
+        }
+        [value] => Job::Number(parse_number(line, value)?),
+        _ => return Err(ParseError::new(line, job, "a number or an operation")),
     };
+    Ok((name, job))
+}
 
-    Node {
-        name: name.to_string(),
-        left,
-        right,
-        expression,
+/// Sort the monkeys so each one comes after the monkeys it waits for.
+/// Returns the id of a monkey waiting for itself, if their jobs form a cycle.
+fn get_topological_order(jobs: &[Job<usize>]) -> Result<Vec<usize>, usize> {
+    #[derive(Copy, Clone, Eq, PartialEq)]
+    enum Mark {
+        Unvisited,
+        InProgress,
+        Done,
     }
+
+    let mut marks = vec![Mark::Unvisited; jobs.len()];
+    let mut order = Vec::with_capacity(jobs.len());
+    for first_id in 0..jobs.len() {
+        // Each monkey is stacked with whether the monkeys it waits for were already stacked
+        let mut stack = vec![(first_id, false)];
+        while let Some((id, is_expanded)) = stack.pop() {
+            if is_expanded {
+                marks[id] = Mark::Done;
+                order.push(id);
+                continue;
+            }
+            match marks[id] {
+                Mark::Done => continue,
+                // Only the monkeys on the path from `first_id` are in progress
+                Mark::InProgress => return Err(id),
+                Mark::Unvisited => {}
+            }
+            marks[id] = Mark::InProgress;
+            stack.push((id, true));
+            if let Job::Operation(left, _, right) = jobs[id] {
+                stack.push((right, false));
+                stack.push((left, false));
+            }
+        }
+    }
+    Ok(order)
 }
 
+/// The job of a monkey, `M` referring to the other monkeys by name or by id
 #[derive(Debug)]
-enum Expression {
-    Add,
-    Sub,
-    Mul,
-    Div,
-    Value(i64),
+enum Job<M> {
+    Number(i64),
+    Operation(M, Operator, M),
 }
 
+/// The monkeys and their jobs, each monkey being computed once even when several others wait
+/// for it
 #[derive(Debug)]
-struct Node {
-    name: String,
-    left: Option<Box<Node>>,
-    right: Option<Box<Node>>,
-    expression: Expression,
+pub struct Monkeys {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    jobs: Vec<Job<usize>>,
+    /// Every monkey, after the monkeys it waits for
+    order: Vec<usize>,
 }
 
-impl Node {
-    fn evaluate(&self) -> i64 {
-        match self.expression {
-            Expression::Add => self.get_left().evaluate() + self.get_right().evaluate(),
-            Expression::Sub => self.get_left().evaluate() - self.get_right().evaluate(),
-            Expression::Mul => self.get_left().evaluate() * self.get_right().evaluate(),
-            Expression::Div => self.get_left().evaluate() / self.get_right().evaluate(),
-            Expression::Value(val) => val,
+impl Monkeys {
+    /// Build the expressions yelled by the monkeys, and returns the id of each monkey's node.
+    /// The monkey `unknown`, if any, becomes a variable whatever its job.
+    fn get_expressions(&self, unknown: Option<usize>) -> (ExpressionDag, Vec<usize>) {
+        let mut dag = ExpressionDag::new();
+        let mut node_ids = vec![0; self.jobs.len()];
+        for &id in &self.order {
+            let node = match self.jobs[id] {
+                _ if unknown == Some(id) => Node::Variable(self.names[id].clone()),
+                Job::Number(value) => Node::Constant(value.into()),
+                Job::Operation(left, operator, right) => {
+                    Node::Operation(node_ids[left], operator, node_ids[right])
+                }
+            };
+            node_ids[id] = dag.push(node);
         }
+        (dag, node_ids)
     }
 
-    fn get_left(&self) -> &Node {
-        self.left.as_ref().unwrap()
+    /// Returns the number yelled by a monkey
+    fn evaluate(&self, name: &str) -> Result<Rational, ExpressionError> {
+        let (dag, node_ids) = self.get_expressions(None);
+        dag.evaluate(node_ids[self.ids[name]])
     }
 
-    fn get_right(&self) -> &Node {
-        self.right.as_ref().unwrap()
-    }
-
-    fn contains_name(&self, name: &str) -> bool {
-        if self.name == name {
-            true
-        } else if !matches!(self.expression, Expression::Value(_)) {
-            self.get_left().contains_name(name) || self.get_right().contains_name(name)
-        } else {
-            false
-        }
-    }
-
-    fn find_x(&self, mut x_value: i64) -> i64 {
-        if self.name == MY_KEY {
-            return x_value;
-        }
-        let left = self.get_left();
-        let right = self.get_right();
-        if left.contains_name(MY_KEY) {
-            x_value = simplify_value(x_value, &self.expression, right.evaluate(), false);
-            left.find_x(x_value)
-        } else {
-            x_value = simplify_value(x_value, &self.expression, left.evaluate(), true);
-            right.find_x(x_value)
+    /// Returns the equation of the root monkey, whose operands must be equal, with the monkey
+    /// named `unknown` as a variable. Operations without variables are replaced by their value.
+    fn get_root_equation(&self, unknown: &str) -> Result<Equation, ExpressionError> {
+        let (dag, node_ids) = self.get_expressions(self.ids.get(unknown).copied());
+        let values = dag.constant_values()?;
+        match self.jobs[self.ids[ROOT_KEY]] {
+            Job::Operation(left, _, right) => Ok(Equation::new(
+                dag.to_expression(node_ids[left], &values),
+                dag.to_expression(node_ids[right], &values),
+            )),
+            Job::Number(_) => unreachable!("The root monkey is checked when parsed"),
         }
     }
 }
 
-fn part_one(nodes: &Node) -> i64 {
-    nodes.evaluate()
+fn part_one(monkeys: &Monkeys) -> i64 {
+    let value = monkeys
+        .evaluate(ROOT_KEY)
+        .unwrap_or_else(|error| panic!("Cannot evaluate the root monkey: {}", error));
+    to_integer(value.to_integer())
 }
 
-fn part_two(nodes: &Node) -> i64 {
-    let left = nodes.get_left();
-    let right = nodes.get_right();
-    if left.contains_name(MY_KEY) {
-        left.find_x(right.evaluate())
-    } else {
-        right.find_x(left.evaluate())
-    }
+fn part_two(monkeys: &Monkeys) -> i64 {
+    let equation = monkeys
+        .get_root_equation(MY_KEY)
+        .unwrap_or_else(|error| panic!("Cannot build the root equation: {}", error));
+    let value = equation
+        .solve(MY_KEY)
+        .unwrap_or_else(|error| panic!("Cannot solve {}: {}", equation, error));
+    to_integer(value.to_integer())
 }
 
-fn simplify_value(
-    x_value: i64,
-    operand: &Expression,
-    other_value: i64,
-    x_is_right_side: bool,
-) -> i64 {
-    match operand {
-        Expression::Add => x_value - other_value,
-        Expression::Sub => {
-            if x_is_right_side {
-                -x_value + other_value
-            } else {
-                x_value + other_value
-            }
-        }
-        Expression::Mul => x_value / other_value,
-        Expression::Div => {
-            if x_is_right_side {
-                other_value / x_value
-            } else {
-                x_value * other_value
-            }
-        }
-        _ => panic!("Cannot process {:?}", operand),
-    }
+fn to_integer(value: Option<i128>) -> i64 {
+    value
+        .and_then(|value| i64::try_from(value).ok())
+        .expect("The answer is not an integer")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const EXAMPLE: &str = "root: pppw + sjmn
+dbpl: 5
+cczh: sllz + lgvd
+zczc: 2
+ptdq: humn - dvpt
+dvpt: 3
+lfqf: 4
+humn: 5
+ljgn: 2
+sjmn: drzm * dbpl
+sllz: 4
+pppw: cczh / lfqf
+lgvd: ljgn * ptdq
+drzm: hmdt - zczc
+hmdt: 32";
+
+    #[test]
+    fn test_example() {
+        let input = Day21::parse(EXAMPLE).unwrap();
+        assert_eq!(Day21::part_one(&input), 152);
+        assert_eq!(Day21::part_two(&input), 301);
+    }
+
+    #[test]
+    fn test_root_equation() {
+        let monkeys = Day21::parse(EXAMPLE).unwrap();
+        let equation = monkeys.get_root_equation(MY_KEY).unwrap();
+        assert_eq!(equation.to_string(), "(4 + (2 * (humn - 3))) / 4 = 150");
+        // The number yelled by `pppw` to make the equality true
+        let equation = monkeys.get_root_equation("pppw").unwrap();
+        assert_eq!(equation.solve("pppw"), Ok(150.into()));
+    }
+
     #[test]
-    fn test_simplify_solution() {
-        // 150 = x - 3
-        assert_eq!(153, simplify_value(150, &Expression::Sub, 3, false));
-        // 150 = 3 - x
-        assert_eq!(-147, simplify_value(150, &Expression::Sub, 3, true));
-
-        // 150 = 3 + x
-        assert_eq!(147, simplify_value(150, &Expression::Add, 3, false));
-        // 150 = x + 3
-        assert_eq!(147, simplify_value(150, &Expression::Add, 3, true));
-
-        // 150 = x * 3
-        assert_eq!(50, simplify_value(150, &Expression::Mul, 3, false));
-        // 150 = 3 * x
-        assert_eq!(50, simplify_value(150, &Expression::Mul, 3, true));
-
-        // 150 = x / 3
-        assert_eq!(450, simplify_value(150, &Expression::Div, 3, false));
-        // 150 = 3 / x
-        assert_eq!(1 / 50, simplify_value(150, &Expression::Div, 3, true));
+    fn test_shared_monkeys() {
+        // `humn` is waited for by both sides of the root equation
+        let input = "root: aaaa + bbbb\naaaa: humn * cccc\nbbbb: humn + cccc\ncccc: 3\nhumn: 1";
+        let monkeys = Day21::parse(input).unwrap();
+        assert_eq!(monkeys.evaluate(ROOT_KEY), Ok(7.into()));
+        let equation = monkeys.get_root_equation(MY_KEY).unwrap();
+        assert_eq!(equation.to_string(), "humn * 3 = humn + 3");
+        assert_eq!(
+            equation.solve(MY_KEY),
+            Err(ExpressionError::UnknownOnBothSides(MY_KEY.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_monkeys() {
+        let error = Day21::parse("root: aaaa + bbbb\naaaa: 1\nbbbb: aaaa * cccc").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 14));
+        assert_eq!(error.expected, "the name of a monkey");
+
+        let input = "root: aaaa + bbbb\naaaa: 1\nbbbb: cccc * aaaa\ncccc: bbbb - aaaa";
+        let error = Day21::parse(input).unwrap_err();
+        assert_eq!(
+            error.expected,
+            "a monkey whose job does not depend on itself"
+        );
+        assert!(error.text == "bbbb" || error.text == "cccc");
+
+        let error = Day21::parse("root: aaaa + aaaa\naaaa: 1\naaaa: 2").unwrap_err();
+        assert_eq!((error.line, error.text.as_str()), (3, "aaaa"));
+        assert!(Day21::parse("aaaa: 1").is_err());
+
+        let error = Day21::parse("aaaa: 1\nroot: 5").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(error.expected, "a `root` monkey comparing two monkeys");
     }
 }
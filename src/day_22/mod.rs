@@ -1,78 +1,179 @@
-use advent_of_code::utils::inputs::{get_file, LINE_ENDING};
+use crate::solution::Solution;
+use crate::utils::grid::Grid;
+use crate::utils::inputs::{parse_number, LINE_ENDING};
+use crate::utils::parse_error::ParseError;
 use lazy_static::lazy_static;
 use regex::Regex;
-
-const CUBE_SIDE: usize = 50;
-const WIDTH: usize = 150;
-const HEIGHT: usize = 200;
+use std::collections::VecDeque;
 
 lazy_static! {
     static ref RE_INSTRUCTIONS: Regex = Regex::new(r"(\d+|L|R)").unwrap();
 }
 
-pub fn day_22() {
-    let (map, instructions) = get_input();
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Left,
+    Direction::Down,
+    Direction::Right,
+];
+
+/// A unit vector in the 3D space of the folded cube
+type Vector = (i8, i8, i8);
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+    type Input = (Map, Vec<Instruction>);
+    type PartOne = usize;
+    type PartTwo = usize;
 
-    let solution_1 = part_one(&map, &instructions);
-    println!("\t- Solution 1 is : {}", solution_1);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(input: &Self::Input) -> Self::PartOne {
+        part_one(&input.0, &input.1)
+    }
+
+    fn part_two(input: &Self::Input) -> Self::PartTwo {
+        part_two(&input.0, &input.1)
+    }
 }
 
-fn get_input() -> (Map, Vec<Instruction>) {
-    let file = get_file("./src/day_22/input.txt");
+fn get_input(file: &str) -> Result<(Map, Vec<Instruction>), ParseError> {
     let split_separator = format!("{}{}", LINE_ENDING, LINE_ENDING);
-    let split = file.split(&split_separator).collect::<Vec<_>>();
-    let (board_map_part, instructions_part) = (split[0], split[1]);
-    (
-        get_board_map(board_map_part),
-        get_instructions(instructions_part),
-    )
+    let (board_map_part, instructions_part) =
+        file.split_once(&split_separator).ok_or_else(|| {
+            ParseError::new(file, &file[file.len()..], "an empty line before the path")
+        })?;
+    Ok((
+        get_board_map(file, board_map_part)?,
+        get_instructions(file, instructions_part)?,
+    ))
 }
 
-fn get_board_map(map_part: &str) -> Map {
+fn get_board_map(file: &str, map_part: &str) -> Result<Map, ParseError> {
     let mut rows = vec![];
 
     for line in map_part.lines() {
         let mut row = vec![];
-        for c in line.chars() {
+        for (idx, c) in line.char_indices() {
             let tile = match c {
                 ' ' => Tile::Void,
                 '#' => Tile::Wall,
                 '.' => Tile::Path,
-                _ => panic!("Cannot parse tile `{}`", c),
+                _ => {
+                    return Err(ParseError::new(
+                        file,
+                        &line[idx..idx + c.len_utf8()],
+                        "` `, `#` or `.`",
+                    ))
+                }
             };
             row.push(tile)
         }
         rows.push(row)
     }
-    let max_row_len = rows.iter().map(|r| r.len()).max().unwrap();
+    let max_row_len = rows.iter().map(|r| r.len()).max().unwrap_or(0);
     for line in rows.iter_mut() {
         while line.len() < max_row_len {
             line.push(Tile::Void);
         }
     }
+    let not_a_cube = || ParseError::new(file, map_part, "a board which folds into a cube");
+    let tiles = Grid::from_rows(rows).ok_or_else(not_a_cube)?;
 
-    let tiles: Vec<_> = rows.into_iter().flatten().collect();
+    // The board is the net of a cube, so each one of the 6 faces holds a sixth of the tiles
+    let board_tiles = tiles
+        .iter()
+        .filter(|(_, t)| !matches!(t, Tile::Void))
+        .count();
+    let cube_side = ((board_tiles / 6) as f64).sqrt() as usize;
+    let faces = Some(cube_side)
+        .filter(|side| *side > 0 && board_tiles == 6 * side * side)
+        .and_then(|_| fold_cube(&tiles, cube_side))
+        .filter(|faces| faces.len() == 6)
+        .ok_or_else(not_a_cube)?;
 
-    Map {
-        cube_side: CUBE_SIDE,
-        width: WIDTH,
-        height: HEIGHT,
+    Ok(Map {
+        cube_side,
         tiles,
+        faces,
+    })
+}
+
+/// Fold the net drawn on the map into a cube, and get the orientation of each face.
+///
+/// The first face found on the top row is laid flat, then each face adjacent to an already
+/// folded one on the map is rolled over the shared edge. Returns `None` if the top row is empty.
+fn fold_cube(tiles: &Grid<Tile>, cube_side: usize) -> Option<Vec<Face>> {
+    let is_face = |point: (usize, usize)| !matches!(tiles[point], Tile::Void);
+    let start_x = (0..tiles.width())
+        .step_by(cube_side)
+        .find(|&x| is_face((x, 0)))?;
+
+    let mut faces = vec![Face {
+        x: start_x,
+        y: 0,
+        normal: (0, 0, 1),
+        right: (1, 0, 0),
+        down: (0, 1, 0),
+    }];
+    let mut frontier = VecDeque::new();
+    frontier.push_back(0);
+
+    while let Some(face_idx) = frontier.pop_front() {
+        for direction in DIRECTIONS.iter() {
+            let (offset_x, offset_y) = direction.get_offset();
+            let offset = (offset_x * cube_side as isize, offset_y * cube_side as isize);
+            let (x, y) = match tiles.offset((faces[face_idx].x, faces[face_idx].y), offset) {
+                Some(point) if is_face(point) => point,
+                _ => continue,
+            };
+            if faces.iter().any(|f| f.x == x && f.y == y) {
+                continue;
+            }
+            let new_face = faces[face_idx].roll(direction, x, y);
+            faces.push(new_face);
+            frontier.push_back(faces.len() - 1);
+        }
     }
+    Some(faces)
 }
 
-fn get_instructions(instructions_part: &str) -> Vec<Instruction> {
-    RE_INSTRUCTIONS
-        .captures_iter(instructions_part)
-        .map(|cap| match &cap[1] {
+fn get_instructions(file: &str, instructions_part: &str) -> Result<Vec<Instruction>, ParseError> {
+    let instructions_part = instructions_part.trim_end();
+    let mut instructions = vec![];
+    let mut last_end = 0;
+    for value in RE_INSTRUCTIONS.find_iter(instructions_part) {
+        if value.start() != last_end {
+            let unexpected = &instructions_part[last_end..value.start()];
+            return Err(ParseError::new(
+                file,
+                unexpected,
+                "`L`, `R` or a number of tiles",
+            ));
+        }
+        last_end = value.end();
+        instructions.push(match value.as_str() {
             "R" => Instruction::Right,
             "L" => Instruction::Left,
-            val => Instruction::Move(val.parse::<u8>().unwrap()),
-        })
-        .collect()
+            val => Instruction::Move(parse_number(file, val)?),
+        });
+    }
+    if last_end != instructions_part.len() {
+        let unexpected = &instructions_part[last_end..];
+        return Err(ParseError::new(
+            file,
+            unexpected,
+            "`L`, `R` or a number of tiles",
+        ));
+    }
+    Ok(instructions)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum Direction {
     Up,
     Left,
@@ -80,26 +181,53 @@ enum Direction {
     Right,
 }
 
+impl Direction {
+    /// Returns the `(x, y)` offset of a single step in this direction
+    fn get_offset(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Left => (-1, 0),
+            Direction::Down => (0, 1),
+            Direction::Right => (1, 0),
+        }
+    }
+
+    fn get_facing_value(&self) -> usize {
+        match self {
+            Direction::Right => 0,
+            Direction::Down => 1,
+            Direction::Left => 2,
+            Direction::Up => 3,
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Position {
-    idx: usize,
+    point: (usize, usize),
     direction: Direction,
 }
 
 impl Position {
     fn new(map: &Map) -> Self {
-        let idx = map.get_upper_leftmost_path();
+        let point = map.get_upper_leftmost_path();
         Position {
-            idx,
+            point,
             direction: Direction::Right,
         }
     }
 
-    fn apply_instruction(&mut self, map: &Map, instruction: &Instruction) {
+    fn apply_instruction(&mut self, map: &Map, instruction: &Instruction, on_cube: bool) {
         match instruction {
             Instruction::Left => self.rotate_left(),
             Instruction::Right => self.rotate_right(),
-            Instruction::Move(value) => self.move_forward_flat(map, *value),
+            Instruction::Move(value) => {
+                if on_cube {
+                    self.move_forward_cube(map, *value)
+                } else {
+                    self.move_forward_flat(map, *value)
+                }
+            }
         }
     }
 
@@ -123,81 +251,94 @@ impl Position {
     }
 
     fn move_forward_flat(&mut self, map: &Map, value: u8) {
-        let old_idx = self.idx;
         let mut step_nbr = value;
-        let mut last_valid_idx = self.idx;
+        let mut last_valid_point = self.point;
         while step_nbr > 0 {
-            let next_idx = self.move_flat_surface(map);
-            match map.tiles[next_idx] {
+            let next_point = self.move_flat_surface(map);
+            match map.tiles[next_point] {
                 Tile::Path => {
-                    self.idx = next_idx;
-                    last_valid_idx = self.idx;
+                    self.point = next_point;
+                    last_valid_point = self.point;
                     step_nbr -= 1
                 }
                 Tile::Wall => {
-                    self.idx = last_valid_idx;
+                    self.point = last_valid_point;
                     break;
                 }
-                Tile::Void => self.idx = next_idx,
+                Tile::Void => self.point = next_point,
             }
         }
     }
 
-    fn move_flat_surface(&self, map: &Map) -> usize {
-        match self.direction {
-            Direction::Up => {
-                if self.idx < map.width {
-                    self.idx + (map.width * map.height - map.width)
-                } else {
-                    self.idx - map.width
-                }
-            }
-            Direction::Right => {
-                if (self.idx + 1) % map.width == 0 {
-                    self.idx + 1 - map.width
-                } else {
-                    self.idx + 1
-                }
-            }
-            Direction::Down => {
-                if self.idx >= map.width * (map.height - 1) {
-                    self.idx - (map.width * map.height - map.width)
-                } else {
-                    self.idx + map.width
-                }
-            }
-            Direction::Left => {
-                if self.idx % map.width == 0 {
-                    self.idx + map.width - 1
-                } else {
-                    self.idx - 1
-                }
-            }
-        }
+    /// Returns the point after a single step on the map, wrapping around its edges
+    fn move_flat_surface(&self, map: &Map) -> (usize, usize) {
+        map.tiles
+            .wrapping_offset(self.point, self.direction.get_offset())
     }
-}
 
-fn print_map(position: &Position, map: &Map) {
-    for (idx, tile) in map.tiles.iter().enumerate() {
-        if position.idx == idx {
-            match position.direction {
-                Direction::Up => print!("^"),
-                Direction::Right => print!(">"),
-                Direction::Down => print!("V"),
-                Direction::Left => print!("<"),
-            }
-        } else {
-            match tile {
-                Tile::Path => print!("."),
-                Tile::Wall => print!("#"),
-                Tile::Void => print!(" "),
+    fn move_forward_cube(&mut self, map: &Map, value: u8) {
+        for _ in 0..value {
+            let (next_point, next_direction) = self.move_cube_surface(map);
+            if let Tile::Wall = map.tiles[next_point] {
+                break;
             }
+            self.point = next_point;
+            self.direction = next_direction;
         }
-        if (idx + 1) % map.width == 0 {
-            println!()
+    }
+
+    /// Returns the point and the direction after a single step on the folded cube.
+    ///
+    /// When the step leaves the current face, the next face is the one whose normal is the
+    /// direction we were walking in, and we enter it walking away from the face we just left.
+    fn move_cube_surface(&self, map: &Map) -> ((usize, usize), Direction) {
+        let (x, y) = self.point;
+        if let Some(next_point) = map.get_next_point_on_board(self.point, &self.direction) {
+            return (next_point, self.direction);
         }
+
+        let face = map.get_face(x, y);
+        let next_face = map.get_face_with_normal(face.get_axis(&self.direction));
+        let next_direction = next_face.get_direction(negate(face.normal));
+
+        // Both faces share an edge: get the position along it, and which axis it follows
+        let (edge_axis, edge_offset) = match self.direction {
+            Direction::Left | Direction::Right => (face.down, y - face.y),
+            Direction::Up | Direction::Down => (face.right, x - face.x),
+        };
+        let last = map.cube_side - 1;
+        let get_edge_offset = |axis: Vector| {
+            if axis == edge_axis {
+                edge_offset
+            } else {
+                last - edge_offset
+            }
+        };
+        let (next_offset_x, next_offset_y) = match next_direction {
+            Direction::Right => (0, get_edge_offset(next_face.down)),
+            Direction::Left => (last, get_edge_offset(next_face.down)),
+            Direction::Down => (get_edge_offset(next_face.right), 0),
+            Direction::Up => (get_edge_offset(next_face.right), last),
+        };
+        let next_point = (next_face.x + next_offset_x, next_face.y + next_offset_y);
+        (next_point, next_direction)
     }
-    println!()
+}
+
+#[allow(dead_code)]
+fn print_map(position: &Position, map: &Map) {
+    let mut drawing = map.tiles.map(|tile| match tile {
+        Tile::Path => '.',
+        Tile::Wall => '#',
+        Tile::Void => ' ',
+    });
+    drawing[position.point] = match position.direction {
+        Direction::Up => '^',
+        Direction::Right => '>',
+        Direction::Down => 'V',
+        Direction::Left => '<',
+    };
+    println!("{}\n", drawing)
 }
 
 #[derive(Debug)]
@@ -207,47 +348,176 @@ enum Tile {
     Path,
 }
 
+/// A face of the cube, located on the map by its upper left tile.
+/// `normal` is the direction the face is looking at once the cube is folded, and `right` and
+/// `down` are where the right and down directions of the map are pointing on this face.
 #[derive(Debug)]
-struct Map {
+struct Face {
+    x: usize,
+    y: usize,
+    normal: Vector,
+    right: Vector,
+    down: Vector,
+}
+
+impl Face {
+    /// Returns the face found by rolling the cube over the edge in the given direction
+    fn roll(&self, direction: &Direction, x: usize, y: usize) -> Self {
+        let (right, down) = match direction {
+            Direction::Up => (self.right, self.normal),
+            Direction::Left => (self.normal, self.down),
+            Direction::Down => (self.right, negate(self.normal)),
+            Direction::Right => (negate(self.normal), self.down),
+        };
+        Face {
+            x,
+            y,
+            normal: self.get_axis(direction),
+            right,
+            down,
+        }
+    }
+
+    /// Returns the vector matching a direction on this face
+    fn get_axis(&self, direction: &Direction) -> Vector {
+        match direction {
+            Direction::Up => negate(self.down),
+            Direction::Left => negate(self.right),
+            Direction::Down => self.down,
+            Direction::Right => self.right,
+        }
+    }
+
+    /// Returns the direction on this face matching a vector
+    fn get_direction(&self, axis: Vector) -> Direction {
+        *DIRECTIONS
+            .iter()
+            .find(|d| self.get_axis(d) == axis)
+            .unwrap_or_else(|| panic!("Axis {:?} is not on face {:?}", axis, self))
+    }
+}
+
+fn negate(vector: Vector) -> Vector {
+    (-vector.0, -vector.1, -vector.2)
+}
+
+#[derive(Debug)]
+pub struct Map {
     cube_side: usize,
-    width: usize,
-    height: usize,
-    tiles: Vec<Tile>,
+    tiles: Grid<Tile>,
+    faces: Vec<Face>,
 }
 
 impl Map {
-    fn get_upper_leftmost_path(&self) -> usize {
-        use Tile::*;
-        for (idx, tile) in self.tiles.iter().enumerate() {
-            if matches!(tile, Path) {
-                return idx;
-            }
-        }
-        panic!("Cannot get any Path tile")
+    /// Returns the point after a single step in the given direction, if it is still on the board
+    fn get_next_point_on_board(
+        &self,
+        point: (usize, usize),
+        direction: &Direction,
+    ) -> Option<(usize, usize)> {
+        self.tiles
+            .offset(point, direction.get_offset())
+            .filter(|next_point| !matches!(self.tiles[*next_point], Tile::Void))
+    }
+
+    fn get_face(&self, x: usize, y: usize) -> &Face {
+        self.faces
+            .iter()
+            .find(|f| {
+                (f.x..f.x + self.cube_side).contains(&x) && (f.y..f.y + self.cube_side).contains(&y)
+            })
+            .unwrap_or_else(|| panic!("Tile ({}, {}) is not on any face", x, y))
+    }
+
+    fn get_face_with_normal(&self, normal: Vector) -> &Face {
+        self.faces
+            .iter()
+            .find(|f| f.normal == normal)
+            .unwrap_or_else(|| panic!("Cannot find a face with normal {:?}", normal))
+    }
+
+    fn get_upper_leftmost_path(&self) -> (usize, usize) {
+        self.tiles
+            .find(|tile| matches!(tile, Tile::Path))
+            .expect("Cannot get any Path tile")
     }
 }
 
 #[derive(Debug)]
-enum Instruction {
+pub enum Instruction {
     Left,
     Right,
     Move(u8),
 }
 
-fn part_one(map: &Map, instructions: &Vec<Instruction>) -> usize {
-    use Direction::*;
+fn part_one(map: &Map, instructions: &[Instruction]) -> usize {
     let mut position = Position::new(map);
 
     for instruction in instructions {
-        position.apply_instruction(map, instruction);
+        position.apply_instruction(map, instruction, false);
+    }
+    get_password(&position)
+}
+
+fn part_two(map: &Map, instructions: &[Instruction]) -> usize {
+    let mut position = Position::new(map);
+
+    for instruction in instructions {
+        position.apply_instruction(map, instruction, true);
+    }
+    get_password(&position)
+}
+
+fn get_password(position: &Position) -> usize {
+    let (x, y) = (position.point.0 + 1, position.point.1 + 1);
+    (y * 1000) + (x * 4) + position.direction.get_facing_value()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::inputs::get_file;
+
+    #[test]
+    fn test_fold_cube_with_example() {
+        let (map, _) = get_input(&get_file("./src/day_22/input_example.txt").unwrap()).unwrap();
+        assert_eq!(map.cube_side, 4);
+        assert_eq!(map.faces.len(), 6);
+        for face in &map.faces {
+            assert_eq!(
+                map.faces.iter().filter(|f| f.normal == face.normal).count(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn test_move_cube_surface() {
+        let (map, _) = get_input(&get_file("./src/day_22/input_example.txt").unwrap()).unwrap();
+
+        // A, facing right, arrives at B facing down
+        let position = Position {
+            point: (11, 5),
+            direction: Direction::Right,
+        };
+        let (point, direction) = position.move_cube_surface(&map);
+        assert_eq!(point, (14, 8));
+        assert_eq!(direction, Direction::Down);
+
+        // C, facing down, arrives at D facing up
+        let position = Position {
+            point: (10, 11),
+            direction: Direction::Down,
+        };
+        let (point, direction) = position.move_cube_surface(&map);
+        assert_eq!(point, (1, 7));
+        assert_eq!(direction, Direction::Up);
+    }
+
+    #[test]
+    fn test_example() {
+        let input = Day22::parse(include_str!("input_example.txt")).unwrap();
+        assert_eq!(Day22::part_one(&input), 6032);
+        assert_eq!(Day22::part_two(&input), 5031);
     }
-    let facing_value = match position.direction {
-        Up => 3,
-        Right => 0,
-        Left => 2,
-        Down => 1,
-    };
-    let y = position.idx / map.width + 1;
-    let x = position.idx % map.width + 1;
-    (y * 1000) + (x * 4) + facing_value
 }
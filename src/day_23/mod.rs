@@ -1,61 +1,47 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::geometry::Point2;
+use crate::utils::grid::Grid;
+use crate::utils::parse_error::ParseError;
 use ahash::AHashSet;
-use lazy_static::lazy_static;
-use std::cmp::{max, min};
 use std::hash::{Hash, Hasher};
 use std::mem::swap;
-use std::ops::{Add, AddAssign};
-
-lazy_static! {
-    static ref SUROUNDING: [Point; 8] = [
-        Point { y: -1, x: -1 },
-        Point { y: -1, x: 0 },
-        Point { y: -1, x: 1 },
-        Point { y: 0, x: -1 },
-        Point { y: 0, x: 1 },
-        Point { y: 1, x: -1 },
-        Point { y: 1, x: 0 },
-        Point { y: 1, x: 1 },
-    ];
-    static ref NORTH_DIRECTION: [Point; 3] = [
-        Point { y: -1, x: -1 },
-        Point { y: -1, x: 0 },
-        Point { y: -1, x: 1 }
-    ];
-    static ref SOUTH_DIRECTION: [Point; 3] = [
-        Point { y: 1, x: -1 },
-        Point { y: 1, x: 0 },
-        Point { y: 1, x: 1 }
-    ];
-    static ref WEST_DIRECTION: [Point; 3] = [
-        Point { y: -1, x: -1 },
-        Point { y: 0, x: -1 },
-        Point { y: 1, x: -1 }
-    ];
-    static ref EAST_DIRECTION: [Point; 3] = [
-        Point { y: -1, x: 1 },
-        Point { y: 0, x: 1 },
-        Point { y: 1, x: 1 }
-    ];
-    static ref DIRECTIONS: [[Point; 3]; 4] = [
-        *NORTH_DIRECTION,
-        *SOUTH_DIRECTION,
-        *WEST_DIRECTION,
-        *EAST_DIRECTION
-    ];
-}
 
-pub fn day_23() {
-    let elves = get_input();
-    let solution_1 = part_one(&elves);
-    println!("\t- Solution 1 is : {}", solution_1);
+type Point = Point2<i64>;
+
+const NORTH_DIRECTION: [Point; 3] = [Point::new(-1, -1), Point::new(0, -1), Point::new(1, -1)];
+const SOUTH_DIRECTION: [Point; 3] = [Point::new(-1, 1), Point::new(0, 1), Point::new(1, 1)];
+const WEST_DIRECTION: [Point; 3] = [Point::new(-1, -1), Point::new(-1, 0), Point::new(-1, 1)];
+const EAST_DIRECTION: [Point; 3] = [Point::new(1, -1), Point::new(1, 0), Point::new(1, 1)];
+const DIRECTIONS: [[Point; 3]; 4] = [
+    NORTH_DIRECTION,
+    SOUTH_DIRECTION,
+    WEST_DIRECTION,
+    EAST_DIRECTION,
+];
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+    type Input = AHashSet<Elf>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(elves: &Self::Input) -> Self::PartOne {
+        part_one(elves)
+    }
 
-    let solution_2 = part_two(&elves);
-    println!("\t- Solution 2 is : {}", solution_2);
+    fn part_two(elves: &Self::Input) -> Self::PartTwo {
+        part_two(elves)
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
-struct Elf {
+pub struct Elf {
     id: usize,
     position: Point,
 }
@@ -83,50 +69,23 @@ impl Hash for Elf {
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Debug, Copy, Clone)]
-struct Point {
-    y: i64,
-    x: i64,
-}
-
-impl Add for Point {
-    type Output = Point;
-
-    fn add(self, other: Point) -> Point {
-        Point {
-            y: self.y + other.y,
-            x: self.x + other.x,
-        }
-    }
-}
+fn get_input(input: &str) -> Result<AHashSet<Elf>, ParseError> {
+    let grove = Grid::parse(input, "`#` or `.`", |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })?;
 
-impl AddAssign for Point {
-    fn add_assign(&mut self, other: Point) {
-        self.y += other.y;
-        self.x += other.x;
-    }
-}
-
-fn get_input() -> AHashSet<Elf> {
-    let mut elves = AHashSet::new();
-    let mut current_idx = 0;
-    for (y, line) in get_file("./src/day_23/input.txt").lines().enumerate() {
-        line.chars()
-            .enumerate()
-            .filter(|(_, char)| '#'.eq(char))
-            .for_each(|(x, _)| {
-                let elf = Elf {
-                    id: current_idx,
-                    position: Point {
-                        y: y as i64,
-                        x: x as i64,
-                    },
-                };
-                elves.insert(elf);
-                current_idx += 1;
-            });
-    }
-    elves
+    let elves = grove
+        .iter()
+        .filter(|(_, is_elf)| **is_elf)
+        .enumerate()
+        .map(|(id, ((x, y), _))| Elf {
+            id,
+            position: Point::new(x as i64, y as i64),
+        })
+        .collect();
+    Ok(elves)
 }
 
 fn part_one(elves: &AHashSet<Elf>) -> usize {
@@ -194,7 +153,10 @@ fn calculate_elves_next_moves(
 }
 
 fn is_alone(elves: &AHashSet<Elf>, elf: &Elf) -> bool {
-    SUROUNDING.iter().all(|d| !elves.contains(&elf.move_to(d)))
+    Point::new(0, 0)
+        .neighbours_8()
+        .iter()
+        .all(|d| !elves.contains(&elf.move_to(d)))
 }
 
 fn can_move_to_tile(elves: &AHashSet<Elf>, elf: &Elf, directions: &[Point; 3]) -> bool {
@@ -231,33 +193,45 @@ fn update_elves_next_turn(
 }
 
 fn get_boundaries(elves: &AHashSet<Elf>) -> (Point, Point) {
-    let (mut min_x, mut min_y) = (i64::MAX, i64::MAX);
-    let (mut max_x, mut max_y) = (i64::MIN, i64::MIN);
+    let mut min_point = Point::new(i64::MAX, i64::MAX);
+    let mut max_point = Point::new(i64::MIN, i64::MIN);
     for elf in elves {
-        min_x = min(min_x, elf.position.x);
-        min_y = min(min_y, elf.position.y);
-        max_x = max(max_x, elf.position.x);
-        max_y = max(max_y, elf.position.y);
+        min_point = min_point.component_min(&elf.position);
+        max_point = max_point.component_max(&elf.position);
     }
-    (Point { y: min_y, x: min_x }, Point { y: max_y, x: max_x })
+    (min_point, max_point)
 }
 
 fn _print_map(elves: &AHashSet<Elf>, min_points: &Point, max_points: &Point) {
-    for y in min_points.y..=max_points.y {
-        for x in min_points.x..=max_points.x {
-            if elves.contains(&Elf {
-                id: 0,
-                position: Point { y, x },
-            }) {
-                print!("#");
-            } else {
-                print!(".");
-            }
-        }
-        println!();
+    let size = *max_points - *min_points;
+    let mut grove = Grid::new(size.x as usize + 1, size.y as usize + 1, '.');
+    for elf in elves {
+        let position = elf.position - *min_points;
+        grove[(position.x as usize, position.y as usize)] = '#';
     }
+    println!("{}", grove);
 }
 
 fn count_space(elves: &AHashSet<Elf>, min_points: &Point, max_points: &Point) -> usize {
     ((max_points.y - min_points.y + 1) * (max_points.x - min_points.x + 1)) as usize - elves.len()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "....#..
+..###.#
+#...#.#
+.#...##
+#.###..
+##.#.##
+.#..#..";
+
+    #[test]
+    fn test_example() {
+        let input = Day23::parse(EXAMPLE).unwrap();
+        assert_eq!(Day23::part_one(&input), 110);
+        assert_eq!(Day23::part_two(&input), 20);
+    }
+}
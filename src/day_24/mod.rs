@@ -1,6 +1,8 @@
-use advent_of_code::utils::inputs::get_file;
-use ahash::AHashMap;
-use std::collections::VecDeque;
+use crate::solution::Solution;
+use crate::utils::cycle::{Cycle, CycleDetector};
+use crate::utils::grid::{Grid, Position};
+use crate::utils::parse_error::ParseError;
+use crate::utils::search;
 
 const DIRECTIONS: [Direction; 4] = [
     Direction::Up,
@@ -9,33 +11,38 @@ const DIRECTIONS: [Direction; 4] = [
     Direction::Left,
 ];
 
-type MinuteIdx = (usize, usize);
+pub struct Day24;
 
-pub fn day_24() {
-    let (map, map_states) = get_input();
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+    type Input = (MapProperties, MapStates);
+    type PartOne = usize;
+    type PartTwo = usize;
 
-    let solution_1 = part_one(&map, &map_states);
-    println!("\t- Solution 1 is : {}", solution_1);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(input: &Self::Input) -> Self::PartOne {
+        part_one(&input.0, &input.1)
+    }
 
-    let solution_2 = part_two(&map, &map_states);
-    println!("\t- Solution 2 is : {}", solution_2);
+    fn part_two(input: &Self::Input) -> Self::PartTwo {
+        part_two(&input.0, &input.1)
+    }
 }
 
 fn part_one(map: &MapProperties, map_states: &MapStates) -> usize {
-    let path = bfs(map, map_states, map.start, map.end, 0);
-    path.len() - 1
+    bfs(map, map_states, map.start, map.end, 0)
 }
 
 fn part_two(map: &MapProperties, map_states: &MapStates) -> usize {
     // First trip
-    let path = bfs(map, map_states, map.start, map.end, 0);
-    let last_pos = path[path.len() - 1];
+    let minute = bfs(map, map_states, map.start, map.end, 0);
     // Going back
-    let path = bfs(map, map_states, map.end, map.start, last_pos.0);
-    let last_pos = path[path.len() - 1];
+    let minute = bfs(map, map_states, map.end, map.start, minute);
     // Second trip
-    let path = bfs(map, map_states, map.start, map.end, last_pos.0);
-    path[path.len() - 1].0
+    bfs(map, map_states, map.start, map.end, minute)
 }
 
 #[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
@@ -47,52 +54,66 @@ enum Direction {
 }
 
 #[derive(Debug)]
-struct MapProperties {
+pub struct MapProperties {
     width: usize,
     height: usize,
-    start: usize,
-    end: usize,
+    start: Position,
+    end: Position,
+}
+
+impl MapProperties {
+    /// State if a point is inside the walls of the valley
+    fn is_in_valley(&self, (x, y): Position) -> bool {
+        (1..self.width - 1).contains(&x) && (1..self.height - 1).contains(&y)
+    }
 }
 
+/// The states of the valley at each minute, until they repeat
 #[derive(Debug, Hash, Eq, PartialEq, Clone)]
-struct MapStates {
+pub struct MapStates {
     states: Vec<State>,
+    cycle: Cycle<usize>,
 }
 
 impl MapStates {
-    fn get_state_at_minute(&self, minute: usize) -> &State {
-        &self.states[minute % self.states.len()]
-    }
-
-    fn generate_states(&mut self, map: &MapProperties) {
-        let first_turn = self.states[0].clone();
-        for current_turn in 0..1_000_000 {
-            let new_state = self.states[current_turn].get_state_next_minute(map);
-            if first_turn.eq(&new_state) {
-                break;
+    fn new(first_state: State, map: &MapProperties) -> Self {
+        let mut detector = CycleDetector::new();
+        let mut state = first_state;
+        let cycle = loop {
+            // The states are never shifted, so their offset is always 0
+            if let Some(cycle) = detector.push(state, 0) {
+                break cycle;
             }
-            self.states.push(new_state);
+            state = detector.key(detector.len() - 1).get_state_next_minute(map);
+        };
+        Self {
+            states: detector.into_keys(),
+            cycle,
         }
     }
 
+    fn get_state_at_minute(&self, minute: usize) -> &State {
+        &self.states[self.cycle.reduce(minute).0]
+    }
+
     fn get_possible_expedition_moves(
         &self,
         map: &MapProperties,
         curr_minute: usize,
-        curr_idx: usize,
-    ) -> Vec<usize> {
+        curr_position: Position,
+    ) -> Vec<Position> {
         let state = self.get_state_at_minute(curr_minute + 1);
         let mut moves = Vec::with_capacity(5);
 
         for direction in DIRECTIONS.iter() {
-            if let Some(tmp_idx) = State::move_expedition(map, direction, curr_idx) {
-                if state.cells[tmp_idx].is_empty() {
-                    moves.push(tmp_idx)
+            if let Some(tmp_position) = State::move_expedition(map, direction, curr_position) {
+                if state.cells[tmp_position].is_empty() {
+                    moves.push(tmp_position)
                 }
             }
         }
-        if state.cells[curr_idx].is_empty() {
-            moves.push(curr_idx)
+        if state.cells[curr_position].is_empty() {
+            moves.push(curr_position)
         }
         moves
     }
@@ -100,230 +121,180 @@ impl MapStates {
 
 #[derive(Debug, Hash, Eq, PartialEq, Clone)]
 struct State {
-    cells: Vec<Vec<Direction>>,
+    cells: Grid<Vec<Direction>>,
 }
 
 impl State {
     fn new_empty(map: &MapProperties) -> Self {
         Self {
-            cells: (0..(map.width * map.height)).map(|_| vec![]).collect(),
+            cells: Grid::new(map.width, map.height, vec![]),
         }
     }
 
-    fn push_value(&mut self, value: Direction, idx: usize) {
-        self.cells[idx].push(value)
-    }
-
-    fn get_values(&self, idx: usize) -> &Vec<Direction> {
-        &self.cells[idx]
+    fn push_value(&mut self, value: Direction, position: Position) {
+        self.cells[position].push(value)
     }
 
     fn get_state_next_minute(&self, map: &MapProperties) -> Self {
         let mut new_state = State::new_empty(map);
-        for (idx, cell) in self.cells.iter().enumerate() {
+        for (position, cell) in self.cells.iter() {
             for direction in cell {
-                let idx_next_turn = Self::get_blizzard_idx_next_minute(map, direction, idx);
-                new_state.push_value(*direction, idx_next_turn);
+                let position_next_turn =
+                    Self::get_blizzard_position_next_minute(map, direction, position);
+                new_state.push_value(*direction, position_next_turn);
             }
         }
         new_state
     }
 
-    fn get_blizzard_idx_next_minute(
+    /// Blizzards reaching a wall appear on the other side of the valley
+    fn get_blizzard_position_next_minute(
         map: &MapProperties,
         direction: &Direction,
-        current_idx: usize,
-    ) -> usize {
+        (x, y): Position,
+    ) -> Position {
         match direction {
-            Direction::Up => {
-                if current_idx < map.width * 2 {
-                    current_idx + (map.width * (map.height - 3))
-                } else {
-                    current_idx - map.width
-                }
-            }
-            Direction::Right => {
-                if (current_idx + 2) % map.width == 0 {
-                    current_idx - (map.width - 3)
-                } else {
-                    current_idx + 1
-                }
-            }
-            Direction::Down => {
-                if current_idx >= map.width * (map.height - 2) {
-                    current_idx - (map.width * (map.height - 3))
-                } else {
-                    current_idx + map.width
-                }
-            }
-            Direction::Left => {
-                if current_idx % map.width == 1 {
-                    current_idx + (map.width - 3)
-                } else {
-                    current_idx - 1
-                }
-            }
+            Direction::Up if y == 1 => (x, map.height - 2),
+            Direction::Up => (x, y - 1),
+            Direction::Right if x == map.width - 2 => (1, y),
+            Direction::Right => (x + 1, y),
+            Direction::Down if y == map.height - 2 => (x, 1),
+            Direction::Down => (x, y + 1),
+            Direction::Left if x == 1 => (map.width - 2, y),
+            Direction::Left => (x - 1, y),
         }
     }
 
+    /// The expedition can move inside the valley, or to the start and end points in its walls
     fn move_expedition(
         map: &MapProperties,
         direction: &Direction,
-        current_idx: usize,
-    ) -> Option<usize> {
-        match direction {
-            Direction::Up => {
-                if !(current_idx < map.width * 2
-                    && (map.start != current_idx.saturating_sub(map.width)))
-                {
-                    return Some(current_idx - map.width);
-                }
-            }
-            Direction::Right => {
-                if !((current_idx + 2) % map.width == 0
-                    || (current_idx < map.width)
-                    || (current_idx >= map.width * (map.height - 1)))
-                {
-                    return Some(current_idx + 1);
-                }
-            }
-            Direction::Down => {
-                if !(current_idx >= map.width * (map.height - 2)
-                    && map.end != current_idx + map.width)
-                {
-                    return Some(current_idx + map.width);
-                }
-            }
-            Direction::Left => {
-                if !(current_idx % map.width == 1
-                    || (current_idx < map.width)
-                    || (current_idx >= map.width * (map.height - 1)))
-                {
-                    return Some(current_idx - 1);
-                }
-            }
+        current_position: Position,
+    ) -> Option<Position> {
+        let (offset_x, offset_y) = match direction {
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+        };
+        let (x, y) = current_position;
+        let position = (
+            x.checked_add_signed(offset_x)?,
+            y.checked_add_signed(offset_y)?,
+        );
+        if map.is_in_valley(position) || position == map.start || position == map.end {
+            Some(position)
+        } else {
+            None
         }
-        None
     }
 }
 
-fn get_input() -> (MapProperties, MapStates) {
-    let file = get_file("./src/day_24/input.txt");
-    let lines: Vec<_> = file.lines().collect();
-    let height = lines.len();
-    let width = lines[0].len();
-    let start_x = lines[0].find('.').expect("Cannot find starting point");
-    let end_x = lines[lines.len() - 1]
-        .find('.')
-        .expect("Cannot find starting point");
-    let start = start_x;
-    let end = width * height - (width - end_x);
+fn get_input(input: &str) -> Result<(MapProperties, MapStates), ParseError> {
+    let tiles = Grid::parse(input, "a wall, a ground or a blizzard tile", |c| {
+        Some(c).filter(|c| "#.><v^".contains(*c))
+    })?;
+    if tiles.height() < 3 {
+        return Err(ParseError::new(
+            input,
+            input,
+            "a valley surrounded by walls",
+        ));
+    }
+    let lines: Vec<_> = input.lines().collect();
+    let (width, height) = (tiles.width(), tiles.height());
+    let start_x = tiles
+        .row(0)
+        .iter()
+        .position(|c| *c == '.')
+        .ok_or_else(|| ParseError::new(input, lines[0], "a starting point"))?;
+    let end_x = tiles
+        .row(height - 1)
+        .iter()
+        .position(|c| *c == '.')
+        .ok_or_else(|| ParseError::new(input, lines[height - 1], "an ending point"))?;
     let map = MapProperties {
         width,
         height,
-        start,
-        end,
+        start: (start_x, 0),
+        end: (end_x, height - 1),
     };
-    let mut state = State::new_empty(&map);
 
-    for (y, row) in lines.iter().enumerate() {
-        for (x, cell) in row.chars().enumerate() {
-            match cell {
-                '>' => {
-                    state.push_value(Direction::Right, y * width + x);
-                }
-                '<' => {
-                    state.push_value(Direction::Left, y * width + x);
-                }
-                'v' => {
-                    state.push_value(Direction::Down, y * width + x);
-                }
-                '^' => {
-                    state.push_value(Direction::Up, y * width + x);
-                }
-                _ => {}
-            };
-        }
-    }
-    let mut map_states = MapStates {
-        states: vec![state],
-    };
-    map_states.generate_states(&map);
-    (map, map_states)
+    let cells = tiles.map(|tile| match tile {
+        '>' => vec![Direction::Right],
+        '<' => vec![Direction::Left],
+        'v' => vec![Direction::Down],
+        '^' => vec![Direction::Up],
+        _ => vec![],
+    });
+    let map_states = MapStates::new(State { cells }, &map);
+    Ok((map, map_states))
 }
 
 #[allow(dead_code)]
-fn print_map(map: &MapProperties, state: &State, expedition: usize) {
-    for y in 0..map.height {
-        for x in 0..map.width {
-            let point = y * map.width + x;
-            let point_value = state.get_values(point);
-            if (y * map.width + x) == expedition {
-                print!("E");
-            } else if point.eq(&map.start) {
-                print!("S")
-            } else if point.eq(&map.end) {
-                print!("G")
-            } else if point_value.len() > 1 {
-                print!("{}", point_value.len())
-            } else if point_value.len() == 1 {
-                match point_value[0] {
-                    Direction::Up => print!("^"),
-                    Direction::Right => print!(">"),
-                    Direction::Down => print!("v"),
-                    Direction::Left => print!("<"),
-                }
-            } else if y == 0 || y == map.height - 1 || x == 0 || x == map.width - 1 {
-                print!("#");
-            } else {
-                print!(".");
-            }
+fn print_map(map: &MapProperties, state: &State, expedition: Position) {
+    let drawing = state.cells.map(|values| match values[..] {
+        [] => '.',
+        [Direction::Up] => '^',
+        [Direction::Right] => '>',
+        [Direction::Down] => 'v',
+        [Direction::Left] => '<',
+        _ => char::from_digit(values.len() as u32, 10).unwrap_or('*'),
+    });
+    for (position, tile) in drawing.iter() {
+        if position == expedition {
+            print!("E");
+        } else if position == map.start {
+            print!("S")
+        } else if position == map.end {
+            print!("G")
+        } else if *tile == '.' && !map.is_in_valley(position) {
+            print!("#");
+        } else {
+            print!("{}", tile);
+        }
+        if position.0 == map.width - 1 {
+            println!()
         }
-        println!()
     }
 }
 
+/// Returns the minute the expedition reaches `end`, leaving `start` at `minute`
 fn bfs(
     map: &MapProperties,
     map_states: &MapStates,
-    start: usize,
-    end: usize,
+    start: Position,
+    end: Position,
     minute: usize,
-) -> Vec<MinuteIdx> {
-    let mut frontier = VecDeque::with_capacity(1000);
-    let mut came_from = AHashMap::with_capacity(120_000);
-    frontier.push_back((minute, start));
-
-    while let Some((curr_minute, curr_idx)) = frontier.pop_front() {
-        for possible_idx in map_states.get_possible_expedition_moves(map, curr_minute, curr_idx) {
-            came_from
-                .entry((curr_minute + 1, possible_idx))
-                .or_insert_with(|| {
-                    frontier.push_back((curr_minute + 1, possible_idx));
-                    (curr_minute, curr_idx)
-                });
-            if possible_idx == end {
-                return get_path(&came_from, start, end, curr_minute + 1, minute);
-            }
-        }
-    }
-    panic!("No path found")
+) -> usize {
+    let search = search::bfs(
+        (minute, start),
+        |&(curr_minute, curr_position)| {
+            map_states
+                .get_possible_expedition_moves(map, curr_minute, curr_position)
+                .into_iter()
+                .map(move |possible_position| (curr_minute + 1, possible_position))
+        },
+        |(_, position)| *position == end,
+    );
+    search.goal().expect("No path found").0
 }
 
-fn get_path(
-    came_from: &AHashMap<MinuteIdx, MinuteIdx>,
-    start: usize,
-    end: usize,
-    minute: usize,
-    start_min: usize,
-) -> Vec<MinuteIdx> {
-    let mut current = (minute, end);
-    let mut path = vec![current];
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#";
 
-    while current != (start_min, start) {
-        current = *came_from.get(&current).unwrap();
-        path.push(current);
+    #[test]
+    fn test_example() {
+        let input = Day24::parse(EXAMPLE).unwrap();
+        assert_eq!(Day24::part_one(&input), 18);
+        assert_eq!(Day24::part_two(&input), 54);
     }
-    path.reverse();
-    path
 }
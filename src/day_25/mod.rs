@@ -1,34 +1,50 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::{NoSolution, Solution};
+use crate::utils::inputs::parse_lines;
+use crate::utils::parse_error::ParseError;
 
-pub fn day_25() {
-    let numbers = get_input();
+pub struct Day25;
 
-    let solution_1 = part_one(&numbers);
-    println!("\t- Solution 1 is : {}", solution_1);
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+    type Input = Vec<i64>;
+    type PartOne = String;
+    type PartTwo = NoSolution;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
+
+    fn part_one(numbers: &Self::Input) -> Self::PartOne {
+        part_one(numbers)
+    }
+
+    fn part_two(_numbers: &Self::Input) -> Self::PartTwo {
+        NoSolution
+    }
 }
 
-fn get_input() -> Vec<i64> {
-    get_file("./src/day_25/input.txt")
-        .lines()
-        .map(snafu_to_decimal)
-        .collect()
+fn get_input(input: &str) -> Result<Vec<i64>, ParseError> {
+    parse_lines(input, snafu_to_decimal)
 }
 
-fn snafu_to_decimal(snafu: &str) -> i64 {
+fn snafu_to_decimal(snafu: &str) -> Result<i64, ParseError> {
     snafu
-        .chars()
+        .char_indices()
         .rev()
         .enumerate()
-        .map(|(idx, digit)| {
+        .map(|(power, (idx, digit))| {
             let value: i64 = match digit {
                 '=' => -2,
                 '-' => -1,
                 '0' => 0,
                 '1' => 1,
                 '2' => 2,
-                _ => panic!("Cannot convert SNAFU value {} to i64", digit),
+                _ => {
+                    let text = &snafu[idx..idx + digit.len_utf8()];
+                    return Err(ParseError::new(snafu, text, "a SNAFU digit"));
+                }
             };
-            value * 5_i64.pow(idx as u32)
+            Ok(value * 5_i64.pow(power as u32))
         })
         .sum()
 }
@@ -59,6 +75,28 @@ fn decimal_to_snafu(mut decimal: i64) -> String {
 mod tests {
     use super::*;
 
+    const EXAMPLE: &str = "1=-0-2
+12111
+2=0=
+21
+2=01
+111
+20012
+112
+1=-1=
+1-12
+12
+1=
+122";
+
+    #[test]
+    fn test_example() {
+        let input = Day25::parse(EXAMPLE).unwrap();
+        assert_eq!(input.iter().sum::<i64>(), 4890);
+        assert_eq!(Day25::part_one(&input), "2=-1=0");
+        assert_eq!(Day25::part_two(&input), NoSolution);
+    }
+
     #[test]
     fn test_decimal_to_snafu() {
         let values = vec![
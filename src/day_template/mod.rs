@@ -1,28 +1,36 @@
-use advent_of_code::utils::inputs::get_file;
+use crate::solution::Solution;
+use crate::utils::inputs::{parse_lines, parse_number};
+use crate::utils::parse_error::ParseError;
 
+pub struct Day00;
 
-pub fn day_00() {
-    // let inputs = get_input();
-    //
-    // let solution_1 = part_one(&inputs);
-    // println!("\t- Solution 1 is : {}", solution_1);
-    //
-    // let solution_2 = part_two(&inputs);
-    // println!("\t- Solution 2 is : {}", solution_2);
-}
+impl Solution for Day00 {
+    const DAY: u8 = 0;
+    type Input = Vec<i32>;
+    type PartOne = i32;
+    type PartTwo = i32;
 
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_input(input)
+    }
 
-fn get_input() -> Vec<i32> {
-    let file = get_file("./src/day_00/input.txt");
-    unimplemented!()
+    fn part_one(inputs: &Self::Input) -> Self::PartOne {
+        part_one(inputs)
+    }
+
+    fn part_two(inputs: &Self::Input) -> Self::PartTwo {
+        part_two(inputs)
+    }
 }
 
+fn get_input(input: &str) -> Result<Vec<i32>, ParseError> {
+    parse_lines(input, |line| parse_number(line, line))
+}
 
-fn part_one(inputs: &[i32]) -> i32 {
+fn part_one(_inputs: &[i32]) -> i32 {
     unimplemented!()
 }
 
-
-fn part_two(inputs: &[i32]) -> i32 {
+fn part_two(_inputs: &[i32]) -> i32 {
     unimplemented!()
 }
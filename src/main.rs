@@ -1,80 +1,413 @@
+use advent_of_code::scaffold;
+use advent_of_code::solution::{get_solver, get_solvers, Answers, Timings};
+use advent_of_code::utils::answers::{get_day_answers_path, Check, ExpectedAnswers};
+use advent_of_code::utils::inputs::{read_input, InputSource};
+use ansi_term::Colour;
+use chrono::{Datelike, Local};
 use std::env;
+use std::panic;
+use std::path::PathBuf;
+use std::process;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 
-mod day_01;
-mod day_02;
-mod day_03;
-mod day_04;
-mod day_05;
-mod day_06;
-mod day_07;
-mod day_08;
-mod day_09;
-mod day_10;
-mod day_11;
-mod day_12;
-mod day_13;
-mod day_14;
-mod day_15;
-mod day_16;
-mod day_17;
-mod day_18;
-mod day_19;
-mod day_20;
-mod day_21;
-mod day_22;
-mod day_23;
-mod day_24;
-mod day_25;
+const USAGE: &str = "Usage: advent_of_code <days> [--input <path> | -] [--check] [--answers <path>]
+                     [--threshold <ms>] [--jobs <n>]
+       advent_of_code new-day <day> [--root <path>] [--year <year>]
+       advent_of_code --help
+
+Arguments:
+    <days>            The days to solve: a single day between 1 and 25, a range of days like `5..12`,
+                      or `all`
+
+Options:
+    --input <path>    Read the puzzle input from <path> instead of `src/day_XX/input.txt`.
+                      Use `-` to read it from the standard input. Only for a single day.
+    --check           Check the answers against the expected ones, stored in
+                      `src/day_XX/answers.toml`
+    --answers <path>  Check the answers against the expected ones stored in <path>.
+                      Only for a single day.
+    --threshold <ms>  When solving several days, flag the days slower than <ms> milliseconds
+                      [default: 1000]
+    --jobs <n>        When solving several days, solve at most <n> days at the same time
+                      [default: the number of CPUs]
+    -h, --help        Print this help
+
+Commands:
+    new-day <day>     Create `src/day_XX/` from `src/day_template/`, and register the new day
+    --root <path>     The repository to create the day in, laid out like this one [default: .]
+    --year <year>     The year of the puzzle linked in the new day's README [default: this year]";
+
+const DEFAULT_THRESHOLD: Duration = Duration::from_millis(1000);
+
+struct Args {
+    days: Vec<u8>,
+    input: Option<InputSource>,
+    check: bool,
+    answers: Option<String>,
+    threshold: Duration,
+    jobs: Option<usize>,
+}
+
+/// Why a day could not be solved
+enum DayError {
+    /// The input cannot be read or parsed, with the error message to print
+    Input(String),
+    Panic,
+}
+
+/// The outcome of solving a day, or the error if it failed
+struct DayReport {
+    day: u8,
+    result: Result<(Answers, Timings), DayError>,
+    /// The expected answers, when they are checked
+    expected: Option<Result<ExpectedAnswers, String>>,
+}
+
+impl DayReport {
+    /// Returns the check of each part, if the day was solved and its answers are checked
+    fn get_checks(&self) -> Option<(Check, Check)> {
+        match (&self.result, &self.expected) {
+            (Ok((answers, _)), Some(Ok(expected))) => Some(expected.check(answers)),
+            (Ok(_), Some(Err(_))) => Some((Check::Unchecked, Check::Unchecked)),
+            _ => None,
+        }
+    }
+
+    fn is_success(&self) -> bool {
+        let has_failed_check = self.get_checks().is_some_and(|(part_one, part_two)| {
+            matches!(part_one, Check::Fail { .. }) || matches!(part_two, Check::Fail { .. })
+        });
+        self.result.is_ok() && !has_failed_check
+    }
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    match args.len() {
-        2 => solve_day(
-            args[1]
-                .trim()
-                .parse()
-                .expect("You must enter a number between 1 and 25."),
-        ),
-        _ => invalid_input(),
-    }
-}
-
-fn solve_day(day: i32) {
-    if !(1..=25).contains(&day) {
-        invalid_input();
-    }
-    let fn_day = match day {
-        1 => day_01::day_01,
-        2 => day_02::day_02,
-        3 => day_03::day_03,
-        4 => day_04::day_04,
-        5 => day_05::day_05,
-        6 => day_06::day_06,
-        7 => day_07::day_07,
-        8 => day_08::day_08,
-        9 => day_09::day_09,
-        10 => day_10::day_10,
-        11 => day_11::day_11,
-        12 => day_12::day_12,
-        13 => day_13::day_13,
-        14 => day_14::day_14,
-        15 => day_15::day_15,
-        16 => day_16::day_16,
-        17 => day_17::day_17,
-        18 => day_18::day_18,
-        19 => day_19::day_19,
-        20 => day_20::day_20,
-        21 => day_21::day_21,
-        22 => day_22::day_22,
-        23 => day_23::day_23,
-        24 => day_24::day_24,
-        25 => day_25::day_25,
-        _ => unimplemented!(),
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+    if args.first().map(String::as_str) == Some("new-day") {
+        new_day(&args[1..]);
+        return;
+    }
+    let args = parse_args(&args).unwrap_or_else(|message| invalid_input(&message));
+
+    let reports = if let [day] = args.days[..] {
+        let source = args.input.unwrap_or(InputSource::Bundled(day));
+        let check = args.check;
+        let answers = args
+            .answers
+            .or_else(|| Some(get_day_answers_path(day)).filter(|_| check));
+        let report = solve_day(day, &source, answers.as_deref());
+        print_report(&report);
+        vec![report]
+    } else {
+        let start = Instant::now();
+        let reports = solve_days(&args.days, args.check, args.jobs);
+        print_timings(&reports, args.threshold, start.elapsed());
+        reports
+    };
+    if args.check {
+        print_checks_summary(&reports);
+    }
+    if !reports.iter().all(DayReport::is_success) {
+        process::exit(1)
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut args = args.iter();
+    let days = parse_days(args.next().ok_or("Missing day")?)?;
+    let mut input = None;
+    let mut check = false;
+    let mut answers = None;
+    let mut threshold = DEFAULT_THRESHOLD;
+    let mut jobs = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                let path = args.next().ok_or("Missing path after `--input`")?;
+                input = Some(InputSource::from_arg(path));
+            }
+            "-" => input = Some(InputSource::Stdin),
+            "--check" => check = true,
+            "--answers" => {
+                let path = args.next().ok_or("Missing path after `--answers`")?;
+                answers = Some(path.to_string());
+                check = true;
+            }
+            "--threshold" => {
+                let value = args.next().ok_or("Missing value after `--threshold`")?;
+                let millis = value
+                    .parse()
+                    .map_err(|_| format!("Invalid threshold `{}`", value))?;
+                threshold = Duration::from_millis(millis);
+            }
+            "--jobs" => {
+                let value = args.next().ok_or("Missing value after `--jobs`")?;
+                match value.parse() {
+                    Ok(value) if value > 0 => jobs = Some(value),
+                    _ => return Err(format!("Invalid number of jobs `{}`", value)),
+                }
+            }
+            _ => return Err(format!("Unknown argument `{}`", arg)),
+        }
+    }
+    if input.is_some() && days.len() > 1 {
+        return Err("`--input` can only be used with a single day".to_string());
+    }
+    if answers.is_some() && days.len() > 1 {
+        return Err("`--answers` can only be used with a single day".to_string());
+    }
+    if check && input.is_some() && answers.is_none() {
+        return Err("`--check` needs `--answers <path>` to check another puzzle input".to_string());
+    }
+    Ok(Args {
+        days,
+        input,
+        check,
+        answers,
+        threshold,
+        jobs,
+    })
+}
+
+/// Get the days to solve from `all`, a single day, or an inclusive range of days like `5..12`
+fn parse_days(arg: &str) -> Result<Vec<u8>, String> {
+    if arg == "all" {
+        return Ok(get_solvers().iter().map(|solver| solver.day()).collect());
+    }
+    let parse_day = |day: &str| match day.trim().parse() {
+        Ok(day) if get_solver(day).is_some() => Ok(day),
+        _ => Err(format!("Invalid day `{}`", day)),
+    };
+    match arg.split_once("..") {
+        Some((start, end)) => {
+            let (start, end) = (parse_day(start)?, parse_day(end.trim_start_matches('='))?);
+            if start > end {
+                return Err(format!("Invalid range of days `{}`", arg));
+            }
+            Ok((start..=end).collect())
+        }
+        None => Ok(vec![parse_day(arg)?]),
+    }
+}
+
+/// The arguments following `new-day`
+struct NewDayArgs {
+    day: u8,
+    root: PathBuf,
+    year: i32,
+}
+
+/// Create the module of a new day, from the arguments following `new-day`
+fn new_day(args: &[String]) {
+    let NewDayArgs { day, root, year } =
+        parse_new_day_args(args).unwrap_or_else(|message| invalid_input(&message));
+    match scaffold::create_day(&root, day, year) {
+        Ok(()) => {
+            let day_dir = root.join(format!("src/day_{:02}", day));
+            println!("Created day {} in `{}`:", day, day_dir.display());
+            for file in ["mod.rs", "input.txt", "README.md"] {
+                println!("\t- {}", day_dir.join(file).display());
+            }
+        }
+        Err(error) => {
+            eprintln!("error: cannot create day {}: {}", day, error);
+            process::exit(1)
+        }
+    }
+}
+
+fn parse_new_day_args(args: &[String]) -> Result<NewDayArgs, String> {
+    let mut args = args.iter();
+    let value = args.next().ok_or("Missing day after `new-day`")?;
+    let day = match value.parse() {
+        Ok(day) if (1..=25).contains(&day) => day,
+        _ => return Err(format!("Invalid day `{}`", value)),
     };
-    println!("# Processing Day {} :", day);
-    fn_day()
+    let mut root = PathBuf::from(".");
+    let mut year = Local::now().year();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--root" => {
+                root = PathBuf::from(args.next().ok_or("Missing path after `--root`")?);
+            }
+            "--year" => {
+                let value = args.next().ok_or("Missing value after `--year`")?;
+                year = value
+                    .parse()
+                    .map_err(|_| format!("Invalid year `{}`", value))?;
+            }
+            _ => return Err(format!("Unknown argument `{}`", arg)),
+        }
+    }
+    Ok(NewDayArgs { day, root, year })
+}
+
+/// Solve a day with the input read from `source`, and read the expected answers from `answers` if
+/// they are checked
+fn solve_day(day: u8, source: &InputSource, answers: Option<&str>) -> DayReport {
+    let result = read_input(source)
+        .map_err(|error| format!("error: cannot read input `{}`: {}", source, error))
+        .and_then(|input| {
+            let solver = get_solver(day).expect("Unknown day");
+            solver
+                .solve_timed(&input)
+                .map_err(|error| error.render(&source.to_string(), &input))
+        })
+        .map_err(DayError::Input);
+    let expected = answers.map(ExpectedAnswers::read);
+    DayReport {
+        day,
+        result,
+        expected,
+    }
+}
+
+/// Solve the days on a thread pool of `jobs` threads, and print their answers in order as soon as
+/// they are available
+fn solve_days(days: &[u8], check: bool, jobs: Option<usize>) -> Vec<DayReport> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.unwrap_or(0))
+        .build()
+        .expect("Cannot build the thread pool");
+    let (sender, receiver) = mpsc::channel();
+    for (idx, day) in days.iter().copied().enumerate() {
+        let sender = sender.clone();
+        pool.spawn(move || {
+            let answers = Some(get_day_answers_path(day)).filter(|_| check);
+            let report = panic::catch_unwind(|| {
+                solve_day(day, &InputSource::Bundled(day), answers.as_deref())
+            })
+            .unwrap_or(DayReport {
+                day,
+                result: Err(DayError::Panic),
+                expected: None,
+            });
+            sender.send((idx, report)).ok();
+        });
+    }
+    drop(sender);
+
+    let mut pending: Vec<Option<DayReport>> = days.iter().map(|_| None).collect();
+    let mut reports = Vec::with_capacity(days.len());
+    for (idx, report) in receiver {
+        pending[idx] = Some(report);
+        while let Some(report) = pending.get_mut(reports.len()).and_then(Option::take) {
+            print_report(&report);
+            reports.push(report);
+        }
+    }
+    reports
+}
+
+fn print_report(report: &DayReport) {
+    println!("# Processing Day {} :", report.day);
+    match &report.result {
+        Ok((answers, _)) => {
+            let (check_one, check_two) = match report.get_checks() {
+                Some((part_one, part_two)) => (format_check(&part_one), format_check(&part_two)),
+                None => (String::new(), String::new()),
+            };
+            println!("\t- Solution 1 is : {}{}", answers.part_one, check_one);
+            println!("\t- Solution 2 is : {}{}", answers.part_two, check_two);
+        }
+        Err(DayError::Input(message)) => eprintln!("{}", message),
+        Err(DayError::Panic) => eprintln!("error: day {} panicked", report.day),
+    }
+    if let Some(Err(message)) = &report.expected {
+        eprintln!("{}", Colour::Yellow.paint(format!("warning: {}", message)));
+    }
+}
+
+fn format_check(check: &Check) -> String {
+    match check {
+        Check::Pass => format!("  {}", Colour::Green.paint("PASS")),
+        Check::Fail { expected } => format!(
+            "  {}",
+            Colour::Red.paint(format!("FAIL, expected {}", expected))
+        ),
+        Check::Unchecked => format!("  {}", Colour::Yellow.paint("UNCHECKED")),
+    }
+}
+
+/// Print how many answers are correct, wrong, or not checked, and how many days have an input
+/// which cannot be read or parsed
+fn print_checks_summary(reports: &[DayReport]) {
+    let (mut passed, mut failed, mut unchecked, mut input_errors) = (0, 0, 0, 0);
+    for report in reports {
+        match (report.get_checks(), &report.result) {
+            (Some((part_one, part_two)), _) => {
+                for check in [part_one, part_two] {
+                    match check {
+                        Check::Pass => passed += 1,
+                        Check::Fail { .. } => failed += 1,
+                        Check::Unchecked => unchecked += 1,
+                    }
+                }
+            }
+            (None, Err(DayError::Input(_))) => input_errors += 1,
+            (None, _) => failed += 2,
+        }
+    }
+    println!();
+    print!(
+        "{}, {}, {}",
+        Colour::Green.paint(format!("{} passed", passed)),
+        Colour::Red.paint(format!("{} failed", failed)),
+        Colour::Yellow.paint(format!("{} unchecked", unchecked))
+    );
+    if input_errors > 0 {
+        let plural = if input_errors > 1 { "s" } else { "" };
+        let message = format!("{} input error{}", input_errors, plural);
+        print!(", {}", Colour::Red.paint(message));
+    }
+    println!();
+}
+
+/// Print the time spent on each day, flagging the days slower than `threshold`
+fn print_timings(reports: &[DayReport], threshold: Duration, elapsed: Duration) {
+    println!();
+    println!(
+        "{:>6} {:>12} {:>12} {:>12} {:>12}",
+        "Day", "Parse", "Part 1", "Part 2", "Total"
+    );
+    for report in reports {
+        let day = report.day;
+        match &report.result {
+            Ok((_, timing)) => println!(
+                "{:>6} {:>12} {:>12} {:>12} {:>12}{}",
+                day,
+                format_duration(timing.parse),
+                format_duration(timing.part_one),
+                format_duration(timing.part_two),
+                format_duration(timing.total()),
+                if timing.total() > threshold {
+                    "  <- slow"
+                } else {
+                    ""
+                }
+            ),
+            Err(DayError::Input(_)) => println!("{:>6} {:>12}", day, "input error"),
+            Err(DayError::Panic) => println!("{:>6} {:>12}", day, "panicked"),
+        }
+    }
+    println!("{:>6} {:>51}", "All", format_duration(elapsed));
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{:.3} ms", duration.as_secs_f64() * 1000.)
+    } else {
+        format!("{:.3} s", duration.as_secs_f64())
+    }
 }
 
-fn invalid_input() {
-    panic!("You must enter a number between 1 and 25.")
+fn invalid_input(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    process::exit(2)
 }
@@ -0,0 +1,271 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+lazy_static! {
+    static ref RE_DAY_MODULE: Regex = Regex::new(r"day_(\d{2})\b").unwrap();
+}
+
+/// The maximum width of a line, as formatted by rustfmt
+const MAX_WIDTH: usize = 100;
+
+/// Create the module of a new day in `src/day_NN/` from `src/day_template/`, with an empty input
+/// and a README linking to the puzzle of the given year. The day is then declared in `src/lib.rs`,
+/// registered in `src/solution.rs`, and benchmarked in `benches/days.rs`.
+pub fn create_day(root: &Path, day: u8, year: i32) -> io::Result<()> {
+    let module = format!("day_{:02}", day);
+    let day_dir = root.join("src").join(&module);
+    if day_dir.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("`{}` already exists", day_dir.display()),
+        ));
+    }
+
+    // Prepare every file first, so nothing is written if one of them cannot be updated
+    let template = fs::read_to_string(root.join("src/day_template/mod.rs"))?;
+    let lib_path = root.join("src/lib.rs");
+    let lib = add_module_declaration(&fs::read_to_string(&lib_path)?, day)
+        .ok_or_else(|| cannot_update(&lib_path))?;
+    let solution_path = root.join("src/solution.rs");
+    let solution = register_solver(&fs::read_to_string(&solution_path)?, day)
+        .ok_or_else(|| cannot_update(&solution_path))?;
+    let bench_path = root.join("benches/days.rs");
+    let bench = if bench_path.exists() {
+        let bench = register_bench(&fs::read_to_string(&bench_path)?, day)
+            .ok_or_else(|| cannot_update(&bench_path))?;
+        Some(bench)
+    } else {
+        None
+    };
+
+    fs::create_dir_all(&day_dir)?;
+    fs::write(day_dir.join("mod.rs"), render_template(&template, day))?;
+    fs::write(day_dir.join("input.txt"), "")?;
+    fs::write(day_dir.join("README.md"), get_readme(day, year))?;
+    fs::write(lib_path, lib)?;
+    fs::write(solution_path, solution)?;
+    if let Some(bench) = bench {
+        fs::write(bench_path, bench)?;
+    }
+    Ok(())
+}
+
+fn cannot_update(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "cannot find where to add the new day in `{}`",
+            path.display()
+        ),
+    )
+}
+
+/// Turn the template's `Day00` into the module of the given day
+pub fn render_template(template: &str, day: u8) -> String {
+    template
+        .replace("Day00", &format!("Day{:02}", day))
+        .replace("const DAY: u8 = 0;", &format!("const DAY: u8 = {};", day))
+}
+
+fn get_readme(day: u8, year: i32) -> String {
+    let url = format!("https://adventofcode.com/{}/day/{}", year, day);
+    format!(
+        "# Day {}\n\n[{}]({})\n\n## Description\n\n### Part One\n\n### Part Two\n",
+        day, url, url
+    )
+}
+
+/// Declare the module of the day in `src/lib.rs`
+pub fn add_module_declaration(lib: &str, day: u8) -> Option<String> {
+    insert_day_line(
+        lib,
+        "pub mod day_",
+        &format!("pub mod day_{:02};", day),
+        day,
+    )
+}
+
+/// Import the module of the day in `src/solution.rs`, and add its solver to the registry
+pub fn register_solver(solution: &str, day: u8) -> Option<String> {
+    let solution = add_to_use_list(solution, "use crate::{", day)?;
+    let solver = format!(
+        "    &Registered::<day_{:02}::Day{:02}>(PhantomData),",
+        day, day
+    );
+    insert_day_line(&solution, "&Registered::<day_", &solver, day)
+}
+
+/// Import the module of the day in `benches/days.rs`, and add it to the benchmarks
+pub fn register_bench(bench: &str, day: u8) -> Option<String> {
+    let bench = add_to_use_list(bench, "use advent_of_code::{", day)?;
+    let target = format!("        bench_day::<day_{:02}::Day{:02}>,", day, day);
+    insert_day_line(&bench, "bench_day::<day_", &target, day)
+}
+
+/// Insert `new_line` among the lines starting with `prefix`, ordered by the day they refer to
+fn insert_day_line(source: &str, prefix: &str, new_line: &str, day: u8) -> Option<String> {
+    let mut lines: Vec<&str> = source.lines().collect();
+    let days: Vec<(usize, u8)> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_start().starts_with(prefix))
+        .filter_map(|(idx, line)| Some((idx, get_line_day(line)?)))
+        .collect();
+    let position = match days.iter().find(|(_, line_day)| *line_day > day) {
+        Some((idx, _)) => *idx,
+        None => days.last()?.0 + 1,
+    };
+    lines.insert(position, new_line);
+    Some(lines.join("\n") + "\n")
+}
+
+fn get_line_day(line: &str) -> Option<u8> {
+    RE_DAY_MODULE.captures(line)?[1].parse().ok()
+}
+
+/// Add the module of the day to the list of modules imported by `use_prefix`, like
+/// `use crate::{day_01, day_02};`, keeping it sorted and formatted like rustfmt does.
+fn add_to_use_list(source: &str, use_prefix: &str, day: u8) -> Option<String> {
+    let start = source.find(use_prefix)?;
+    let end = start + source[start..].find("};")? + 2;
+    let list_start = start + use_prefix.len();
+
+    let mut modules: Vec<String> = source[list_start..end - 2]
+        .split(',')
+        .map(|module| module.trim().to_string())
+        .filter(|module| !module.is_empty())
+        .collect();
+    modules.push(format!("day_{:02}", day));
+    modules.sort();
+    modules.dedup();
+
+    let single_line = format!("{}{}}};", use_prefix, modules.join(", "));
+    let use_statement = if single_line.len() <= MAX_WIDTH {
+        single_line
+    } else {
+        let mut lines = vec![];
+        let mut line = String::from("   ");
+        for module in modules {
+            if line.len() + module.len() + 2 > MAX_WIDTH {
+                lines.push(line);
+                line = String::from("   ");
+            }
+            line += &format!(" {},", module);
+        }
+        lines.push(line);
+        format!("{}\n{}\n}};", use_prefix, lines.join("\n"))
+    };
+    Some(format!(
+        "{}{}{}",
+        &source[..start],
+        use_statement,
+        &source[end..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let template =
+            "pub struct Day00;\n\nimpl Solution for Day00 {\n    const DAY: u8 = 0;\n}\n";
+        assert_eq!(
+            render_template(template, 7),
+            "pub struct Day07;\n\nimpl Solution for Day07 {\n    const DAY: u8 = 7;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_add_module_declaration() {
+        let lib = "pub mod day_01;\npub mod day_03;\npub mod solution;\n";
+        assert_eq!(
+            add_module_declaration(lib, 2).unwrap(),
+            "pub mod day_01;\npub mod day_02;\npub mod day_03;\npub mod solution;\n"
+        );
+        assert_eq!(
+            add_module_declaration(lib, 4).unwrap(),
+            "pub mod day_01;\npub mod day_03;\npub mod day_04;\npub mod solution;\n"
+        );
+        assert_eq!(add_module_declaration("pub mod solution;\n", 4), None);
+    }
+
+    #[test]
+    fn test_register_solver() {
+        let solution = "use crate::{day_01, day_03};
+
+static SOLVERS: &[&dyn Solver] = &[
+    &Registered::<day_01::Day01>(PhantomData),
+    &Registered::<day_03::Day03>(PhantomData),
+];
+";
+        assert_eq!(
+            register_solver(solution, 2).unwrap(),
+            "use crate::{day_01, day_02, day_03};
+
+static SOLVERS: &[&dyn Solver] = &[
+    &Registered::<day_01::Day01>(PhantomData),
+    &Registered::<day_02::Day02>(PhantomData),
+    &Registered::<day_03::Day03>(PhantomData),
+];
+"
+        );
+    }
+
+    #[test]
+    fn test_create_day() {
+        // A repository laid out like this one, with a single day
+        let root = std::env::temp_dir().join(format!("new_day_{}", std::process::id()));
+        fs::create_dir_all(root.join("src/day_template")).unwrap();
+        fs::copy(
+            "./src/day_template/mod.rs",
+            root.join("src/day_template/mod.rs"),
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/lib.rs"),
+            "pub mod day_01;\npub mod solution;\n",
+        )
+        .unwrap();
+        let solution = "use crate::{day_01};\n\nstatic SOLVERS: &[&dyn Solver] = &[\n    \
+                        &Registered::<day_01::Day01>(PhantomData),\n];\n";
+        fs::write(root.join("src/solution.rs"), solution).unwrap();
+
+        let created = create_day(&root, 2, 2023);
+        let error = create_day(&root, 2, 2023).map_err(|error| error.kind());
+        let module = fs::read_to_string(root.join("src/day_02/mod.rs"));
+        let readme = fs::read_to_string(root.join("src/day_02/README.md"));
+        let lib = fs::read_to_string(root.join("src/lib.rs"));
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(created.is_ok());
+        assert_eq!(error, Err(io::ErrorKind::AlreadyExists));
+        assert!(module.unwrap().contains("impl Solution for Day02"));
+        assert!(readme
+            .unwrap()
+            .contains("https://adventofcode.com/2023/day/2"));
+        assert_eq!(
+            lib.unwrap(),
+            "pub mod day_01;\npub mod day_02;\npub mod solution;\n"
+        );
+    }
+
+    #[test]
+    fn test_add_to_use_list_wraps_like_rustfmt() {
+        let source = fs::read_to_string("./src/solution.rs").unwrap();
+        let start = source.find("use crate::{").unwrap();
+        let end = start + source[start..].find("};").unwrap() + 2;
+        let use_statement = &source[start..end];
+
+        // Removing a day then adding it back gives the list formatted by rustfmt
+        let without_day = use_statement.replace(" day_14,", "");
+        assert_eq!(
+            add_to_use_list(&without_day, "use crate::{", 14).unwrap(),
+            use_statement
+        );
+    }
+}
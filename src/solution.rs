@@ -0,0 +1,208 @@
+use std::fmt;
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+use crate::utils::parse_error::ParseError;
+use crate::{
+    day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10, day_11, day_12,
+    day_13, day_14, day_15, day_16, day_17, day_18, day_19, day_20, day_21, day_22, day_23, day_24,
+    day_25,
+};
+
+/// The solver of a single day's puzzle.
+///
+/// The input is parsed once, and shared by both parts.
+pub trait Solution {
+    const DAY: u8;
+    type Input;
+    type PartOne: Display;
+    type PartTwo: Display;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part_one(input: &Self::Input) -> Self::PartOne;
+    fn part_two(input: &Self::Input) -> Self::PartTwo;
+}
+
+/// The answer of a part which has no puzzle to solve, like the last day's part two.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct NoSolution;
+
+impl Display for NoSolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "-")
+    }
+}
+
+/// The answers of both parts, already formatted.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Answers {
+    pub part_one: String,
+    pub part_two: String,
+}
+
+/// The wall-clock time spent on each step of a solver
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Timings {
+    pub parse: Duration,
+    pub part_one: Duration,
+    pub part_two: Duration,
+}
+
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.parse + self.part_one + self.part_two
+    }
+}
+
+/// An object-safe version of [`Solution`], so every day can be stored in the same registry.
+pub trait Solver: Sync {
+    fn day(&self) -> u8;
+
+    /// Solve both parts, and measure the time spent parsing the input and solving each part.
+    fn solve_timed(&self, input: &str) -> Result<(Answers, Timings), ParseError>;
+
+    fn solve(&self, input: &str) -> Result<Answers, ParseError> {
+        self.solve_timed(input).map(|(answers, _)| answers)
+    }
+}
+
+struct Registered<S>(PhantomData<fn() -> S>);
+
+impl<S: Solution> Solver for Registered<S> {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn solve_timed(&self, input: &str) -> Result<(Answers, Timings), ParseError> {
+        let start = Instant::now();
+        let input = S::parse(input)?;
+        let parse = start.elapsed();
+
+        let start = Instant::now();
+        let part_one = S::part_one(&input).to_string();
+        let part_one_time = start.elapsed();
+
+        let start = Instant::now();
+        let part_two = S::part_two(&input).to_string();
+        let part_two_time = start.elapsed();
+
+        let answers = Answers { part_one, part_two };
+        let timings = Timings {
+            parse,
+            part_one: part_one_time,
+            part_two: part_two_time,
+        };
+        Ok((answers, timings))
+    }
+}
+
+static SOLVERS: &[&dyn Solver] = &[
+    &Registered::<day_01::Day01>(PhantomData),
+    &Registered::<day_02::Day02>(PhantomData),
+    &Registered::<day_03::Day03>(PhantomData),
+    &Registered::<day_04::Day04>(PhantomData),
+    &Registered::<day_05::Day05>(PhantomData),
+    &Registered::<day_06::Day06>(PhantomData),
+    &Registered::<day_07::Day07>(PhantomData),
+    &Registered::<day_08::Day08>(PhantomData),
+    &Registered::<day_09::Day09>(PhantomData),
+    &Registered::<day_10::Day10>(PhantomData),
+    &Registered::<day_11::Day11>(PhantomData),
+    &Registered::<day_12::Day12>(PhantomData),
+    &Registered::<day_13::Day13>(PhantomData),
+    &Registered::<day_14::Day14>(PhantomData),
+    &Registered::<day_15::Day15>(PhantomData),
+    &Registered::<day_16::Day16>(PhantomData),
+    &Registered::<day_17::Day17>(PhantomData),
+    &Registered::<day_18::Day18>(PhantomData),
+    &Registered::<day_19::Day19>(PhantomData),
+    &Registered::<day_20::Day20>(PhantomData),
+    &Registered::<day_21::Day21>(PhantomData),
+    &Registered::<day_22::Day22>(PhantomData),
+    &Registered::<day_23::Day23>(PhantomData),
+    &Registered::<day_24::Day24>(PhantomData),
+    &Registered::<day_25::Day25>(PhantomData),
+];
+
+/// Returns every registered solver, ordered by day
+pub fn get_solvers() -> &'static [&'static dyn Solver] {
+    SOLVERS
+}
+
+pub fn get_solver(day: u8) -> Option<&'static dyn Solver> {
+    SOLVERS.iter().find(|s| s.day() == day).copied()
+}
+
+/// Solve both parts of a day with the given puzzle input.
+/// Returns `None` if no solver is registered for this day.
+pub fn solve(day: u8, input: &str) -> Option<Result<Answers, ParseError>> {
+    get_solver(day).map(|solver| solver.solve(input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_is_ordered_by_day() {
+        let days: Vec<u8> = get_solvers().iter().map(|s| s.day()).collect();
+        assert_eq!(days, (1..=25).collect::<Vec<u8>>());
+        assert!(get_solver(0).is_none());
+        assert_eq!(get_solver(7).map(|s| s.day()), Some(7));
+    }
+
+    #[test]
+    fn test_solve_from_string() {
+        let answers = solve(1, "1000\n2000\n\n3000\n\n500").unwrap().unwrap();
+        assert_eq!(answers.part_one, "3000");
+        assert_eq!(answers.part_two, "6500");
+        assert!(solve(26, "").is_none());
+    }
+
+    /// A solution whose parts must not be solved, since its input never parses
+    struct Unparsable;
+
+    impl Solution for Unparsable {
+        const DAY: u8 = 0;
+        type Input = ();
+        type PartOne = NoSolution;
+        type PartTwo = NoSolution;
+
+        fn parse(input: &str) -> Result<Self::Input, ParseError> {
+            Err(ParseError::new(input, input, "nothing"))
+        }
+
+        fn part_one(_: &Self::Input) -> Self::PartOne {
+            panic!("Part one solved without an input")
+        }
+
+        fn part_two(_: &Self::Input) -> Self::PartTwo {
+            panic!("Part two solved without an input")
+        }
+    }
+
+    #[test]
+    fn test_solve_timed() {
+        let input = "1000\n2000\n\n3000\n\n500";
+        let (answers, _) = get_solver(1).unwrap().solve_timed(input).unwrap();
+        let parsed = day_01::Day01::parse(input).unwrap();
+        assert_eq!(
+            answers,
+            Answers {
+                part_one: day_01::Day01::part_one(&parsed).to_string(),
+                part_two: day_01::Day01::part_two(&parsed).to_string(),
+            }
+        );
+
+        let solver = Registered::<Unparsable>(PhantomData);
+        assert_eq!(solver.solve_timed("abc").unwrap_err().expected, "nothing");
+    }
+
+    #[test]
+    fn test_solve_with_invalid_input() {
+        let error = solve(1, "1000\n2000\n\n3000\nabc").unwrap().unwrap_err();
+        assert_eq!((error.line, error.column), (5, 1));
+        assert_eq!(error.text, "abc");
+    }
+}
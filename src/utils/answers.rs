@@ -0,0 +1,131 @@
+use crate::solution::Answers;
+use std::fs;
+use toml::Value;
+
+/// The expected answers of a puzzle input, read from a TOML file like:
+///
+/// ``` toml
+/// part_one = 24000
+/// part_two = "45000"
+/// ```
+///
+/// A missing part is not checked.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ExpectedAnswers {
+    pub part_one: Option<String>,
+    pub part_two: Option<String>,
+}
+
+/// The result of checking an answer against the expected one
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Check {
+    Pass,
+    Fail { expected: String },
+    Unchecked,
+}
+
+impl ExpectedAnswers {
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        let table = content
+            .parse::<Value>()
+            .map_err(|error| format!("invalid answers file: {}", error))?;
+        let get_part = |key: &str| match table.get(key) {
+            None => Ok(None),
+            Some(Value::String(value)) => Ok(Some(value.clone())),
+            Some(Value::Integer(value)) => Ok(Some(value.to_string())),
+            Some(value) => Err(format!(
+                "invalid answers file: `{}` is not a string or an integer: {}",
+                key, value
+            )),
+        };
+        Ok(ExpectedAnswers {
+            part_one: get_part("part_one")?,
+            part_two: get_part("part_two")?,
+        })
+    }
+
+    pub fn read(file_name: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(file_name)
+            .map_err(|error| format!("cannot read answers `{}`: {}", file_name, error))?;
+        Self::from_toml(&content)
+    }
+
+    /// Check both answers, returning the result of each part
+    pub fn check(&self, answers: &Answers) -> (Check, Check) {
+        (
+            check_answer(self.part_one.as_deref(), &answers.part_one),
+            check_answer(self.part_two.as_deref(), &answers.part_two),
+        )
+    }
+}
+
+/// Returns the path of the expected answers of the puzzle input bundled with the given day
+pub fn get_day_answers_path(day: u8) -> String {
+    format!("./src/day_{:02}/answers.toml", day)
+}
+
+/// Compare an answer to the expected one.
+/// Trailing whitespace is ignored, so drawings like the CRT of day 10 can be stored as is.
+fn check_answer(expected: Option<&str>, answer: &str) -> Check {
+    match expected {
+        None => Check::Unchecked,
+        Some(expected) if normalize(expected) == normalize(answer) => Check::Pass,
+        Some(expected) => Check::Fail {
+            expected: expected.to_string(),
+        },
+    }
+}
+
+fn normalize(answer: &str) -> String {
+    answer
+        .trim()
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml() {
+        let expected = ExpectedAnswers::from_toml("part_one = 24000\npart_two = \"CMZ\"").unwrap();
+        assert_eq!(expected.part_one.as_deref(), Some("24000"));
+        assert_eq!(expected.part_two.as_deref(), Some("CMZ"));
+
+        let expected = ExpectedAnswers::from_toml("part_one = \"2=-1=0\"").unwrap();
+        assert_eq!(expected.part_two, None);
+        assert!(ExpectedAnswers::from_toml("part_one = [1]").is_err());
+        assert!(ExpectedAnswers::from_toml("part_one = ").is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let expected = ExpectedAnswers {
+            part_one: Some("24000".to_string()),
+            part_two: None,
+        };
+        let answers = Answers {
+            part_one: "24000".to_string(),
+            part_two: "45000".to_string(),
+        };
+        assert_eq!(expected.check(&answers), (Check::Pass, Check::Unchecked));
+
+        let answers = Answers {
+            part_one: "24001".to_string(),
+            part_two: "45000".to_string(),
+        };
+        let expected_fail = Check::Fail {
+            expected: "24000".to_string(),
+        };
+        assert_eq!(expected.check(&answers).0, expected_fail);
+    }
+
+    #[test]
+    fn test_check_ignores_trailing_whitespace() {
+        assert_eq!(check_answer(Some("# #\n##"), "\n# #  \n##\n "), Check::Pass);
+        assert_ne!(check_answer(Some("# #\n##"), "#  #\n##"), Check::Pass);
+    }
+}
@@ -1,44 +1,300 @@
-#[derive(Debug, Default)]
-pub struct ArenaTree<T>
-where
-    T: PartialEq,
-{
-    arena: Vec<Node<T>>,
-}
-
-impl<T> ArenaTree<T>
-where
-    T: PartialEq,
-{
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::iter;
+
+/// A tree whose nodes are stored in a vector, and refer to each other by their index.
+///
+/// Removed nodes leave an empty slot, so the indices of the other nodes never change.
+#[derive(Debug)]
+pub struct ArenaTree<T> {
+    arena: Vec<Option<Node<T>>>,
+    len: usize,
+}
+
+impl<T> Default for ArenaTree<T> {
+    fn default() -> Self {
+        Self {
+            arena: vec![],
+            len: 0,
+        }
+    }
+}
+
+/// An error on an operation changing the shape of the tree
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TreeError {
+    UnknownNode(usize),
+    /// Moving a node under one of its descendants
+    Cycle {
+        node: usize,
+        new_parent: usize,
+    },
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeError::UnknownNode(idx) => write!(f, "no node at index {}", idx),
+            TreeError::Cycle { node, new_parent } => write!(
+                f,
+                "cannot move node {} under its descendant {}",
+                node, new_parent
+            ),
+        }
+    }
+}
+
+impl Error for TreeError {}
+
+impl<T> ArenaTree<T> {
+    /// Insert a node as the last child of `parent`, or as a new root.
+    /// Panics if `parent` is not in the tree.
     pub fn insert_node(&mut self, val: T, parent: Option<usize>) -> usize {
         let idx = self.arena.len();
-        self.arena.push(Node::new(idx, val, parent));
         if let Some(parent_idx) = parent {
-            self.arena.get_mut(parent_idx).unwrap().children.push(idx);
+            self.get_node_mut(parent_idx).children.push(idx);
         }
+        self.arena.push(Some(Node::new(idx, val, parent)));
+        self.len += 1;
         idx
     }
 
+    pub fn get(&self, idx: usize) -> Option<&Node<T>> {
+        self.arena.get(idx).and_then(Option::as_ref)
+    }
+
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut Node<T>> {
+        self.arena.get_mut(idx).and_then(Option::as_mut)
+    }
+
     pub fn get_unwrapped(&self, idx: usize) -> &Node<T> {
-        self.arena.get(idx).unwrap()
+        self.get(idx)
+            .unwrap_or_else(|| panic!("No node at index {}", idx))
+    }
+
+    fn get_node_mut(&mut self, idx: usize) -> &mut Node<T> {
+        self.get_mut(idx)
+            .unwrap_or_else(|| panic!("No node at index {}", idx))
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        self.get(idx).is_some()
+    }
+
+    /// The number of nodes in the tree
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over the nodes without a parent
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.arena
+            .iter()
+            .flatten()
+            .filter(|node| node.parent.is_none())
+            .map(|node| node.idx)
+    }
+
+    /// Returns the index of the first child of `idx` whose value matches `predicate`
+    pub fn find_child<P>(&self, idx: usize, mut predicate: P) -> Option<usize>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.get(idx)?
+            .children
+            .iter()
+            .copied()
+            .find(|child| predicate(&self.get_unwrapped(*child).val))
+    }
+
+    /// Iterate over the ancestors of a node, from its parent up to its root
+    pub fn ancestors(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        iter::successors(self.get(idx).and_then(|node| node.parent), move |idx| {
+            self.get_unwrapped(*idx).parent
+        })
+    }
+
+    /// Returns the indices from the node up to its root, both included
+    pub fn path_to_root(&self, idx: usize) -> Vec<usize> {
+        if !self.contains(idx) {
+            return vec![];
+        }
+        iter::once(idx).chain(self.ancestors(idx)).collect()
+    }
+
+    /// The number of ancestors of a node, so a root has a depth of 0
+    pub fn depth(&self, idx: usize) -> usize {
+        self.ancestors(idx).count()
+    }
+
+    /// Iterate over the subtree of `idx` depth-first, each node coming before its children
+    pub fn pre_order(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut stack: Vec<usize> = self.get(idx).map(|node| node.idx).into_iter().collect();
+        iter::from_fn(move || {
+            let idx = stack.pop()?;
+            stack.extend(self.get_unwrapped(idx).children.iter().rev());
+            Some(idx)
+        })
+    }
+
+    /// Iterate over the subtree of `idx` depth-first, each node coming after its children
+    pub fn post_order(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        // Each node is stacked with whether its children were already stacked
+        let mut stack: Vec<(usize, bool)> = self
+            .get(idx)
+            .map(|node| (node.idx, false))
+            .into_iter()
+            .collect();
+        iter::from_fn(move || loop {
+            let (idx, is_expanded) = stack.pop()?;
+            if is_expanded {
+                return Some(idx);
+            }
+            stack.push((idx, true));
+            let children = &self.get_unwrapped(idx).children;
+            stack.extend(children.iter().rev().map(|child| (*child, false)));
+        })
+    }
+
+    /// Iterate over the subtree of `idx` level by level
+    pub fn breadth_first(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut queue: VecDeque<usize> = self.get(idx).map(|node| node.idx).into_iter().collect();
+        iter::from_fn(move || {
+            let idx = queue.pop_front()?;
+            queue.extend(self.get_unwrapped(idx).children.iter());
+            Some(idx)
+        })
+    }
+
+    /// Reduce the subtree of `idx` bottom-up: `f` gets the value of each node, and the results
+    /// of its children.
+    /// Panics if `idx` is not in the tree.
+    pub fn fold<B, F>(&self, idx: usize, mut f: F) -> B
+    where
+        F: FnMut(&T, Vec<B>) -> B,
+    {
+        let mut results: Vec<Option<B>> =
+            iter::repeat_with(|| None).take(self.arena.len()).collect();
+        for node_idx in self.post_order(idx) {
+            let node = self.get_unwrapped(node_idx);
+            let children = node
+                .children
+                .iter()
+                .map(|child| results[*child].take().unwrap())
+                .collect();
+            results[node_idx] = Some(f(&node.val, children));
+        }
+        results[idx]
+            .take()
+            .unwrap_or_else(|| panic!("No node at index {}", idx))
+    }
+
+    /// Like [`ArenaTree::fold`], but returns the result of every node of the subtree, in
+    /// post-order
+    pub fn aggregate<B, F>(&self, idx: usize, mut f: F) -> Vec<(usize, B)>
+    where
+        B: Clone,
+        F: FnMut(&T, &[B]) -> B,
+    {
+        let mut results: Vec<Option<B>> =
+            iter::repeat_with(|| None).take(self.arena.len()).collect();
+        let mut aggregates = vec![];
+        for node_idx in self.post_order(idx) {
+            let node = self.get_unwrapped(node_idx);
+            let children: Vec<B> = node
+                .children
+                .iter()
+                .map(|child| results[*child].clone().unwrap())
+                .collect();
+            let result = f(&node.val, &children);
+            results[node_idx] = Some(result.clone());
+            aggregates.push((node_idx, result));
+        }
+        aggregates
+    }
+
+    /// Remove a node, and give its children to its parent in its place. The children of a
+    /// removed root become roots.
+    pub fn remove(&mut self, idx: usize) -> Result<T, TreeError> {
+        let node = self
+            .arena
+            .get_mut(idx)
+            .and_then(Option::take)
+            .ok_or(TreeError::UnknownNode(idx))?;
+        self.len -= 1;
+        for child in &node.children {
+            self.get_node_mut(*child).parent = node.parent;
+        }
+        if let Some(parent_idx) = node.parent {
+            let siblings = &mut self.get_node_mut(parent_idx).children;
+            let position = siblings.iter().position(|child| *child == idx).unwrap();
+            siblings.splice(position..=position, node.children.iter().copied());
+        }
+        Ok(node.val)
+    }
+
+    /// Remove a node and all its descendants, and returns their values in pre-order
+    pub fn remove_subtree(&mut self, idx: usize) -> Result<Vec<T>, TreeError> {
+        if !self.contains(idx) {
+            return Err(TreeError::UnknownNode(idx));
+        }
+        self.detach(idx);
+        let subtree: Vec<usize> = self.pre_order(idx).collect();
+        self.len -= subtree.len();
+        Ok(subtree
+            .into_iter()
+            .map(|node_idx| self.arena[node_idx].take().unwrap().val)
+            .collect())
+    }
+
+    /// Move a node with all its descendants, as the last child of `new_parent` or as a new root
+    pub fn reparent(&mut self, idx: usize, new_parent: Option<usize>) -> Result<(), TreeError> {
+        if !self.contains(idx) {
+            return Err(TreeError::UnknownNode(idx));
+        }
+        if let Some(parent_idx) = new_parent {
+            if !self.contains(parent_idx) {
+                return Err(TreeError::UnknownNode(parent_idx));
+            }
+            if self.path_to_root(parent_idx).contains(&idx) {
+                return Err(TreeError::Cycle {
+                    node: idx,
+                    new_parent: parent_idx,
+                });
+            }
+            self.detach(idx);
+            self.get_node_mut(parent_idx).children.push(idx);
+        } else {
+            self.detach(idx);
+        }
+        self.get_node_mut(idx).parent = new_parent;
+        Ok(())
+    }
+
+    /// Remove a node from the children of its parent
+    fn detach(&mut self, idx: usize) {
+        if let Some(parent_idx) = self.get_unwrapped(idx).parent {
+            self.get_node_mut(parent_idx)
+                .children
+                .retain(|child| *child != idx);
+        }
     }
 }
 
 #[derive(Debug)]
-pub struct Node<T>
-where
-    T: PartialEq,
-{
-    pub idx: usize,
-    pub val: T,
-    pub parent: Option<usize>,
-    pub children: Vec<usize>,
-}
-
-impl<T> Node<T>
-where
-    T: PartialEq,
-{
+pub struct Node<T> {
+    idx: usize,
+    val: T,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+impl<T> Node<T> {
     fn new(idx: usize, val: T, parent: Option<usize>) -> Self {
         Self {
             idx,
@@ -47,4 +303,131 @@ where
             children: vec![],
         }
     }
+
+    pub fn idx(&self) -> usize {
+        self.idx
+    }
+
+    pub fn value(&self) -> &T {
+        &self.val
+    }
+
+    pub fn value_mut(&mut self) -> &mut T {
+        &mut self.val
+    }
+
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build the tree:
+    /// ```text
+    /// a
+    /// ├── b
+    /// │   ├── d
+    /// │   └── e
+    /// └── c
+    ///     └── f
+    /// ```
+    fn get_tree() -> ArenaTree<char> {
+        let mut tree = ArenaTree::default();
+        let a = tree.insert_node('a', None);
+        let b = tree.insert_node('b', Some(a));
+        let c = tree.insert_node('c', Some(a));
+        tree.insert_node('d', Some(b));
+        tree.insert_node('e', Some(b));
+        tree.insert_node('f', Some(c));
+        tree
+    }
+
+    fn get_values(tree: &ArenaTree<char>, indices: impl Iterator<Item = usize>) -> String {
+        indices
+            .map(|idx| *tree.get_unwrapped(idx).value())
+            .collect()
+    }
+
+    #[test]
+    fn test_traversals() {
+        let tree = get_tree();
+        assert_eq!(get_values(&tree, tree.pre_order(0)), "abdecf");
+        assert_eq!(get_values(&tree, tree.post_order(0)), "debfca");
+        assert_eq!(get_values(&tree, tree.breadth_first(0)), "abcdef");
+        assert_eq!(get_values(&tree, tree.pre_order(2)), "cf");
+        assert_eq!(tree.pre_order(42).count(), 0);
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let tree = get_tree();
+        assert_eq!(get_values(&tree, tree.ancestors(4)), "ba");
+        assert_eq!(tree.path_to_root(4), vec![4, 1, 0]);
+        assert_eq!(tree.depth(4), 2);
+        assert_eq!(tree.depth(0), 0);
+        assert_eq!(tree.find_child(0, |val| *val == 'c'), Some(2));
+        assert_eq!(tree.find_child(0, |val| *val == 'd'), None);
+        assert_eq!(tree.roots().collect::<Vec<_>>(), vec![0]);
+    }
+
+    #[test]
+    fn test_fold() {
+        let tree = get_tree();
+        let count = |_: &char, children: Vec<usize>| 1 + children.iter().sum::<usize>();
+        assert_eq!(tree.fold(0, count), 6);
+        assert_eq!(tree.fold(1, count), 3);
+
+        let heights = tree.aggregate(0, |_, children: &[usize]| {
+            children.iter().max().map_or(0, |height| height + 1)
+        });
+        assert_eq!(heights.last(), Some(&(0, 2)));
+        assert!(heights.contains(&(2, 1)));
+        assert!(heights.contains(&(5, 0)));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut tree = get_tree();
+        assert_eq!(tree.remove(1), Ok('b'));
+        assert_eq!(tree.len(), 5);
+        assert_eq!(get_values(&tree, tree.pre_order(0)), "adecf");
+        assert_eq!(tree.get_unwrapped(3).parent(), Some(0));
+        assert_eq!(tree.remove(1), Err(TreeError::UnknownNode(1)));
+
+        assert_eq!(tree.remove_subtree(2), Ok(vec!['c', 'f']));
+        assert_eq!(get_values(&tree, tree.pre_order(0)), "ade");
+        assert_eq!(tree.len(), 3);
+        assert!(!tree.contains(5));
+
+        assert_eq!(tree.remove(0), Ok('a'));
+        assert_eq!(tree.roots().collect::<Vec<_>>(), vec![3, 4]);
+    }
+
+    #[test]
+    fn test_reparent() {
+        let mut tree = get_tree();
+        assert_eq!(tree.reparent(1, Some(5)), Ok(()));
+        assert_eq!(get_values(&tree, tree.pre_order(0)), "acfbde");
+        assert_eq!(tree.path_to_root(3), vec![3, 1, 5, 2, 0]);
+
+        assert_eq!(
+            tree.reparent(2, Some(4)),
+            Err(TreeError::Cycle {
+                node: 2,
+                new_parent: 4
+            })
+        );
+        assert_eq!(tree.reparent(2, Some(42)), Err(TreeError::UnknownNode(42)));
+
+        assert_eq!(tree.reparent(1, None), Ok(()));
+        assert_eq!(tree.roots().collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(get_values(&tree, tree.pre_order(0)), "acf");
+    }
 }
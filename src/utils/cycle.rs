@@ -0,0 +1,349 @@
+use ahash::{AHashMap, RandomState};
+use std::hash::Hash;
+use std::ops::Sub;
+
+/// A cycle in a sequence of states: after `prefix_length` states, the same `period` states
+/// repeat forever, shifted by `delta` at each repetition
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub struct Cycle<D> {
+    pub prefix_length: usize,
+    pub period: usize,
+    pub delta: D,
+}
+
+impl<D> Cycle<D> {
+    /// Returns the index of the state matching state `index` among the first
+    /// `prefix_length + period` ones, and the number of periods between them
+    pub fn reduce(&self, index: usize) -> (usize, usize) {
+        if index < self.prefix_length {
+            (index, 0)
+        } else {
+            let cycles = (index - self.prefix_length) / self.period;
+            (index - cycles * self.period, cycles)
+        }
+    }
+}
+
+/// Offset-aware equality for states which are simply equal or not
+pub fn equal<T: Eq>(earlier: &T, later: &T) -> Option<()> {
+    if earlier == later {
+        Some(())
+    } else {
+        None
+    }
+}
+
+/// Finds the cycle of the sequence `start, next(start), next(next(start))...` with Floyd's
+/// tortoise and hare algorithm.
+/// `offset` returns how much a state is shifted from an earlier one, if they are equal apart from
+/// that shift. Shifted states must have equally shifted next states.
+/// Never returns if the sequence does not cycle.
+pub fn floyd<T, D>(
+    start: T,
+    mut next: impl FnMut(&T) -> T,
+    offset: impl Fn(&T, &T) -> Option<D>,
+) -> Cycle<D>
+where
+    T: Clone,
+{
+    // The hare is twice as far as the tortoise, so they match once the tortoise is in the cycle
+    // at a multiple of the period
+    let mut tortoise = next(&start);
+    let mut hare = next(&tortoise);
+    while offset(&tortoise, &hare).is_none() {
+        tortoise = next(&tortoise);
+        let hare_next = next(&hare);
+        hare = next(&hare_next);
+    }
+
+    // Keeping the same gap, the first match is at the start of the cycle
+    let mut prefix_length = 0;
+    tortoise = start;
+    while offset(&tortoise, &hare).is_none() {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        prefix_length += 1;
+    }
+
+    let mut period = 1;
+    hare = next(&tortoise);
+    loop {
+        if let Some(delta) = offset(&tortoise, &hare) {
+            return Cycle {
+                prefix_length,
+                period,
+                delta,
+            };
+        }
+        hare = next(&hare);
+        period += 1;
+    }
+}
+
+/// Finds the cycle of the sequence `start, next(start), next(next(start))...` with Brent's
+/// algorithm, which calls `next` less often than `floyd`.
+/// `offset` returns how much a state is shifted from an earlier one, if they are equal apart from
+/// that shift. Shifted states must have equally shifted next states.
+/// Never returns if the sequence does not cycle.
+pub fn brent<T, D>(
+    start: T,
+    mut next: impl FnMut(&T) -> T,
+    offset: impl Fn(&T, &T) -> Option<D>,
+) -> Cycle<D>
+where
+    T: Clone,
+{
+    // The tortoise teleports to the hare each time the hare has made a power of two steps
+    let (mut power, mut period) = (1, 1);
+    let mut tortoise = start.clone();
+    let mut hare = next(&start);
+    while offset(&tortoise, &hare).is_none() {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = next(&hare);
+        period += 1;
+    }
+
+    // With a gap of one period, the first match is at the start of the cycle
+    let mut prefix_length = 0;
+    tortoise = start.clone();
+    hare = start;
+    for _ in 0..period {
+        hare = next(&hare);
+    }
+    loop {
+        if let Some(delta) = offset(&tortoise, &hare) {
+            return Cycle {
+                prefix_length,
+                period,
+                delta,
+            };
+        }
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        prefix_length += 1;
+    }
+}
+
+/// Finds cycles in a sequence of states fed one at a time, each state being split in a hashable
+/// key and an offset, such as a height. Two states are equal apart from their offsets if their
+/// keys are equal, and a cycle is found once the states of a whole period have equal keys and
+/// offsets shifted by the same delta.
+#[derive(Debug, Clone)]
+pub struct CycleDetector<K, O> {
+    /// The distinct keys in order of appearance, a key id being its index
+    keys: Vec<K>,
+    /// The ids of the keys with each hash
+    ids: AHashMap<u64, Vec<usize>>,
+    hash_builder: RandomState,
+    /// The key id and the offset of each state
+    history: Vec<(usize, O)>,
+    /// The indices of the states with each key id, in order
+    indices: Vec<Vec<usize>>,
+    repetitions: usize,
+}
+
+impl<K, O> Default for CycleDetector<K, O> {
+    fn default() -> Self {
+        Self {
+            keys: vec![],
+            ids: AHashMap::new(),
+            hash_builder: RandomState::new(),
+            history: vec![],
+            indices: vec![],
+            repetitions: 1,
+        }
+    }
+}
+
+impl<K, O> CycleDetector<K, O>
+where
+    K: Hash + Eq,
+    O: Copy + Eq + Sub<Output = O>,
+{
+    /// A cycle is found as soon as a key is repeated, which requires the key to describe
+    /// everything determining the next states
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A cycle is only found once the states of `repetitions - 1` whole periods are equal to the
+    /// states one period before them. This helps when keys only describe part of the states.
+    /// Panics if `repetitions` is 0.
+    pub fn with_repetitions(mut self, repetitions: usize) -> Self {
+        assert!(repetitions > 0, "A cycle must be seen at least once");
+        self.repetitions = repetitions;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.history.is_empty()
+    }
+
+    /// Returns the key of the state at `index`
+    pub fn key(&self, index: usize) -> &K {
+        &self.keys[self.history[index].0]
+    }
+
+    /// Returns the offset of the state at `index`
+    pub fn offset(&self, index: usize) -> O {
+        self.history[index].1
+    }
+
+    /// Returns the distinct keys in order of appearance
+    pub fn into_keys(self) -> Vec<K> {
+        self.keys
+    }
+
+    /// Adds the next state of the sequence, and returns the shortest cycle it completes, if any.
+    /// States can still be added once a cycle is found.
+    pub fn push(&mut self, key: K, offset: O) -> Option<Cycle<O>> {
+        let index = self.history.len();
+        // Keys are only stored once, so they can be moved in
+        let keys = &mut self.keys;
+        let ids = self
+            .ids
+            .entry(self.hash_builder.hash_one(&key))
+            .or_default();
+        let id = match ids.iter().find(|&&id| keys[id] == key) {
+            Some(&id) => id,
+            None => {
+                ids.push(keys.len());
+                keys.push(key);
+                self.indices.push(vec![]);
+                keys.len() - 1
+            }
+        };
+        self.history.push((id, offset));
+        let cycle = self.indices[id]
+            .iter()
+            .rev()
+            .find_map(|&earlier| self.get_cycle(earlier, index));
+        self.indices[id].push(index);
+        cycle
+    }
+
+    /// Returns the cycle of period `last - earlier`, if the states before `last` match it
+    fn get_cycle(&self, earlier: usize, last: usize) -> Option<Cycle<O>> {
+        let period = last - earlier;
+        let delta = self.history[last].1 - self.history[earlier].1;
+        let matches = |index: usize| {
+            let ((key, offset), (next_key, next_offset)) =
+                (&self.history[index], &self.history[index + period]);
+            key == next_key && *next_offset - *offset == delta
+        };
+        let checks = ((self.repetitions - 1) * period).max(1);
+        if checks > earlier + 1 || !(earlier + 1 - checks..=earlier).all(matches) {
+            return None;
+        }
+        let prefix_length = (0..earlier + 1 - checks)
+            .rev()
+            .find(|&index| !matches(index))
+            .map_or(0, |index| index + 1);
+        Some(Cycle {
+            prefix_length,
+            period,
+            delta,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 3 states before a cycle of 4, each state being shifted by 10 from 4 states before
+    fn next((key, offset): &(usize, usize)) -> (usize, usize) {
+        match key {
+            0..=5 => (key + 1, offset + 1),
+            _ => (3, offset + 7),
+        }
+    }
+
+    fn offset(
+        (key, offset): &(usize, usize),
+        (other_key, other_offset): &(usize, usize),
+    ) -> Option<usize> {
+        Some(other_offset - offset).filter(|_| key == other_key)
+    }
+
+    #[test]
+    fn test_floyd_and_brent() {
+        let expected = Cycle {
+            prefix_length: 3,
+            period: 4,
+            delta: 10,
+        };
+        assert_eq!(floyd((0, 0), next, offset), expected);
+        assert_eq!(brent((0, 0), next, offset), expected);
+
+        let next = |value: &u32| (value * value + 1) % 255;
+        let cycle = brent(3, next, equal);
+        assert_eq!(cycle, floyd(3, next, equal));
+        assert_eq!((cycle.prefix_length, cycle.period), (2, 6));
+        assert_eq!(cycle.reduce(3), (3, 0));
+        assert_eq!(cycle.reduce(20), (2, 3));
+    }
+
+    #[test]
+    fn test_detector() {
+        let mut detector = CycleDetector::new();
+        let mut state = (0, 0);
+        let cycle = loop {
+            if let Some(cycle) = detector.push(state.0, state.1) {
+                break cycle;
+            }
+            state = next(&state);
+        };
+        assert_eq!(detector.len(), 8);
+        assert_eq!(detector.key(7), &3);
+        assert_eq!(detector.clone().into_keys(), vec![0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(
+            cycle,
+            Cycle {
+                prefix_length: 3,
+                period: 4,
+                delta: 10
+            }
+        );
+        let (index, cycles) = cycle.reduce(1000);
+        assert_eq!(detector.offset(index) + cycles * cycle.delta, 2494);
+    }
+
+    #[test]
+    fn test_detector_repetitions() {
+        // The keys are the shapes of falling rocks, the offsets their heights
+        let history = [(2, 0), (0, 1), (1, 4), (2, 6), (0, 7), (1, 10)];
+        let mut detector = CycleDetector::new().with_repetitions(2);
+        let cycles: Vec<_> = IntoIterator::into_iter(history)
+            .map(|(key, offset)| detector.push(key, offset))
+            .collect();
+        assert!(cycles[..5].iter().all(Option::is_none));
+        assert_eq!(
+            cycles[5],
+            Some(Cycle {
+                prefix_length: 0,
+                period: 3,
+                delta: 6
+            })
+        );
+        // A single repeated key is enough by default
+        let mut detector = CycleDetector::new();
+        let first_cycle = IntoIterator::into_iter(history)
+            .position(|(key, offset)| detector.push(key, offset).is_some());
+        assert_eq!(first_cycle, Some(3));
+
+        // The same key with a different delta
+        let mut detector = CycleDetector::new().with_repetitions(2);
+        let history = [(0, 1), (1, 4), (0, 6), (1, 10), (0, 13)];
+        assert!(IntoIterator::into_iter(history)
+            .all(|(key, offset)| detector.push(key, offset).is_none()));
+    }
+}
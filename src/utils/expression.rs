@@ -0,0 +1,524 @@
+use crate::utils::rational::Rational;
+use std::error::Error;
+use std::fmt;
+
+/// An arithmetic operator between two expressions
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Operator {
+    /// Returns the operator written as `+`, `-`, `*` or `/`
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol {
+            "+" => Some(Operator::Add),
+            "-" => Some(Operator::Sub),
+            "*" => Some(Operator::Mul),
+            "/" => Some(Operator::Div),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Sub => '-',
+            Operator::Mul => '*',
+            Operator::Div => '/',
+        }
+    }
+
+    pub fn apply(&self, left: Rational, right: Rational) -> Result<Rational, ExpressionError> {
+        let result = match self {
+            Operator::Add => left.checked_add(right),
+            Operator::Sub => left.checked_sub(right),
+            Operator::Mul => left.checked_mul(right),
+            Operator::Div if right == Rational::ZERO => {
+                return Err(ExpressionError::DivisionByZero)
+            }
+            Operator::Div => left.checked_div(right),
+        };
+        result.ok_or(ExpressionError::Overflow)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ExpressionError {
+    DivisionByZero,
+    /// A value does not fit in a rational number
+    Overflow,
+    /// A variable was left in an expression being evaluated
+    UnboundVariable(String),
+    /// The unknown of an equation appears on none of its sides
+    UnknownNotFound(String),
+    /// The unknown of an equation appears on both of its sides
+    UnknownOnBothSides(String),
+    /// The unknown appears more than once on the same side of an equation
+    RepeatedUnknown(String),
+    /// The equation has no solution, or infinitely many, like `0 * x = 1`
+    NoUniqueSolution(String),
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpressionError::DivisionByZero => write!(f, "division by zero"),
+            ExpressionError::Overflow => write!(f, "overflow"),
+            ExpressionError::UnboundVariable(name) => write!(f, "`{}` has no value", name),
+            ExpressionError::UnknownNotFound(name) => {
+                write!(f, "`{}` does not appear in the equation", name)
+            }
+            ExpressionError::UnknownOnBothSides(name) => {
+                write!(f, "`{}` appears on both sides of the equation", name)
+            }
+            ExpressionError::RepeatedUnknown(name) => {
+                write!(f, "`{}` appears more than once in the equation", name)
+            }
+            ExpressionError::NoUniqueSolution(name) => {
+                write!(f, "the equation has no single value of `{}`", name)
+            }
+        }
+    }
+}
+
+impl Error for ExpressionError {}
+
+/// An arithmetic expression over rational numbers, with named variables
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Expression {
+    Constant(Rational),
+    Variable(String),
+    Operation(Box<Expression>, Operator, Box<Expression>),
+}
+
+impl Expression {
+    pub fn operation(left: Expression, operator: Operator, right: Expression) -> Self {
+        Expression::Operation(Box::new(left), operator, Box::new(right))
+    }
+
+    pub fn evaluate(&self) -> Result<Rational, ExpressionError> {
+        match self {
+            Expression::Constant(value) => Ok(*value),
+            Expression::Variable(name) => Err(ExpressionError::UnboundVariable(name.clone())),
+            Expression::Operation(left, operator, right) => {
+                operator.apply(left.evaluate()?, right.evaluate()?)
+            }
+        }
+    }
+
+    /// Replace every operation without variables by its value, in a single pass.
+    /// What remains are the variables, and the operations on the path to them.
+    pub fn simplify(&self) -> Result<Expression, ExpressionError> {
+        match self {
+            Expression::Operation(left, operator, right) => {
+                match (left.simplify()?, right.simplify()?) {
+                    (Expression::Constant(left), Expression::Constant(right)) => {
+                        Ok(Expression::Constant(operator.apply(left, right)?))
+                    }
+                    (left, right) => Ok(Expression::operation(left, *operator, right)),
+                }
+            }
+            _ => Ok(self.clone()),
+        }
+    }
+
+    pub fn is_constant(&self) -> bool {
+        matches!(self, Expression::Constant(_))
+    }
+
+    pub fn contains_variable(&self, name: &str) -> bool {
+        match self {
+            Expression::Constant(_) => false,
+            Expression::Variable(variable) => variable == name,
+            Expression::Operation(left, _, right) => {
+                left.contains_variable(name) || right.contains_variable(name)
+            }
+        }
+    }
+
+    /// Write an operand, between parentheses if it could be read otherwise
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Constant(value) if value.is_integer() && *value >= Rational::ZERO => {
+                write!(f, "{}", value)
+            }
+            Expression::Variable(name) => write!(f, "{}", name),
+            _ => write!(f, "({})", self),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Constant(value) => write!(f, "{}", value),
+            Expression::Variable(name) => write!(f, "{}", name),
+            Expression::Operation(left, operator, right) => {
+                left.fmt_operand(f)?;
+                write!(f, " {} ", operator.symbol())?;
+                right.fmt_operand(f)
+            }
+        }
+    }
+}
+
+/// A node of an [`ExpressionDag`], referring to its operands by id
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Node {
+    Constant(Rational),
+    Variable(String),
+    Operation(usize, Operator, usize),
+}
+
+/// Expressions sharing their common operands, as nodes referring to their operands by id.
+/// Each node is built and computed once, however many nodes use it.
+#[derive(Debug, Clone, Default)]
+pub struct ExpressionDag {
+    nodes: Vec<Node>,
+}
+
+impl ExpressionDag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a node after its operands, and returns its id.
+    /// Panics if an operand is not already in the DAG.
+    pub fn push(&mut self, node: Node) -> usize {
+        if let Node::Operation(left, _, right) = node {
+            assert!(
+                left < self.nodes.len() && right < self.nodes.len(),
+                "The operands of a node must be added before it"
+            );
+        }
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// Returns the value of every node without variables, indexed by id. As operands come
+    /// before the nodes using them, each value is computed once from the values of its operands.
+    pub fn constant_values(&self) -> Result<Vec<Option<Rational>>, ExpressionError> {
+        let mut values = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let value = match node {
+                Node::Constant(value) => Some(*value),
+                Node::Variable(_) => None,
+                Node::Operation(left, operator, right) => match (values[*left], values[*right]) {
+                    (Some(left), Some(right)) => Some(operator.apply(left, right)?),
+                    _ => None,
+                },
+            };
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    pub fn evaluate(&self, id: usize) -> Result<Rational, ExpressionError> {
+        let values = self.constant_values()?;
+        match values[id] {
+            Some(value) => Ok(value),
+            None => self.to_expression(id, &values).evaluate(),
+        }
+    }
+
+    /// Returns the expression of a node, where the nodes without variables are replaced by their
+    /// `values`, as given by `constant_values`. What remains are the variables, and the
+    /// operations on the path to them.
+    pub fn to_expression(&self, id: usize, values: &[Option<Rational>]) -> Expression {
+        if let Some(value) = values[id] {
+            return Expression::Constant(value);
+        }
+        match &self.nodes[id] {
+            Node::Constant(value) => Expression::Constant(*value),
+            Node::Variable(name) => Expression::Variable(name.clone()),
+            Node::Operation(left, operator, right) => Expression::operation(
+                self.to_expression(*left, values),
+                *operator,
+                self.to_expression(*right, values),
+            ),
+        }
+    }
+}
+
+/// An equality between two expressions
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Equation {
+    pub left: Expression,
+    pub right: Expression,
+}
+
+impl Equation {
+    pub fn new(left: Expression, right: Expression) -> Self {
+        Self { left, right }
+    }
+
+    pub fn simplify(&self) -> Result<Equation, ExpressionError> {
+        Ok(Self::new(self.left.simplify()?, self.right.simplify()?))
+    }
+
+    /// Find the value of `unknown` making both sides equal.
+    /// It must appear exactly once, and every other variable must already be replaced by its
+    /// value.
+    pub fn solve(&self, unknown: &str) -> Result<Rational, ExpressionError> {
+        let equation = self.simplify()?;
+        let (mut expression, mut value) = match (&equation.left, &equation.right) {
+            (expression, Expression::Constant(value))
+            | (Expression::Constant(value), expression) => (expression, *value),
+            (left, right)
+                if left.contains_variable(unknown) && right.contains_variable(unknown) =>
+            {
+                return Err(ExpressionError::UnknownOnBothSides(unknown.to_string()))
+            }
+            (left, right) => return Err(unbound_variable(left, right, unknown)),
+        };
+
+        // Undo the operations one by one, from the outermost one
+        loop {
+            match expression {
+                Expression::Variable(name) if name == unknown => return Ok(value),
+                Expression::Variable(name) => {
+                    return Err(ExpressionError::UnboundVariable(name.clone()))
+                }
+                Expression::Constant(_) => {
+                    return Err(ExpressionError::UnknownNotFound(unknown.to_string()))
+                }
+                Expression::Operation(left, operator, right) => {
+                    (expression, value) = match (left.as_ref(), right.as_ref()) {
+                        (left, Expression::Constant(constant)) => {
+                            (left, invert_left(*operator, value, *constant, unknown)?)
+                        }
+                        (Expression::Constant(constant), right) => {
+                            (right, invert_right(*operator, value, *constant, unknown)?)
+                        }
+                        (left, right) => return Err(unbound_variable(left, right, unknown)),
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Equation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.left, self.right)
+    }
+}
+
+/// Returns `x` so that `x <operator> constant = value`
+fn invert_left(
+    operator: Operator,
+    value: Rational,
+    constant: Rational,
+    unknown: &str,
+) -> Result<Rational, ExpressionError> {
+    let result = match operator {
+        Operator::Add => value.checked_sub(constant),
+        Operator::Sub => value.checked_add(constant),
+        Operator::Mul | Operator::Div if constant == Rational::ZERO => {
+            return Err(no_unique_solution(unknown))
+        }
+        Operator::Mul => value.checked_div(constant),
+        Operator::Div => value.checked_mul(constant),
+    };
+    result.ok_or(ExpressionError::Overflow)
+}
+
+/// Returns `x` so that `constant <operator> x = value`
+fn invert_right(
+    operator: Operator,
+    value: Rational,
+    constant: Rational,
+    unknown: &str,
+) -> Result<Rational, ExpressionError> {
+    let result = match operator {
+        Operator::Add => value.checked_sub(constant),
+        Operator::Sub => constant.checked_sub(value),
+        // `0 / x` is 0 whatever `x` is, and never anything else
+        Operator::Mul | Operator::Div if constant == Rational::ZERO => {
+            return Err(no_unique_solution(unknown))
+        }
+        Operator::Mul => value.checked_div(constant),
+        // `constant / x` is never 0
+        Operator::Div if value == Rational::ZERO => return Err(no_unique_solution(unknown)),
+        Operator::Div => constant.checked_div(value),
+    };
+    result.ok_or(ExpressionError::Overflow)
+}
+
+fn no_unique_solution(unknown: &str) -> ExpressionError {
+    ExpressionError::NoUniqueSolution(unknown.to_string())
+}
+
+/// Explain why neither side of an operation or an equation is constant, once simplified
+fn unbound_variable(left: &Expression, right: &Expression, unknown: &str) -> ExpressionError {
+    if left.contains_variable(unknown) && right.contains_variable(unknown) {
+        return ExpressionError::RepeatedUnknown(unknown.to_string());
+    }
+    match first_other_variable(left, unknown).or_else(|| first_other_variable(right, unknown)) {
+        Some(name) => ExpressionError::UnboundVariable(name.to_string()),
+        None => ExpressionError::UnknownNotFound(unknown.to_string()),
+    }
+}
+
+fn first_other_variable<'a>(expression: &'a Expression, unknown: &str) -> Option<&'a str> {
+    match expression {
+        Expression::Constant(_) => None,
+        Expression::Variable(name) => Some(name.as_str()).filter(|name| *name != unknown),
+        Expression::Operation(left, _, right) => {
+            first_other_variable(left, unknown).or_else(|| first_other_variable(right, unknown))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constant(value: i64) -> Expression {
+        Expression::Constant(value.into())
+    }
+
+    fn variable(name: &str) -> Expression {
+        Expression::Variable(name.to_string())
+    }
+
+    #[test]
+    fn test_evaluate_and_simplify() {
+        // (4 + 2 * (x - 3)) / 4
+        let expression = Expression::operation(
+            Expression::operation(
+                constant(4),
+                Operator::Add,
+                Expression::operation(
+                    constant(2),
+                    Operator::Mul,
+                    Expression::operation(variable("x"), Operator::Sub, constant(3)),
+                ),
+            ),
+            Operator::Div,
+            Expression::operation(constant(8), Operator::Div, constant(2)),
+        );
+        assert_eq!(
+            expression.evaluate(),
+            Err(ExpressionError::UnboundVariable("x".to_string()))
+        );
+        let simplified = expression.simplify().unwrap();
+        assert_eq!(simplified.to_string(), "(4 + (2 * (x - 3))) / 4");
+        assert!(simplified.contains_variable("x"));
+
+        let division = Expression::operation(constant(7), Operator::Div, constant(2));
+        assert_eq!(division.evaluate(), Ok(Rational::new(7, 2)));
+        let division = Expression::operation(constant(7), Operator::Div, constant(0));
+        assert_eq!(division.simplify(), Err(ExpressionError::DivisionByZero));
+    }
+
+    #[test]
+    fn test_dag() {
+        // (x - 3) * (8 / 2) + (8 / 2), where `8 / 2` is shared
+        let mut dag = ExpressionDag::new();
+        let x = dag.push(Node::Variable("x".to_string()));
+        let three = dag.push(Node::Constant(3.into()));
+        let eight = dag.push(Node::Constant(8.into()));
+        let two = dag.push(Node::Constant(2.into()));
+        let four = dag.push(Node::Operation(eight, Operator::Div, two));
+        let x_minus_three = dag.push(Node::Operation(x, Operator::Sub, three));
+        let product = dag.push(Node::Operation(x_minus_three, Operator::Mul, four));
+        let sum = dag.push(Node::Operation(product, Operator::Add, four));
+
+        let values = dag.constant_values().unwrap();
+        assert_eq!(values[four], Some(4.into()));
+        assert_eq!(values[sum], None);
+        assert_eq!(dag.evaluate(four), Ok(4.into()));
+        assert_eq!(
+            dag.evaluate(sum),
+            Err(ExpressionError::UnboundVariable("x".to_string()))
+        );
+        let expression = dag.to_expression(sum, &values);
+        assert_eq!(expression.to_string(), "((x - 3) * 4) + 4");
+        assert_eq!(
+            Equation::new(expression, constant(12)).solve("x"),
+            Ok(5.into())
+        );
+
+        let zero = dag.push(Node::Constant(0.into()));
+        dag.push(Node::Operation(four, Operator::Div, zero));
+        assert_eq!(dag.evaluate(four), Err(ExpressionError::DivisionByZero));
+    }
+
+    #[test]
+    fn test_solve_operations() {
+        let solve = |left, operator, right| {
+            Equation::new(Expression::operation(left, operator, right), constant(150))
+                .solve("x")
+                .unwrap()
+        };
+        assert_eq!(solve(variable("x"), Operator::Sub, constant(3)), 153.into());
+        assert_eq!(
+            solve(constant(3), Operator::Sub, variable("x")),
+            (-147).into()
+        );
+        assert_eq!(solve(constant(3), Operator::Add, variable("x")), 147.into());
+        assert_eq!(solve(variable("x"), Operator::Add, constant(3)), 147.into());
+        assert_eq!(solve(variable("x"), Operator::Mul, constant(3)), 50.into());
+        assert_eq!(solve(constant(3), Operator::Mul, variable("x")), 50.into());
+        assert_eq!(solve(variable("x"), Operator::Div, constant(3)), 450.into());
+        assert_eq!(
+            solve(constant(3), Operator::Div, variable("x")),
+            Rational::new(1, 50)
+        );
+    }
+
+    #[test]
+    fn test_solve_errors() {
+        let x_plus_one = Expression::operation(variable("x"), Operator::Add, constant(1));
+        let solve = |left: &Expression, right: &Expression| {
+            Equation::new(left.clone(), right.clone()).solve("x")
+        };
+        assert_eq!(
+            solve(&x_plus_one, &x_plus_one),
+            Err(ExpressionError::UnknownOnBothSides("x".to_string()))
+        );
+        assert_eq!(
+            solve(&constant(1), &constant(2)),
+            Err(ExpressionError::UnknownNotFound("x".to_string()))
+        );
+        assert_eq!(
+            solve(&variable("y"), &constant(2)),
+            Err(ExpressionError::UnboundVariable("y".to_string()))
+        );
+        let x_times_x = Expression::operation(variable("x"), Operator::Mul, variable("x"));
+        assert_eq!(
+            solve(&x_times_x, &constant(4)),
+            Err(ExpressionError::RepeatedUnknown("x".to_string()))
+        );
+        let x_times_zero = Expression::operation(variable("x"), Operator::Mul, constant(0));
+        assert_eq!(
+            solve(&x_times_zero, &constant(4)),
+            Err(ExpressionError::NoUniqueSolution("x".to_string()))
+        );
+        let zero_by_x = Expression::operation(constant(0), Operator::Div, variable("x"));
+        for value in [0, 5] {
+            assert_eq!(
+                solve(&zero_by_x, &constant(value)),
+                Err(ExpressionError::NoUniqueSolution("x".to_string()))
+            );
+        }
+        let x_times_two = Expression::operation(variable("x"), Operator::Mul, constant(2));
+        assert_eq!(
+            solve(
+                &x_times_two,
+                &Expression::Constant(Rational::new(1, i128::MAX))
+            ),
+            Err(ExpressionError::Overflow)
+        );
+        let max = Expression::Constant(Rational::from_integer(i128::MAX));
+        let max_plus_one = Expression::operation(max, Operator::Add, constant(1));
+        assert_eq!(max_plus_one.evaluate(), Err(ExpressionError::Overflow));
+        let equation = Equation::new(x_plus_one, constant(3));
+        assert_eq!(equation.to_string(), "x + 1 = 3");
+    }
+}
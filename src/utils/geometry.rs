@@ -0,0 +1,384 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// The numbers usable as coordinates
+pub trait Coordinate:
+    Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Returns `|self - other|`, without overflowing for unsigned numbers
+    fn abs_diff(self, other: Self) -> Self {
+        if self > other {
+            self - other
+        } else {
+            other - self
+        }
+    }
+
+    /// Returns `-1`, `0` or `1` depending on the sign of the number
+    fn signum(self) -> Self;
+}
+
+/// The coordinates which can be negative, so points can be moved in every direction
+pub trait SignedCoordinate: Coordinate + Neg<Output = Self> {}
+
+macro_rules! impl_coordinate {
+    (signed: $($t:ty),*) => {
+        $(
+            impl Coordinate for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn signum(self) -> Self {
+                    <$t>::signum(self)
+                }
+            }
+
+            impl SignedCoordinate for $t {}
+        )*
+    };
+    (unsigned: $($t:ty),*) => {
+        $(
+            impl Coordinate for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn signum(self) -> Self {
+                    (self > 0) as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_coordinate!(signed: i8, i16, i32, i64, i128, isize);
+impl_coordinate!(unsigned: u8, u16, u32, u64, u128, usize);
+
+fn max<T: Coordinate>(a: T, b: T) -> T {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+fn min<T: Coordinate>(a: T, b: T) -> T {
+    if a < b {
+        a
+    } else {
+        b
+    }
+}
+
+/// A point, or a vector, on a plane whose `y` axis points down like the rows of a puzzle input
+#[derive(Debug, Default, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T: Coordinate> Point2<T> {
+    pub fn manhattan_distance(&self, other: &Self) -> T {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// The number of king moves between both points, diagonals included
+    pub fn chebyshev_distance(&self, other: &Self) -> T {
+        max(self.x.abs_diff(other.x), self.y.abs_diff(other.y))
+    }
+
+    /// Returns the vector of the signs of each coordinate, which is a single step toward the
+    /// direction of `self`, diagonals included
+    pub fn signum(&self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+
+    /// Returns the point made of the smallest coordinates of both points
+    pub fn component_min(&self, other: &Self) -> Self {
+        Self::new(min(self.x, other.x), min(self.y, other.y))
+    }
+
+    /// Returns the point made of the largest coordinates of both points
+    pub fn component_max(&self, other: &Self) -> Self {
+        Self::new(max(self.x, other.x), max(self.y, other.y))
+    }
+}
+
+impl<T: SignedCoordinate> Point2<T> {
+    /// Rotate by a quarter turn counterclockwise, as seen on the input
+    pub fn rotate_left(&self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+
+    /// Rotate by a quarter turn clockwise, as seen on the input
+    pub fn rotate_right(&self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// Returns the 4 orthogonal neighbours: up, right, down, then left
+    pub fn neighbours(&self) -> [Self; 4] {
+        Direction::ALL.map(|direction| *self + direction.offset())
+    }
+
+    /// Returns the 8 neighbours, diagonals included, row by row
+    pub fn neighbours_8(&self) -> [Self; 8] {
+        let (zero, one) = (T::ZERO, T::ONE);
+        [
+            (-one, -one),
+            (zero, -one),
+            (one, -one),
+            (-one, zero),
+            (one, zero),
+            (-one, one),
+            (zero, one),
+            (one, one),
+        ]
+        .map(|(x, y)| *self + Self::new(x, y))
+    }
+}
+
+impl<T> From<(T, T)> for Point2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl<T> From<Point2<T>> for (T, T) {
+    fn from(point: Point2<T>) -> Self {
+        (point.x, point.y)
+    }
+}
+
+/// A point, or a vector, in a 3D space
+#[derive(Debug, Default, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T: Coordinate> Point3<T> {
+    pub fn manhattan_distance(&self, other: &Self) -> T {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y) + self.z.abs_diff(other.z)
+    }
+
+    pub fn chebyshev_distance(&self, other: &Self) -> T {
+        max(
+            max(self.x.abs_diff(other.x), self.y.abs_diff(other.y)),
+            self.z.abs_diff(other.z),
+        )
+    }
+
+    pub fn signum(&self) -> Self {
+        Self::new(self.x.signum(), self.y.signum(), self.z.signum())
+    }
+
+    pub fn component_min(&self, other: &Self) -> Self {
+        Self::new(
+            min(self.x, other.x),
+            min(self.y, other.y),
+            min(self.z, other.z),
+        )
+    }
+
+    pub fn component_max(&self, other: &Self) -> Self {
+        Self::new(
+            max(self.x, other.x),
+            max(self.y, other.y),
+            max(self.z, other.z),
+        )
+    }
+}
+
+impl<T: SignedCoordinate> Point3<T> {
+    /// Returns the 6 neighbours sharing a face: along `x`, then `y`, then `z`
+    pub fn neighbours(&self) -> [Self; 6] {
+        let (zero, one) = (T::ZERO, T::ONE);
+        [
+            (-one, zero, zero),
+            (one, zero, zero),
+            (zero, -one, zero),
+            (zero, one, zero),
+            (zero, zero, -one),
+            (zero, zero, one),
+        ]
+        .map(|(x, y, z)| *self + Self::new(x, y, z))
+    }
+}
+
+impl<T> From<(T, T, T)> for Point3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+macro_rules! impl_ops {
+    ($point:ident { $($field:ident),* }) => {
+        impl<T: Add<Output = T>> Add for $point<T> {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self { $($field: self.$field + other.$field),* }
+            }
+        }
+
+        impl<T: AddAssign> AddAssign for $point<T> {
+            fn add_assign(&mut self, other: Self) {
+                $(self.$field += other.$field;)*
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $point<T> {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self { $($field: self.$field - other.$field),* }
+            }
+        }
+
+        impl<T: SubAssign> SubAssign for $point<T> {
+            fn sub_assign(&mut self, other: Self) {
+                $(self.$field -= other.$field;)*
+            }
+        }
+
+        impl<T: Neg<Output = T>> Neg for $point<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($field: -self.$field),* }
+            }
+        }
+
+        /// Scale the vector by a factor
+        impl<T: Mul<Output = T> + Copy> Mul<T> for $point<T> {
+            type Output = Self;
+
+            fn mul(self, factor: T) -> Self {
+                Self { $($field: self.$field * factor),* }
+            }
+        }
+    };
+}
+
+impl_ops!(Point2 { x, y });
+impl_ops!(Point3 { x, y, z });
+
+/// One of the 4 orthogonal directions, as seen on the puzzle input
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// Every direction, clockwise from `Up`
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    /// Returns the vector of a single step in this direction
+    pub fn offset<T: SignedCoordinate>(&self) -> Point2<T> {
+        match self {
+            Direction::Up => Point2::new(T::ZERO, -T::ONE),
+            Direction::Right => Point2::new(T::ONE, T::ZERO),
+            Direction::Down => Point2::new(T::ZERO, T::ONE),
+            Direction::Left => Point2::new(-T::ONE, T::ZERO),
+        }
+    }
+
+    pub fn turn_left(&self) -> Self {
+        Self::ALL[(*self as usize + 3) % 4]
+    }
+
+    pub fn turn_right(&self) -> Self {
+        Self::ALL[(*self as usize + 1) % 4]
+    }
+
+    pub fn opposite(&self) -> Self {
+        Self::ALL[(*self as usize + 2) % 4]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ops() {
+        let mut point = Point2::new(1, 2) + Point2::new(3, -4);
+        assert_eq!(point, Point2::new(4, -2));
+        point -= Point2::new(1, 1);
+        assert_eq!(point, Point2::new(3, -3));
+        assert_eq!(-point * 2, Point2::new(-6, 6));
+        assert_eq!(
+            Point3::new(1, 2, 3) - Point3::new(3, 2, 1),
+            Point3::new(-2, 0, 2)
+        );
+    }
+
+    #[test]
+    fn test_distances() {
+        let (a, b) = (Point2::new(2, 18), Point2::new(-2, 15));
+        assert_eq!(a.manhattan_distance(&b), 7);
+        assert_eq!(a.chebyshev_distance(&b), 4);
+        assert_eq!((b - a).signum(), Point2::new(-1, -1));
+
+        let (a, b) = (Point2::new(5_usize, 1), Point2::new(2, 3));
+        assert_eq!(a.manhattan_distance(&b), 5);
+        assert_eq!(b.manhattan_distance(&a), 5);
+
+        let (a, b) = (Point3::new(1, 2, 3), Point3::new(2, 0, 3));
+        assert_eq!(a.manhattan_distance(&b), 3);
+        assert_eq!(a.chebyshev_distance(&b), 2);
+        assert_eq!(a.component_min(&b), Point3::new(1, 0, 3));
+    }
+
+    #[test]
+    fn test_rotations() {
+        let right = Direction::Right.offset::<i32>();
+        assert_eq!(right.rotate_right(), Direction::Down.offset());
+        assert_eq!(right.rotate_left(), Direction::Up.offset());
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Left.turn_right(), Direction::Up);
+        assert_eq!(Direction::Down.opposite(), Direction::Up);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let point = Point2::new(0, 0);
+        assert_eq!(
+            point.neighbours(),
+            [
+                Point2::new(0, -1),
+                Point2::new(1, 0),
+                Point2::new(0, 1),
+                Point2::new(-1, 0)
+            ]
+        );
+        assert_eq!(point.neighbours_8().len(), 8);
+        assert!(!point.neighbours_8().contains(&point));
+        let neighbours = Point3::new(1, 1, 1).neighbours();
+        assert!(neighbours
+            .iter()
+            .all(|n| n.manhattan_distance(&Point3::new(1, 1, 1)) == 1));
+    }
+}
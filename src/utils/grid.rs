@@ -0,0 +1,358 @@
+use crate::utils::parse_error::ParseError;
+use std::fmt;
+use std::fmt::Display;
+use std::iter;
+use std::ops::{Index, IndexMut};
+
+/// The `(x, y)` position of a tile, `(0, 0)` being the upper left one
+pub type Position = (usize, usize);
+
+/// The `(x, y)` offsets to the 4 orthogonal neighbours: up, right, down, then left
+pub const NEIGHBOURS_4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// The `(x, y)` offsets to the 8 neighbours, row by row
+pub const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// A rectangular grid of tiles, stored row by row
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    tiles: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Create a grid filled with `value`.
+    /// Panics if it has no tile, like grids built from rows cannot.
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        assert!(
+            width > 0 && height > 0,
+            "A grid cannot be {}x{}, it needs at least one tile",
+            width,
+            height
+        );
+        Self {
+            width,
+            height,
+            tiles: vec![value; width * height],
+        }
+    }
+
+    /// Create a grid from its rows.
+    /// Returns `None` if there is no tile, or if the rows are not all as wide.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let width = rows.first().map_or(0, |row| row.len());
+        if width == 0 || rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Some(Self {
+            width,
+            height: rows.len(),
+            tiles: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parse a grid with one tile per character, and one row per line.
+    /// `parse_tile` returns `None` for unexpected characters, which are reported as `expected`.
+    pub fn parse<F>(input: &str, expected: &str, mut parse_tile: F) -> Result<Self, ParseError>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut rows = vec![];
+        for line in input.lines() {
+            let row = line
+                .char_indices()
+                .map(|(idx, c)| {
+                    parse_tile(c).ok_or_else(|| {
+                        ParseError::new(input, &line[idx..idx + c.len_utf8()], expected)
+                    })
+                })
+                .collect::<Result<Vec<T>, ParseError>>()?;
+            rows.push(row);
+        }
+
+        let width = rows.first().map_or(0, |row| row.len());
+        if width == 0 {
+            return Err(ParseError::new(input, input, "a grid of tiles"));
+        }
+        if let Some(y) = rows.iter().position(|row| row.len() != width) {
+            let line = input.lines().nth(y).unwrap();
+            return Err(ParseError::new(
+                input,
+                line,
+                format!("a row of {} tiles", width),
+            ));
+        }
+        Ok(Self::from_rows(rows).unwrap())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): Position) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.get_index(position).map(|idx| &self.tiles[idx])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.get_index(position)
+            .map(move |idx| &mut self.tiles[idx])
+    }
+
+    fn get_index(&self, (x, y): Position) -> Option<usize> {
+        if self.contains((x, y)) {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the position moved by `(offset_x, offset_y)`, if it is still in the grid
+    pub fn offset(
+        &self,
+        (x, y): Position,
+        (offset_x, offset_y): (isize, isize),
+    ) -> Option<Position> {
+        let x = x.checked_add_signed(offset_x)?;
+        let y = y.checked_add_signed(offset_y)?;
+        Some((x, y)).filter(|position| self.contains(*position))
+    }
+
+    /// Returns the position moved by `(offset_x, offset_y)`, wrapping around the edges of the grid
+    pub fn wrapping_offset(
+        &self,
+        (x, y): Position,
+        (offset_x, offset_y): (isize, isize),
+    ) -> Position {
+        let wrap = |value: usize, offset: isize, len: usize| {
+            (value as isize + offset).rem_euclid(len as isize) as usize
+        };
+        (
+            wrap(x, offset_x, self.width),
+            wrap(y, offset_y, self.height),
+        )
+    }
+
+    /// Iterate over the orthogonal neighbours of a position which are in the grid
+    pub fn neighbours(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        NEIGHBOURS_4
+            .iter()
+            .filter_map(move |offset| self.offset(position, *offset))
+    }
+
+    /// Iterate over the orthogonal and diagonal neighbours of a position which are in the grid
+    pub fn neighbours_8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        NEIGHBOURS_8
+            .iter()
+            .filter_map(move |offset| self.offset(position, *offset))
+    }
+
+    /// Iterate over the positions met when walking from `start` by steps of `step`, until leaving
+    /// the grid. `start` itself is not included.
+    pub fn ray(
+        &self,
+        start: Position,
+        step: (isize, isize),
+    ) -> impl Iterator<Item = Position> + '_ {
+        iter::successors(Some(start), move |position| self.offset(*position, step)).skip(1)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.tiles[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.tiles.chunks(self.width)
+    }
+
+    /// Panics if `x` is out of the grid, like `row` does for `y`
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(
+            x < self.width,
+            "Column {} is out of a grid {} tiles wide",
+            x,
+            self.width
+        );
+        self.tiles[x..].iter().step_by(self.width)
+    }
+
+    /// Iterate over every position, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Iterate over every tile and its position, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.tiles.iter())
+    }
+
+    /// Returns the position of the first tile matching `predicate`, row by row
+    pub fn find<P>(&self, mut predicate: P) -> Option<Position>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.iter()
+            .find(|(_, tile)| predicate(tile))
+            .map(|(position, _)| position)
+    }
+
+    /// Create a grid of the same size, with each tile converted by `f`
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            tiles: self.tiles.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        let idx = self.get_index(position).unwrap_or_else(|| {
+            panic!(
+                "Position {:?} is out of a {}x{} grid",
+                position, self.width, self.height
+            )
+        });
+        &self.tiles[idx]
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        let idx = self.get_index(position).unwrap_or_else(|| {
+            panic!(
+                "Position {:?} is out of a {}x{} grid",
+                position, self.width, self.height
+            )
+        });
+        &mut self.tiles[idx]
+    }
+}
+
+/// Render the grid row by row, without a trailing line break
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for tile in row {
+                write!(f, "{}", tile)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_grid() -> Grid<char> {
+        Grid::parse("abc\ndef", "a letter", |c| {
+            Some(c).filter(char::is_ascii_lowercase)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = get_grid();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 0)], 'c');
+        assert_eq!(grid.get((0, 1)), Some(&'d'));
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.to_string(), "abc\ndef");
+
+        let parse = |input| Grid::parse(input, "a digit", |c| c.to_digit(10));
+        let error = parse("12\n3x").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        assert_eq!(error.expected, "a digit");
+        let error = parse("12\n345").unwrap_err();
+        assert_eq!((error.line, error.text.as_str()), (2, "345"));
+        assert_eq!(error.expected, "a row of 2 tiles");
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = get_grid();
+        let neighbours: Vec<_> = grid.neighbours((0, 0)).collect();
+        assert_eq!(neighbours, vec![(1, 0), (0, 1)]);
+        let neighbours: Vec<_> = grid.neighbours_8((1, 1)).collect();
+        assert_eq!(neighbours, vec![(0, 0), (1, 0), (2, 0), (0, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn test_offsets() {
+        let grid = get_grid();
+        assert_eq!(grid.offset((1, 1), (1, -1)), Some((2, 0)));
+        assert_eq!(grid.offset((1, 1), (2, 0)), None);
+        assert_eq!(grid.offset((0, 0), (-1, 0)), None);
+        assert_eq!(grid.wrapping_offset((0, 0), (-1, 0)), (2, 0));
+        assert_eq!(grid.wrapping_offset((2, 1), (1, 3)), (0, 0));
+    }
+
+    #[test]
+    fn test_rows_columns_and_rays() {
+        let grid = get_grid();
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(grid.column(2).collect::<String>(), "cf");
+        let ray: Vec<_> = grid.ray((0, 0), (1, 0)).collect();
+        assert_eq!(ray, vec![(1, 0), (2, 0)]);
+        assert_eq!(grid.ray((2, 1), (1, 1)).count(), 0);
+    }
+
+    #[test]
+    fn test_iter_find_and_map() {
+        let mut grid = get_grid();
+        assert_eq!(grid.find(|c| *c == 'e'), Some((1, 1)));
+        assert_eq!(grid.find(|c| *c == 'z'), None);
+        grid[(1, 1)] = 'z';
+        assert_eq!(grid.iter().nth(4), Some(((1, 1), &'z')));
+        assert_eq!(grid.map(|c| c.to_ascii_uppercase()).to_string(), "ABC\nDZF");
+        assert_eq!(Grid::from_rows(vec![vec![1], vec![2, 3]]), None);
+        assert_eq!(Grid::new(2, 1, 0).to_string(), "00");
+    }
+
+    #[test]
+    #[should_panic(expected = "Column 3 is out of a grid 3 tiles wide")]
+    fn test_column_out_of_grid() {
+        get_grid().column(3).count();
+    }
+
+    #[test]
+    #[should_panic(expected = "A grid cannot be 0x2")]
+    fn test_new_without_tiles() {
+        Grid::new(0, 2, 'a');
+    }
+}
@@ -1,12 +1,104 @@
+use crate::utils::parse_error::{get_slice_offset, ParseError};
+use std::fmt;
 use std::fs;
+use std::io;
+use std::io::Read;
+use std::str::FromStr;
 
 #[cfg(windows)]
 pub const LINE_ENDING: &str = "\r\n";
 #[cfg(not(windows))]
 pub const LINE_ENDING: &str = "\n";
 
-pub fn get_file(file_name: &str) -> String {
-    fs::read_to_string(file_name).expect("Unable to read file")
+/// Where a puzzle input is read from
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum InputSource {
+    /// The input bundled with the day, in `src/day_XX/input.txt`
+    Bundled(u8),
+    File(String),
+    Stdin,
+}
+
+impl InputSource {
+    /// Get the source from a command line value, where `-` stands for the standard input
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            InputSource::Stdin
+        } else {
+            InputSource::File(arg.to_string())
+        }
+    }
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::Bundled(day) => write!(f, "{}", get_day_input_path(*day)),
+            InputSource::File(file_name) => write!(f, "{}", file_name),
+            InputSource::Stdin => write!(f, "<stdin>"),
+        }
+    }
+}
+
+pub fn get_file(file_name: &str) -> io::Result<String> {
+    fs::read_to_string(file_name)
+}
+
+pub fn get_stdin() -> io::Result<String> {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+    Ok(input)
+}
+
+pub fn read_input(source: &InputSource) -> io::Result<String> {
+    match source {
+        InputSource::Bundled(day) => get_file(&get_day_input_path(*day)),
+        InputSource::File(file_name) => get_file(file_name),
+        InputSource::Stdin => get_stdin(),
+    }
+}
+
+/// Parse each line of `input`. Errors are located on the line they occurred on.
+pub fn parse_lines<'a, T, F>(input: &'a str, mut parse_line: F) -> Result<Vec<T>, ParseError>
+where
+    F: FnMut(&'a str) -> Result<T, ParseError>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| parse_line(line).map_err(|e| e.shift(idx)))
+        .collect()
+}
+
+/// Parse each chunk of `input`, the chunks being separated by an empty line.
+/// Errors are located on the line they occurred on.
+pub fn parse_chunks<'a, T, F>(input: &'a str, mut parse_chunk: F) -> Result<Vec<T>, ParseError>
+where
+    F: FnMut(&'a str) -> Result<T, ParseError>,
+{
+    let split_separator = format!("{}{}", LINE_ENDING, LINE_ENDING);
+    input
+        .split(&split_separator)
+        .map(|chunk| parse_chunk(chunk).map_err(|e| e.shift(get_line_offset(input, chunk))))
+        .collect()
+}
+
+/// Returns the number of lines in `input` before `slice` starts
+pub fn get_line_offset(input: &str, slice: &str) -> usize {
+    get_slice_offset(input, slice).map_or(0, |offset| input[..offset].matches('\n').count())
+}
+
+/// Parse the `value` found in `source`, or get an error located on it.
+pub fn parse_number<T: FromStr>(source: &str, value: &str) -> Result<T, ParseError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| ParseError::new(source, value, "a number"))
+}
+
+/// Returns the path of the puzzle input bundled with the given day
+pub fn get_day_input_path(day: u8) -> String {
+    format!("./src/day_{:02}/input.txt", day)
 }
 
 #[macro_export]
@@ -1,2 +1,12 @@
+pub mod answers;
 pub mod arena_tree;
+pub mod cycle;
+pub mod expression;
+pub mod geometry;
+pub mod grid;
 pub mod inputs;
+pub mod ocr;
+pub mod packet;
+pub mod parse_error;
+pub mod rational;
+pub mod search;
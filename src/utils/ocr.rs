@@ -0,0 +1,149 @@
+use crate::utils::grid::Grid;
+use std::error::Error;
+use std::fmt;
+
+pub const LETTER_WIDTH: usize = 4;
+pub const LETTER_HEIGHT: usize = 6;
+/// Letters are separated by a column of dark pixels
+const LETTER_STRIDE: usize = LETTER_WIDTH + 1;
+
+/// The letters drawn by the puzzles, `#` being a lit pixel
+const FONT: [(char, [&str; LETTER_HEIGHT]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...", "#...", ".#.#", "..#.", "..#.", "..#."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum OcrError {
+    /// The letters must fill the whole height of the screen
+    Height(usize),
+    /// A glyph which is not in the font, drawn with `#` and `.`
+    UnknownLetter { index: usize, glyph: String },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OcrError::Height(height) => write!(
+                f,
+                "expected a screen {} pixels high, found {}",
+                LETTER_HEIGHT, height
+            ),
+            OcrError::UnknownLetter { index, glyph } => {
+                write!(f, "unknown letter at index {}:\n{}", index, glyph)
+            }
+        }
+    }
+}
+
+impl Error for OcrError {}
+
+/// Read the letters drawn on a screen with the font of the puzzles, `true` being a lit pixel.
+/// Letters are 4 pixels wide and 6 pixels high, separated by a dark column, the first one being
+/// on the left edge. Dark columns after the last letter are ignored.
+pub fn recognize(screen: &Grid<bool>) -> Result<String, OcrError> {
+    if screen.height() != LETTER_HEIGHT {
+        return Err(OcrError::Height(screen.height()));
+    }
+    let letters_count = screen.width().div_ceil(LETTER_STRIDE);
+    let glyphs: Vec<String> = (0..letters_count)
+        .map(|index| get_glyph(screen, index * LETTER_STRIDE))
+        .collect();
+    let last_letter = glyphs
+        .iter()
+        .rposition(|glyph| glyph.contains('#'))
+        .map_or(0, |index| index + 1);
+
+    glyphs[..last_letter]
+        .iter()
+        .enumerate()
+        .map(|(index, glyph)| {
+            FONT.iter()
+                .find(|(_, rows)| rows.join("\n") == *glyph)
+                .map(|(letter, _)| *letter)
+                .ok_or_else(|| OcrError::UnknownLetter {
+                    index,
+                    glyph: glyph.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Draw the letter starting at column `x` with `#` and `.`, pixels out of the screen being dark
+fn get_glyph(screen: &Grid<bool>, x: usize) -> String {
+    (0..LETTER_HEIGHT)
+        .map(|y| {
+            (x..x + LETTER_WIDTH)
+                .map(|x| match screen.get((x, y)) {
+                    Some(true) => '#',
+                    _ => '.',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_screen(drawing: &str) -> Grid<bool> {
+        Grid::parse(drawing, "`#` or `.`", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_recognize() {
+        let screen = get_screen(
+            "\
+.##..###..#..#.......
+#..#.#..#.#..#.......
+#..#.###..####.......
+####.#..#.#..#.......
+#..#.#..#.#..#.......
+#..#.###..#..#.......",
+        );
+        assert_eq!(recognize(&screen), Ok("ABH".to_string()));
+
+        // The last letter can lack its separating column
+        let screen = get_screen("#...\n#...\n#...\n#...\n#...\n####");
+        assert_eq!(recognize(&screen), Ok("L".to_string()));
+    }
+
+    #[test]
+    fn test_recognize_errors() {
+        assert_eq!(
+            recognize(&get_screen("####\n####")),
+            Err(OcrError::Height(2))
+        );
+        let screen = get_screen("####\n####\n####\n####\n####\n####");
+        assert_eq!(
+            recognize(&screen),
+            Err(OcrError::UnknownLetter {
+                index: 0,
+                glyph: "####\n####\n####\n####\n####\n####".to_string()
+            })
+        );
+    }
+}
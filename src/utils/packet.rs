@@ -0,0 +1,814 @@
+use crate::utils::parse_error::ParseError;
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::slice;
+use std::str::FromStr;
+
+/// A non-negative integer of any size, stored as its decimal digits without leading zeros
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Integer {
+    digits: String,
+}
+
+impl Integer {
+    /// Returns `None` unless `digits` is a non-empty string of digits without leading zeros
+    pub fn from_digits(digits: &str) -> Option<Self> {
+        Some(Self {
+            digits: digits.to_string(),
+        })
+        .filter(|_| is_canonical(digits))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.digits
+    }
+
+    /// Returns the value if it fits in a `u64`
+    pub fn to_u64(&self) -> Option<u64> {
+        self.digits.parse().ok()
+    }
+}
+
+impl From<u64> for Integer {
+    fn from(value: u64) -> Self {
+        Self {
+            digits: value.to_string(),
+        }
+    }
+}
+
+/// Without leading zeros, a longer number is a larger one
+impl Ord for Integer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_digits(&self.digits, &other.digits)
+    }
+}
+
+fn is_canonical(digits: &str) -> bool {
+    !digits.is_empty()
+        && digits.bytes().all(|byte| byte.is_ascii_digit())
+        && (digits == "0" || !digits.starts_with('0'))
+}
+
+/// Compare two integers written without leading zeros
+fn cmp_digits(left: &str, right: &str) -> Ordering {
+    left.len().cmp(&right.len()).then_with(|| left.cmp(right))
+}
+
+impl PartialOrd for Integer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.digits)
+    }
+}
+
+/// A value of a packet: an integer, or a list of values.
+///
+/// Packets are written like `[1,[2,[]],3]`, without spaces, which is also how they are
+/// displayed. As they are JSON arrays, they can be read from any JSON array of non-negative
+/// integers with `from_json`.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum PacketData {
+    Integer(Integer),
+    List(Vec<PacketData>),
+}
+
+impl PacketData {
+    pub fn is_integer(&self) -> bool {
+        matches!(self, PacketData::Integer(_))
+    }
+
+    pub fn is_list(&self) -> bool {
+        matches!(self, PacketData::List(_))
+    }
+
+    /// Compare two packets with the rules of the distress signal:
+    /// - Integers are compared by value.
+    /// - Lists are compared item by item, and a list running out of items first is lower.
+    /// - An integer compared with a list is first converted to a list containing only itself.
+    ///
+    /// So different packets can be equal, like `1` and `[[1]]`.
+    pub fn packet_cmp(&self, other: &Self) -> Ordering {
+        cmp_packets(self, other, true)
+    }
+
+    /// Compare the shape of two packets: integers come before lists, integers are compared by
+    /// value, and lists item by item
+    fn structural_cmp(&self, other: &Self) -> Ordering {
+        cmp_packets(self, other, false)
+    }
+
+    /// Read a packet from a JSON value, which may contain whitespace
+    pub fn from_json(json: &str) -> Result<Self, PacketError> {
+        parse(json, true)
+    }
+
+    /// Write the packet as a compact JSON value
+    pub fn to_json(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Compare two packets item by item, without recursion so deeply nested packets cannot
+/// overflow the stack. Nothing is allocated unless the packets are nested deeper than
+/// `INLINE_DEPTH` lists.
+/// With `promote_integers`, an integer compared with a list is seen as a list containing only
+/// itself, by borrowing it as a slice instead of building that list. Otherwise it is lower.
+fn cmp_packets(left: &PacketData, right: &PacketData, promote_integers: bool) -> Ordering {
+    // The rest of the enclosing lists, to compare once the current ones are equal
+    let mut enclosing_lists = EnclosingLists::new();
+    let (mut left, mut right) = (slice::from_ref(left), slice::from_ref(right));
+    loop {
+        let (left_item, right_item) = match (left.split_first(), right.split_first()) {
+            (Some((left_item, left_rest)), Some((right_item, right_rest))) => {
+                left = left_rest;
+                right = right_rest;
+                (left_item, right_item)
+            }
+            (None, None) => match enclosing_lists.pop() {
+                Some((left_rest, right_rest)) => {
+                    left = left_rest;
+                    right = right_rest;
+                    continue;
+                }
+                None => return Ordering::Equal,
+            },
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+        };
+        let (left_list, right_list) = match (left_item, right_item) {
+            (PacketData::Integer(left_value), PacketData::Integer(right_value)) => {
+                match left_value.cmp(right_value) {
+                    Ordering::Equal => continue,
+                    ordering => return ordering,
+                }
+            }
+            (PacketData::List(left_list), PacketData::List(right_list)) => {
+                (left_list.as_slice(), right_list.as_slice())
+            }
+            (PacketData::Integer(_), PacketData::List(right_list)) if promote_integers => {
+                (slice::from_ref(left_item), right_list.as_slice())
+            }
+            (PacketData::List(left_list), PacketData::Integer(_)) if promote_integers => {
+                (left_list.as_slice(), slice::from_ref(right_item))
+            }
+            (PacketData::Integer(_), PacketData::List(_)) => return Ordering::Less,
+            (PacketData::List(_), PacketData::Integer(_)) => return Ordering::Greater,
+        };
+        enclosing_lists.push((left, right));
+        left = left_list;
+        right = right_list;
+    }
+}
+
+/// The number of enclosing lists `cmp_packets` keeps track of without allocating
+const INLINE_DEPTH: usize = 32;
+
+/// The rest of the lists enclosing two compared items. The innermost `INLINE_DEPTH` are kept in
+/// a fixed-capacity array, and only the outer ones of deeper packets are moved to the heap.
+struct EnclosingLists<'a> {
+    inline: [(&'a [PacketData], &'a [PacketData]); INLINE_DEPTH],
+    inline_len: usize,
+    spilled: Vec<(&'a [PacketData], &'a [PacketData])>,
+}
+
+impl<'a> EnclosingLists<'a> {
+    fn new() -> Self {
+        Self {
+            inline: [(&[], &[]); INLINE_DEPTH],
+            inline_len: 0,
+            spilled: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, lists: (&'a [PacketData], &'a [PacketData])) {
+        if self.inline_len == INLINE_DEPTH {
+            // Keep the innermost lists inline, as they are the next ones to be popped
+            self.spilled.push(self.inline[0]);
+            self.inline.rotate_left(1);
+            self.inline_len -= 1;
+        }
+        self.inline[self.inline_len] = lists;
+        self.inline_len += 1;
+    }
+
+    fn pop(&mut self) -> Option<(&'a [PacketData], &'a [PacketData])> {
+        if self.inline_len == 0 {
+            return self.spilled.pop();
+        }
+        self.inline_len -= 1;
+        Some(self.inline[self.inline_len])
+    }
+}
+
+/// The order of the distress signal given by [`PacketData::packet_cmp`], where packets equal in
+/// that order are then sorted by their shape, integers first. This makes it a total order, where
+/// only identical packets are equal.
+impl Ord for PacketData {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.packet_cmp(other)
+            .then_with(|| self.structural_cmp(other))
+    }
+}
+
+impl PartialOrd for PacketData {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Drop nested lists one by one, as dropping them recursively could overflow the stack
+impl Drop for PacketData {
+    fn drop(&mut self) {
+        if let PacketData::List(list) = self {
+            let mut items = mem::take(list);
+            while let Some(mut item) = items.pop() {
+                if let PacketData::List(list) = &mut item {
+                    items.append(list);
+                }
+            }
+        }
+    }
+}
+
+impl From<u64> for PacketData {
+    fn from(value: u64) -> Self {
+        PacketData::Integer(value.into())
+    }
+}
+
+impl From<Vec<PacketData>> for PacketData {
+    fn from(list: Vec<PacketData>) -> Self {
+        PacketData::List(list)
+    }
+}
+
+/// Write the packet the way it is parsed, so `packet.to_string().parse()` returns it unchanged
+impl fmt::Display for PacketData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PacketData::Integer(value) => write!(f, "{}", value),
+            PacketData::List(list) => {
+                write!(f, "[")?;
+                for (idx, item) in list.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// Parse a packet written without whitespace, like `[1,[2,[]],3]`
+impl FromStr for PacketData {
+    type Err = PacketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s, false)
+    }
+}
+
+/// An error found at a byte offset of a packet
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PacketError {
+    pub offset: usize,
+    /// The length in bytes of the unexpected text, which is empty at the end of the packet
+    pub len: usize,
+    pub expected: String,
+}
+
+impl PacketError {
+    fn new(offset: usize, len: usize, expected: impl Into<String>) -> Self {
+        Self {
+            offset,
+            len,
+            expected: expected.into(),
+        }
+    }
+
+    /// Locate the error in the `source` the packet was parsed from
+    pub fn to_parse_error(&self, source: &str) -> ParseError {
+        ParseError::new(
+            source,
+            &source[self.offset..self.offset + self.len],
+            self.expected.as_str(),
+        )
+    }
+}
+
+impl fmt::Display for PacketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {} at byte {}", self.expected, self.offset)
+    }
+}
+
+impl Error for PacketError {}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TokenKind<'a> {
+    Open,
+    Close,
+    Comma,
+    Integer(&'a str),
+}
+
+/// A token of a packet, with the byte offset where it starts
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind<'a>,
+    pub offset: usize,
+}
+
+impl Token<'_> {
+    fn len(&self) -> usize {
+        match self.kind {
+            TokenKind::Integer(digits) => digits.len(),
+            _ => 1,
+        }
+    }
+}
+
+/// Split a packet into brackets, commas and integers
+pub struct Tokenizer<'a> {
+    input: &'a str,
+    offset: usize,
+    skip_whitespace: bool,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// With `skip_whitespace`, JSON whitespace between tokens is allowed
+    pub fn new(input: &'a str, skip_whitespace: bool) -> Self {
+        Self {
+            input,
+            offset: 0,
+            skip_whitespace,
+        }
+    }
+
+    /// The offset where the next token starts, or the length of the input after the last one
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<Token<'a>, PacketError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.input.as_bytes();
+        if self.skip_whitespace {
+            while bytes
+                .get(self.offset)
+                .is_some_and(|byte| b" \t\n\r".contains(byte))
+            {
+                self.offset += 1;
+            }
+        }
+        let start = self.offset;
+        let kind = match *bytes.get(start)? {
+            b'[' => TokenKind::Open,
+            b']' => TokenKind::Close,
+            b',' => TokenKind::Comma,
+            b'0'..=b'9' => {
+                let len = bytes[start..]
+                    .iter()
+                    .take_while(|byte| byte.is_ascii_digit())
+                    .count();
+                TokenKind::Integer(&self.input[start..start + len])
+            }
+            _ => {
+                let len = self.input[start..].chars().next().unwrap().len_utf8();
+                self.offset = self.input.len();
+                return Some(Err(PacketError::new(
+                    start,
+                    len,
+                    "`[`, `]`, `,` or a digit",
+                )));
+            }
+        };
+        let token = Token {
+            kind,
+            offset: start,
+        };
+        self.offset += token.len();
+        Some(Ok(token))
+    }
+}
+
+/// What the parser accepts next
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Expected {
+    Packet,
+    Item,
+    ItemOrClose,
+    CommaOrClose,
+    End,
+}
+
+impl Expected {
+    fn description(&self) -> &'static str {
+        match self {
+            Expected::Packet => "a packet",
+            Expected::Item => "an integer or `[`",
+            Expected::ItemOrClose => "an integer, `[` or `]`",
+            Expected::CommaOrClose => "`,` or `]`",
+            Expected::End => "the end of the packet",
+        }
+    }
+}
+
+/// Parse a single value, iteratively so deeply nested packets cannot overflow the stack
+fn parse(input: &str, skip_whitespace: bool) -> Result<PacketData, PacketError> {
+    let mut tokens = Tokenizer::new(input, skip_whitespace);
+    // The lists being parsed, the innermost one last
+    let mut lists: Vec<Vec<PacketData>> = vec![];
+    let mut expected = Expected::Packet;
+
+    loop {
+        let token = tokens
+            .next()
+            .transpose()?
+            .ok_or_else(|| PacketError::new(input.len(), 0, expected.description()))?;
+        let is_item_expected = expected != Expected::CommaOrClose;
+        let value = match token.kind {
+            TokenKind::Open if is_item_expected => {
+                lists.push(vec![]);
+                expected = Expected::ItemOrClose;
+                continue;
+            }
+            TokenKind::Integer(digits) if is_item_expected => {
+                let integer = Integer::from_digits(digits).ok_or_else(|| {
+                    PacketError::new(
+                        token.offset,
+                        token.len(),
+                        "an integer without leading zeros",
+                    )
+                })?;
+                PacketData::Integer(integer)
+            }
+            TokenKind::Close
+                if matches!(expected, Expected::ItemOrClose | Expected::CommaOrClose) =>
+            {
+                PacketData::List(lists.pop().unwrap())
+            }
+            TokenKind::Comma if expected == Expected::CommaOrClose => {
+                expected = Expected::Item;
+                continue;
+            }
+            _ => {
+                return Err(PacketError::new(
+                    token.offset,
+                    token.len(),
+                    expected.description(),
+                ))
+            }
+        };
+        match lists.last_mut() {
+            Some(list) => {
+                list.push(value);
+                expected = Expected::CommaOrClose;
+            }
+            None => {
+                return match tokens.next().transpose()? {
+                    Some(token) => Err(PacketError::new(
+                        token.offset,
+                        input.len() - token.offset,
+                        Expected::End.description(),
+                    )),
+                    None => Ok(value),
+                }
+            }
+        }
+    }
+}
+
+/// An error in one of the packets compared by [`cmp_packet_strs`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CompareError {
+    Left(PacketError),
+    Right(PacketError),
+}
+
+impl fmt::Display for CompareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompareError::Left(error) => write!(f, "left packet: {}", error),
+            CompareError::Right(error) => write!(f, "right packet: {}", error),
+        }
+    }
+}
+
+impl Error for CompareError {}
+
+/// Compare two packets written without whitespace like [`PacketData::packet_cmp`], reading them
+/// token by token without building them, nor allocating anything.
+///
+/// Reading stops as soon as the order is known, so only the beginning of the packets up to that
+/// point is checked.
+pub fn cmp_packet_strs(left: &str, right: &str) -> Result<Ordering, CompareError> {
+    let mut left = TokenStream::new(left).map_err(CompareError::Left)?;
+    let mut right = TokenStream::new(right).map_err(CompareError::Right)?;
+    loop {
+        let (advance_left, advance_right) = match (left.peek(), right.peek()) {
+            (None, None) => return Ok(Ordering::Equal),
+            (Some(TokenKind::Integer(left_digits)), Some(TokenKind::Integer(right_digits))) => {
+                match cmp_digits(left_digits, right_digits) {
+                    Ordering::Equal => (true, true),
+                    ordering => return Ok(ordering),
+                }
+            }
+            (Some(left_kind), Some(right_kind)) if left_kind == right_kind => (true, true),
+            (Some(TokenKind::Close), _) => return Ok(Ordering::Less),
+            (_, Some(TokenKind::Close)) => return Ok(Ordering::Greater),
+            (Some(TokenKind::Open), Some(TokenKind::Integer(_))) => {
+                right.promote();
+                (true, false)
+            }
+            (Some(TokenKind::Integer(_)), Some(TokenKind::Open)) => {
+                left.promote();
+                (false, true)
+            }
+            (left_kind, right_kind) => unreachable!(
+                "Packets out of step on {:?} and {:?}",
+                left_kind, right_kind
+            ),
+        };
+        if advance_left {
+            left.advance().map_err(CompareError::Left)?;
+        }
+        if advance_right {
+            right.advance().map_err(CompareError::Right)?;
+        }
+    }
+}
+
+/// The tokens of a packet, checked as they are read, where integers can be seen as lists
+/// containing only themselves
+struct TokenStream<'a> {
+    tokens: Tokenizer<'a>,
+    /// The next token, or `None` at the end of the packet
+    current: Option<Token<'a>>,
+    depth: usize,
+    expected: Expected,
+    /// The number of lists the current integer is wrapped in
+    wrapping_lists: usize,
+    /// The closing brackets of the lists the last integer was wrapped in, coming before
+    /// `current`
+    virtual_closes: usize,
+}
+
+impl<'a> TokenStream<'a> {
+    fn new(input: &'a str) -> Result<Self, PacketError> {
+        let mut stream = Self {
+            tokens: Tokenizer::new(input, false),
+            current: None,
+            depth: 0,
+            expected: Expected::Packet,
+            wrapping_lists: 0,
+            virtual_closes: 0,
+        };
+        stream.read_token()?;
+        Ok(stream)
+    }
+
+    fn peek(&self) -> Option<TokenKind<'a>> {
+        if self.virtual_closes > 0 {
+            Some(TokenKind::Close)
+        } else {
+            self.current.map(|token| token.kind)
+        }
+    }
+
+    /// See the current integer as a list containing only itself
+    fn promote(&mut self) {
+        self.wrapping_lists += 1;
+    }
+
+    fn advance(&mut self) -> Result<(), PacketError> {
+        if self.virtual_closes > 0 {
+            self.virtual_closes -= 1;
+            return Ok(());
+        }
+        if let Some(TokenKind::Integer(_)) = self.peek() {
+            self.virtual_closes = self.wrapping_lists;
+            self.wrapping_lists = 0;
+        }
+        self.read_token()
+    }
+
+    /// Read the next token, and check it can follow the previous one
+    fn read_token(&mut self) -> Result<(), PacketError> {
+        let offset = self.tokens.offset();
+        self.current = self.tokens.next().transpose()?;
+        let token = match self.current {
+            Some(token) => token,
+            None if self.expected == Expected::End => return Ok(()),
+            None => return Err(PacketError::new(offset, 0, self.expected.description())),
+        };
+        let error = |expected: Expected| {
+            PacketError::new(token.offset, token.len(), expected.description())
+        };
+        let is_item_expected = matches!(
+            self.expected,
+            Expected::Packet | Expected::Item | Expected::ItemOrClose
+        );
+        match token.kind {
+            TokenKind::Open if is_item_expected => {
+                self.depth += 1;
+                self.expected = Expected::ItemOrClose;
+            }
+            TokenKind::Integer(digits) if is_item_expected => {
+                if !is_canonical(digits) {
+                    return Err(PacketError::new(
+                        token.offset,
+                        token.len(),
+                        "an integer without leading zeros",
+                    ));
+                }
+                self.expected = self.get_expected_after_item();
+            }
+            TokenKind::Close
+                if matches!(
+                    self.expected,
+                    Expected::ItemOrClose | Expected::CommaOrClose
+                ) =>
+            {
+                self.depth -= 1;
+                self.expected = self.get_expected_after_item();
+            }
+            TokenKind::Comma if self.expected == Expected::CommaOrClose => {
+                self.expected = Expected::Item;
+            }
+            _ => return Err(error(self.expected)),
+        }
+        Ok(())
+    }
+
+    fn get_expected_after_item(&self) -> Expected {
+        if self.depth == 0 {
+            Expected::End
+        } else {
+            Expected::CommaOrClose
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        for packet in [
+            "[]",
+            "[1,[2,[3,[]]],0]",
+            "42",
+            "[123456789012345678901234567890]",
+        ] {
+            assert_eq!(packet.parse::<PacketData>().unwrap().to_string(), packet);
+        }
+        let packet: PacketData = "[[1],2]".parse().unwrap();
+        assert_eq!(
+            packet,
+            PacketData::List(vec![vec![1.into()].into(), 2.into()])
+        );
+
+        let parse_error = |packet: &str| packet.parse::<PacketData>().unwrap_err();
+        assert_eq!(parse_error(""), PacketError::new(0, 0, "a packet"));
+        assert_eq!(
+            parse_error("[1,]"),
+            PacketError::new(3, 1, "an integer or `[`")
+        );
+        assert_eq!(
+            parse_error("[1 ,2]"),
+            PacketError::new(2, 1, "`[`, `]`, `,` or a digit")
+        );
+        assert_eq!(parse_error("[[1]"), PacketError::new(4, 0, "`,` or `]`"));
+        assert_eq!(
+            parse_error("[1]]"),
+            PacketError::new(3, 1, "the end of the packet")
+        );
+        assert_eq!(
+            parse_error("[1,02]"),
+            PacketError::new(3, 2, "an integer without leading zeros")
+        );
+        assert_eq!(
+            parse_error("[1,é]").to_parse_error("[1,é]").text,
+            "é".to_string()
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let packet = PacketData::from_json(" [ 1, [2 ,\n[]], 3 ] ").unwrap();
+        assert_eq!(packet.to_json(), "[1,[2,[]],3]");
+        assert!(PacketData::from_json("[1, -2]").is_err());
+        assert!(PacketData::from_json("[1.5]").is_err());
+    }
+
+    #[test]
+    fn test_ordering() {
+        let packet = |s: &str| s.parse::<PacketData>().unwrap();
+        assert!(packet("[1,1,3,1,1]") < packet("[1,1,5,1,1]"));
+        assert!(packet("[[1],[2,3,4]]") < packet("[[1],4]"));
+        assert!(packet("[9]") > packet("[[8,7,6]]"));
+        assert!(packet("[]") < packet("[3]"));
+        assert!(packet("[[[]]]") > packet("[[]]"));
+        assert!(packet("[99999999999999999999]") > packet("[100]"));
+
+        // Equal packets for the distress signal are still ordered by their shape
+        assert_eq!(packet("1").packet_cmp(&packet("[[1]]")), Ordering::Equal);
+        assert!(packet("1") < packet("[[1]]"));
+        assert!(packet("[1]") < packet("[[1]]"));
+        assert_eq!(packet("[[1]]").cmp(&packet("[[1]]")), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let depth = 100_000;
+        let deep = format!("{}1{}", "[".repeat(depth), "]".repeat(depth));
+        let packet: PacketData = deep.parse().unwrap();
+        let one = PacketData::from(1);
+        assert_eq!(packet.packet_cmp(&one), Ordering::Equal);
+        assert_eq!(packet.cmp(&one), Ordering::Greater);
+        assert_eq!(cmp_packet_strs(&deep, "2"), Ok(Ordering::Less));
+
+        // Items after lists nested around the number kept without allocating
+        let nested = |depth: usize, last: u64| {
+            let lists = format!("{}1{}", "[".repeat(depth), "]".repeat(depth));
+            format!("[{},{}]", lists, last)
+                .parse::<PacketData>()
+                .unwrap()
+        };
+        for depth in INLINE_DEPTH - 2..INLINE_DEPTH + 2 {
+            assert_eq!(
+                nested(depth, 2).packet_cmp(&nested(depth, 3)),
+                Ordering::Less
+            );
+            assert_eq!(nested(depth, 2).cmp(&nested(depth, 2)), Ordering::Equal);
+        }
+    }
+
+    #[test]
+    fn test_cmp_packet_strs() {
+        let pairs = [
+            ("[1,1,3,1,1]", "[1,1,5,1,1]"),
+            ("[[1],[2,3,4]]", "[[1],4]"),
+            ("[9]", "[[8,7,6]]"),
+            ("[[4,4],4,4]", "[[4,4],4,4,4]"),
+            ("[7,7,7,7]", "[7,7,7]"),
+            ("[]", "[3]"),
+            ("[[[]]]", "[[]]"),
+            ("[1,[2,[3,[4,[5,6,7]]]],8,9]", "[1,[2,[3,[4,[5,6,0]]]],8,9]"),
+            ("[[1],2]", "[1,2]"),
+            ("1", "[[1]]"),
+            ("[[]]", "[]"),
+            ("[10]", "[9,1]"),
+        ];
+        for (left, right) in pairs {
+            let expected = left
+                .parse::<PacketData>()
+                .unwrap()
+                .packet_cmp(&right.parse().unwrap());
+            assert_eq!(
+                cmp_packet_strs(left, right),
+                Ok(expected),
+                "{} {}",
+                left,
+                right
+            );
+            assert_eq!(
+                cmp_packet_strs(right, left),
+                Ok(expected.reverse()),
+                "{} {}",
+                right,
+                left
+            );
+        }
+
+        assert_eq!(
+            cmp_packet_strs("[1,,2]", "[1,3]"),
+            Err(CompareError::Left(PacketError::new(
+                3,
+                1,
+                "an integer or `[`"
+            )))
+        );
+        assert_eq!(
+            cmp_packet_strs("[1]", "[1"),
+            Err(CompareError::Right(PacketError::new(2, 0, "`,` or `]`")))
+        );
+        // The order is known before the invalid end of the right packet
+        assert_eq!(cmp_packet_strs("[1]", "[2,x"), Ok(Ordering::Less));
+    }
+}
@@ -0,0 +1,140 @@
+use std::error::Error;
+use std::fmt;
+
+/// An error raised when a puzzle input does not match the expected format.
+///
+/// `line` and `column` start at 1, and locate `text` in the input.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub expected: String,
+}
+
+impl ParseError {
+    /// Create an error for the `text` found in `source`.
+    ///
+    /// If `text` is a slice of `source`, the error is located where the slice starts, otherwise
+    /// at its first occurrence. Errors created from a part of the input can be moved to their
+    /// actual line with `shift`.
+    pub fn new(source: &str, text: &str, expected: impl Into<String>) -> Self {
+        let offset = get_slice_offset(source, text)
+            .or_else(|| source.find(text))
+            .unwrap_or(0);
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            text: text.to_string(),
+            expected: expected.into(),
+        }
+    }
+
+    /// Move the error `lines` lines down, when its source starts after the first line.
+    pub fn shift(mut self, lines: usize) -> Self {
+        self.line += lines;
+        self
+    }
+
+    /// Locate an error created from `slice` in the `source` it was taken from.
+    pub fn within(mut self, source: &str, slice: &str) -> Self {
+        let start = ParseError::new(source, slice, "");
+        if self.line == 1 {
+            self.column += start.column - 1;
+        }
+        self.line += start.line - 1;
+        self
+    }
+
+    /// Format the error with the line of the input it occurred on, pointing at the offending text.
+    /// `origin` names where the input comes from, like a file path.
+    pub fn render(&self, origin: &str, input: &str) -> String {
+        let mut rendered = format!(
+            "error: {}\n --> {}:{}:{}",
+            self, origin, self.line, self.column
+        );
+        if let Some(line) = input.lines().nth(self.line - 1) {
+            let margin = " ".repeat(self.line.to_string().len());
+            let underline = "^".repeat(self.text.chars().count().max(1));
+            rendered += &format!(
+                "\n{margin} |\n{} | {}\n{margin} | {}{}",
+                self.line,
+                line,
+                " ".repeat(self.column - 1),
+                underline,
+                margin = margin,
+            );
+        }
+        rendered
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.text.is_empty() {
+            write!(f, "expected {}, found nothing", self.expected)
+        } else {
+            write!(f, "expected {}, found `{}`", self.expected, self.text)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Returns the byte offset of `slice` in `source`, if `slice` points inside it.
+pub fn get_slice_offset(source: &str, slice: &str) -> Option<usize> {
+    let start = source.as_ptr() as usize;
+    let slice_start = slice.as_ptr() as usize;
+    if slice_start >= start && slice_start + slice.len() <= start + source.len() {
+        Some(slice_start - start)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_locates_slice() {
+        let input = "1 2\n3 x 4\n";
+        let offending = &input[6..7];
+        let error = ParseError::new(input, offending, "a number");
+        assert_eq!(error.line, 2);
+        assert_eq!(error.column, 3);
+        assert_eq!(error.to_string(), "expected a number, found `x`");
+    }
+
+    #[test]
+    fn test_new_with_owned_text() {
+        let error = ParseError::new("ab\ncd", "d", "a letter").shift(4);
+        assert_eq!((error.line, error.column), (6, 2));
+
+        let error = ParseError::new("ab\ncd", "", "a line");
+        assert_eq!((error.line, error.column), (1, 1));
+        assert_eq!(error.to_string(), "expected a line, found nothing");
+    }
+
+    #[test]
+    fn test_within() {
+        let input = "a: 1\nb: [1,x]";
+        let packet = &input[8..];
+        let error = ParseError::new(packet, &packet[3..4], "a digit").within(input, packet);
+        assert_eq!((error.line, error.column), (2, 7));
+        assert_eq!(error.text, "x");
+    }
+
+    #[test]
+    fn test_render() {
+        let input = "move 1 from 2 to 3\nmove x from 2 to 3";
+        let error = ParseError::new(input, &input[24..25], "a number");
+        assert_eq!(
+            error.render("input.txt", input),
+            "error: expected a number, found `x`\n --> input.txt:2:6\n  |\n2 | move x from 2 to 3\n  |      ^"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// An exact fraction, always stored in lowest terms with a positive denominator
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational::from_integer(0);
+    pub const ONE: Rational = Rational::from_integer(1);
+
+    /// Panics if `denominator` is 0, or if the fraction does not fit once in lowest terms
+    pub fn new(numerator: i128, denominator: i128) -> Self {
+        assert_ne!(denominator, 0, "A rational cannot have a null denominator");
+        Self::checked_new(numerator, denominator)
+            .unwrap_or_else(|| panic!("{}/{} overflows", numerator, denominator))
+    }
+
+    /// Returns `None` if `denominator` is 0, or if the fraction does not fit once in lowest terms
+    pub fn checked_new(numerator: i128, denominator: i128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        // Dividing the absolute values works even when the divisor does not fit in an `i128`
+        let divisor = gcd(numerator, denominator);
+        let magnitude = numerator.unsigned_abs() / divisor;
+        Some(Self {
+            numerator: if (numerator < 0) == (denominator < 0) {
+                i128::try_from(magnitude).ok()?
+            } else {
+                0i128.checked_sub_unsigned(magnitude)?
+            },
+            denominator: i128::try_from(denominator.unsigned_abs() / divisor).ok()?,
+        })
+    }
+
+    pub const fn from_integer(value: i128) -> Self {
+        Self {
+            numerator: value,
+            denominator: 1,
+        }
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == 1
+    }
+
+    /// Returns the value if it is a whole number
+    pub fn to_integer(&self) -> Option<i128> {
+        Some(self.numerator).filter(|_| self.is_integer())
+    }
+
+    /// Returns `None` instead of overflowing
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        // Both fractions are brought to the least common multiple of their denominators
+        let divisor = i128::try_from(gcd(self.denominator, other.denominator)).ok()?;
+        let (self_factor, other_factor) = (other.denominator / divisor, self.denominator / divisor);
+        Self::checked_new(
+            self.numerator
+                .checked_mul(self_factor)?
+                .checked_add(other.numerator.checked_mul(other_factor)?)?,
+            self.denominator.checked_mul(self_factor)?,
+        )
+    }
+
+    /// Returns `None` instead of overflowing
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.checked_add(other.checked_neg()?)
+    }
+
+    /// Returns `None` instead of overflowing
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        // Simplifying before multiplying keeps the products as small as possible
+        let self_divisor = i128::try_from(gcd(self.numerator, other.denominator)).ok()?;
+        let other_divisor = i128::try_from(gcd(other.numerator, self.denominator)).ok()?;
+        Self::checked_new(
+            (self.numerator / self_divisor).checked_mul(other.numerator / other_divisor)?,
+            (self.denominator / other_divisor).checked_mul(other.denominator / self_divisor)?,
+        )
+    }
+
+    /// Returns `None` instead of panicking when dividing by zero, or overflowing
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        self.checked_mul(Self::checked_new(other.denominator, other.numerator)?)
+    }
+
+    /// Returns `None` instead of overflowing
+    pub fn checked_neg(self) -> Option<Self> {
+        Some(Self {
+            numerator: self.numerator.checked_neg()?,
+            denominator: self.denominator,
+        })
+    }
+}
+
+fn gcd(a: i128, b: i128) -> u128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Self::from_integer(value.into())
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.checked_add(other)
+            .unwrap_or_else(|| panic!("{} + {} overflows", self, other))
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.checked_sub(other)
+            .unwrap_or_else(|| panic!("{} - {} overflows", self, other))
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        self.checked_mul(other)
+            .unwrap_or_else(|| panic!("{} * {} overflows", self, other))
+    }
+}
+
+/// Panics when dividing by zero, like integers do
+impl Div for Rational {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        assert_ne!(other, Self::ZERO, "Cannot divide {} by zero", self);
+        self.checked_div(other)
+            .unwrap_or_else(|| panic!("{} / {} overflows", self, other))
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.checked_neg()
+            .unwrap_or_else(|| panic!("-{} overflows", self))
+    }
+}
+
+impl Ord for Rational {
+    /// Compares the integer parts, then the inverses of the fractional parts, so nothing can
+    /// overflow
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (mut left, mut right) = (
+            (self.numerator, self.denominator),
+            (other.numerator, other.denominator),
+        );
+        let mut reversed = false;
+        loop {
+            // The denominators are positive, so the remainders are between 0 and them
+            let ordering = match (left.0.div_euclid(left.1), right.0.div_euclid(right.1)) {
+                (left_integer, right_integer) if left_integer != right_integer => {
+                    left_integer.cmp(&right_integer)
+                }
+                _ => match (left.0.rem_euclid(left.1), right.0.rem_euclid(right.1)) {
+                    (0, 0) => Ordering::Equal,
+                    (0, _) => Ordering::Less,
+                    (_, 0) => Ordering::Greater,
+                    // The larger fractional part has the smaller inverse
+                    (left_rest, right_rest) => {
+                        (left, right) = ((left.1, left_rest), (right.1, right_rest));
+                        reversed = !reversed;
+                        continue;
+                    }
+                },
+            };
+            return if reversed {
+                ordering.reverse()
+            } else {
+                ordering
+            };
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Render the fraction as `numerator/denominator`, or as an integer if it is whole
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization() {
+        assert_eq!(Rational::new(6, -4), Rational::new(-3, 2));
+        assert_eq!(Rational::new(0, -5), Rational::ZERO);
+        assert_eq!(Rational::new(-3, 2).to_string(), "-3/2");
+        assert_eq!(Rational::new(8, 4).to_integer(), Some(2));
+        assert_eq!(Rational::new(1, 3).to_integer(), None);
+    }
+
+    #[test]
+    fn test_operations() {
+        let (half, third) = (Rational::new(1, 2), Rational::new(1, 3));
+        assert_eq!(half + third, Rational::new(5, 6));
+        assert_eq!(half - third, Rational::new(1, 6));
+        assert_eq!(half * third, Rational::new(1, 6));
+        assert_eq!(half / third, Rational::new(3, 2));
+        assert_eq!(-half, Rational::new(-1, 2));
+        assert_eq!(half.checked_div(Rational::ZERO), None);
+        assert!(third < half && -half < third);
+        for (a, b, c, d) in itertools::iproduct!(-6..=6, 1..=6, -6..=6, 1..=6) {
+            let expected = (a * d).cmp(&(c * b));
+            assert_eq!(Rational::new(a, b).cmp(&Rational::new(c, d)), expected);
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        let (max, min) = (
+            Rational::from_integer(i128::MAX),
+            Rational::from_integer(i128::MIN),
+        );
+        assert_eq!(Rational::checked_new(i128::MIN, -1), None);
+        assert_eq!(
+            Rational::checked_new(i128::MIN, i128::MIN),
+            Some(Rational::ONE)
+        );
+        assert_eq!(max.checked_add(Rational::ONE), None);
+        assert_eq!(min.checked_sub(Rational::ONE), None);
+        assert_eq!(max.checked_mul(max), None);
+        assert_eq!(min.checked_neg(), None);
+        assert_eq!(Rational::ONE.checked_div(min), None);
+        assert_eq!(max.checked_div(max), Some(Rational::ONE));
+
+        // Results that fit are found even when intermediate products would not
+        let big = Rational::new(i128::MAX, 2);
+        assert_eq!(
+            big.checked_mul(Rational::new(2, i128::MAX)),
+            Some(Rational::ONE)
+        );
+        assert_eq!(
+            big.checked_add(Rational::new(-1, 2)),
+            Some(Rational::from_integer(i128::MAX / 2))
+        );
+        assert!(Rational::new(i128::MAX - 1, i128::MAX) < Rational::new(i128::MAX, i128::MAX - 2));
+        assert!(min < max && Rational::new(1, i128::MAX) < Rational::new(1, i128::MAX - 1));
+        assert_eq!(
+            Rational::new(i128::MAX, 3).cmp(&Rational::new(i128::MAX, 3)),
+            Ordering::Equal
+        );
+    }
+}
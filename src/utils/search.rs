@@ -0,0 +1,319 @@
+use ahash::AHashMap;
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// The costs of the edges of a graph, `Default` being a null cost
+pub trait Cost: Copy + Ord + Add<Output = Self> + Default {}
+
+impl<T: Copy + Ord + Add<Output = T> + Default> Cost for T {}
+
+/// The outcome of a search from a start node: every node reached with its cost, and the goal
+/// if one was found
+#[derive(Debug, Clone)]
+pub struct Search<N, C> {
+    start: N,
+    goal: Option<N>,
+    /// The best known cost of each reached node, and the node it was reached from
+    reached: AHashMap<N, (C, Option<N>)>,
+    visited: usize,
+}
+
+impl<N, C> Search<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Cost,
+{
+    fn new(start: N) -> Self {
+        let mut reached = AHashMap::new();
+        reached.insert(start.clone(), (C::default(), None));
+        Self {
+            start,
+            goal: None,
+            reached,
+            visited: 0,
+        }
+    }
+
+    pub fn start(&self) -> &N {
+        &self.start
+    }
+
+    pub fn goal(&self) -> Option<&N> {
+        self.goal.as_ref()
+    }
+
+    /// Returns the cost of the best path found to the goal
+    pub fn goal_cost(&self) -> Option<C> {
+        self.goal.as_ref().and_then(|goal| self.cost(goal))
+    }
+
+    /// Returns the cost of the best path found to a node, if it was reached
+    pub fn cost(&self, node: &N) -> Option<C> {
+        self.reached.get(node).map(|(cost, _)| *cost)
+    }
+
+    pub fn is_reached(&self, node: &N) -> bool {
+        self.reached.contains_key(node)
+    }
+
+    /// Iterate over every reached node, the start included
+    pub fn reached(&self) -> impl Iterator<Item = &N> {
+        self.reached.keys()
+    }
+
+    /// The number of nodes reached, the start included
+    pub fn reached_count(&self) -> usize {
+        self.reached.len()
+    }
+
+    /// The number of nodes whose successors were explored
+    pub fn visited_count(&self) -> usize {
+        self.visited
+    }
+
+    /// Returns the best path found from the start to a node, both included
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        let mut path = vec![node.clone()];
+        let mut current = node;
+        while let (_, Some(parent)) = self.reached.get(current)? {
+            path.push(parent.clone());
+            current = parent;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Returns the best path found from the start to the goal, both included
+    pub fn path(&self) -> Option<Vec<N>> {
+        self.goal.as_ref().and_then(|goal| self.path_to(goal))
+    }
+}
+
+/// Explore the graph breadth-first from `start`, until a node matching `is_goal` is reached.
+/// Each edge costs 1, so the cost of a node is its distance to the start.
+pub fn bfs<N, I, S, G>(start: N, mut successors: S, mut is_goal: G) -> Search<N, usize>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+    S: FnMut(&N) -> I,
+    G: FnMut(&N) -> bool,
+{
+    let mut search = Search::new(start.clone());
+    if is_goal(&start) {
+        search.goal = Some(start);
+        return search;
+    }
+    let mut frontier = VecDeque::new();
+    frontier.push_back(start);
+
+    while let Some(node) = frontier.pop_front() {
+        search.visited += 1;
+        let cost = search.reached[&node].0 + 1;
+        for next in successors(&node) {
+            if let Entry::Vacant(entry) = search.reached.entry(next.clone()) {
+                entry.insert((cost, Some(node.clone())));
+                if is_goal(&next) {
+                    search.goal = Some(next);
+                    return search;
+                }
+                frontier.push_back(next);
+            }
+        }
+    }
+    search
+}
+
+/// Explore breadth-first every node which can be reached from `start`
+pub fn bfs_reach<N, I, S>(start: N, successors: S) -> Search<N, usize>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+    S: FnMut(&N) -> I,
+{
+    bfs(start, successors, |_| false)
+}
+
+/// Find the cheapest path from `start` to a node matching `is_goal`.
+/// `successors` returns the nodes linked to a node, with the cost of each link.
+pub fn dijkstra<N, C, I, S, G>(start: N, successors: S, is_goal: G) -> Search<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+    S: FnMut(&N) -> I,
+    G: FnMut(&N) -> bool,
+{
+    astar(start, successors, |_| C::default(), is_goal)
+}
+
+/// Find the cheapest path from `start` to a node matching `is_goal`, exploring first the nodes
+/// which look closer to the goal.
+/// `heuristic` estimates the cost from a node to the goal. It must never overestimate it, or the
+/// path found may not be the cheapest.
+pub fn astar<N, C, I, S, H, G>(
+    start: N,
+    mut successors: S,
+    mut heuristic: H,
+    mut is_goal: G,
+) -> Search<N, C>
+where
+    N: Hash + Eq + Clone,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+    S: FnMut(&N) -> I,
+    H: FnMut(&N) -> C,
+    G: FnMut(&N) -> bool,
+{
+    let mut search = Search::new(start.clone());
+    let mut frontier = BinaryHeap::new();
+    frontier.push(FrontierNode {
+        priority: heuristic(&start),
+        cost: C::default(),
+        node: start,
+    });
+
+    while let Some(FrontierNode { cost, node, .. }) = frontier.pop() {
+        if cost > search.reached[&node].0 {
+            // A cheaper path to this node was found after it was queued
+            continue;
+        }
+        if is_goal(&node) {
+            search.goal = Some(node);
+            return search;
+        }
+        search.visited += 1;
+        for (next, link_cost) in successors(&node) {
+            let next_cost = cost + link_cost;
+            let is_cheaper = search
+                .reached
+                .get(&next)
+                .is_none_or(|(known_cost, _)| next_cost < *known_cost);
+            if is_cheaper {
+                search
+                    .reached
+                    .insert(next.clone(), (next_cost, Some(node.clone())));
+                frontier.push(FrontierNode {
+                    priority: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+    search
+}
+
+/// A node waiting to be explored, the lowest priority coming first out of the heap
+struct FrontierNode<N, C> {
+    priority: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for FrontierNode<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N, C: Ord> Eq for FrontierNode<N, C> {}
+
+impl<N, C: Ord> PartialOrd for FrontierNode<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> Ord for FrontierNode<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::grid::{Grid, Position};
+
+    fn get_maze() -> Grid<bool> {
+        // The lower right corner can be reached from both sides of the walls
+        Grid::parse("...#\n.#.#\n.#..\n.##.\n....", "`#` or `.`", |c| {
+            Some(c == '#').filter(|_| "#.".contains(c))
+        })
+        .unwrap()
+    }
+
+    fn get_open_neighbours(maze: &Grid<bool>, position: Position) -> Vec<Position> {
+        maze.neighbours(position)
+            .filter(|neighbour| !maze[*neighbour])
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let maze = get_maze();
+        let search = bfs(
+            (2, 0),
+            |position| get_open_neighbours(&maze, *position),
+            |position| *position == (3, 4),
+        );
+        assert_eq!(search.goal(), Some(&(3, 4)));
+        assert_eq!(search.goal_cost(), Some(5));
+        let path = search.path().unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!((path[0], path[5]), ((2, 0), (3, 4)));
+        assert!(search.visited_count() <= search.reached_count());
+
+        let search = bfs((0, 0), |_| vec![], |position| *position == (3, 4));
+        assert_eq!(search.goal(), None);
+        assert_eq!(search.path(), None);
+        assert_eq!(search.path_to(&(0, 0)), Some(vec![(0, 0)]));
+    }
+
+    #[test]
+    fn test_bfs_reach() {
+        let maze = get_maze();
+        let search = bfs_reach((0, 0), |position| get_open_neighbours(&maze, *position));
+        assert_eq!(search.reached_count(), 14);
+        assert!(!search.is_reached(&(3, 0)));
+        assert_eq!(search.cost(&(2, 0)), Some(2));
+        assert_eq!(search.cost(&(3, 4)), Some(7));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // The direct link from 'a' to 'c' is more expensive than going through 'b'
+        let links = |node: &char| match node {
+            'a' => vec![('b', 1), ('c', 5)],
+            'b' => vec![('c', 2), ('d', 7)],
+            'c' => vec![('d', 1)],
+            _ => vec![],
+        };
+        let search = dijkstra('a', links, |node| *node == 'd');
+        assert_eq!(search.goal_cost(), Some(4));
+        assert_eq!(search.path(), Some(vec!['a', 'b', 'c', 'd']));
+        let search = dijkstra('a', links, |node| *node == 'e');
+        assert_eq!(search.goal_cost(), None);
+        assert_eq!(search.cost(&'d'), Some(4));
+    }
+
+    #[test]
+    fn test_astar() {
+        let maze = get_maze();
+        let goal = (3, 4);
+        let successors = |position: &Position| {
+            get_open_neighbours(&maze, *position)
+                .into_iter()
+                .map(|neighbour| (neighbour, 1))
+        };
+        let distance = |(x, y): &Position| x.abs_diff(goal.0) + y.abs_diff(goal.1);
+        let search = astar((2, 0), successors, distance, |position| *position == goal);
+        assert_eq!(search.goal_cost(), Some(5));
+        let search_without_heuristic = dijkstra((2, 0), successors, |position| *position == goal);
+        assert_eq!(search_without_heuristic.goal_cost(), Some(5));
+        assert!(search.visited_count() <= search_without_heuristic.visited_count());
+    }
+}